@@ -0,0 +1,415 @@
+use crate::app::{AlbumTableContext, App, RouteId};
+use crate::network::IoEvent;
+use crate::ui::util::SMALL_TERMINAL_HEIGHT;
+use anyhow::{anyhow, Result};
+use crossterm::{cursor::MoveTo, queue};
+use image::{imageops::FilterType, RgbImage};
+use rspotify::model::{image::Image, PlayingItem};
+use std::{
+  cell::RefCell,
+  collections::{HashMap, HashSet, VecDeque},
+  env,
+  io::{self, Write},
+  path::{Path, PathBuf},
+};
+use tui::{backend::Backend, layout::Rect, Terminal};
+
+// Covers are scaled down to fit this many pixels when they are loaded
+const IMAGE_SIZE: u32 = 256;
+// The size Spotify offers that is closest to this is downloaded
+const PREFERRED_WIDTH: i64 = 300;
+const MAX_CACHED_IMAGES: usize = 32;
+// Used for sixel when the terminal does not report the size of its cells
+const DEFAULT_CELL_SIZE: (u32, u32) = (10, 20);
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GraphicsProtocol {
+  // Two pixels per cell with the upper half block character, works in every truecolor terminal
+  HalfBlocks,
+  Kitty,
+  Sixel,
+}
+
+impl GraphicsProtocol {
+  pub fn parse(name: &str) -> Result<Option<Self>> {
+    match name {
+      "auto" => Ok(None),
+      "halfblocks" => Ok(Some(GraphicsProtocol::HalfBlocks)),
+      "kitty" => Ok(Some(GraphicsProtocol::Kitty)),
+      "sixel" => Ok(Some(GraphicsProtocol::Sixel)),
+      _ => Err(anyhow!(
+        "Unknown album art protocol {}, expected auto, halfblocks, kitty or sixel",
+        name
+      )),
+    }
+  }
+
+  /// Guesses the protocol from the environment of the terminal
+  pub fn detect() -> Self {
+    let term = env::var("TERM").unwrap_or_default();
+    let term_program = env::var("TERM_PROGRAM").unwrap_or_default();
+    if env::var_os("KITTY_WINDOW_ID").is_some()
+      || term == "xterm-kitty"
+      || term_program == "WezTerm"
+      || term_program == "ghostty"
+    {
+      GraphicsProtocol::Kitty
+    } else if term.contains("sixel") || term == "foot" || term == "mlterm" {
+      GraphicsProtocol::Sixel
+    } else {
+      GraphicsProtocol::HalfBlocks
+    }
+  }
+}
+
+/// A cover that the terminal draws over the cells of `area`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Placement {
+  pub area: Rect,
+  pub url: String,
+}
+
+pub struct AlbumArt {
+  images: HashMap<String, RgbImage>,
+  // Oldest first, to forget images once there are too many
+  loaded: VecDeque<String>,
+  requested: HashSet<String>,
+  detected_protocol: GraphicsProtocol,
+  // Collected while drawing a frame with a graphics protocol
+  pub placements: RefCell<Vec<Placement>>,
+  written: Vec<Placement>,
+}
+
+impl Default for AlbumArt {
+  fn default() -> Self {
+    AlbumArt {
+      images: HashMap::new(),
+      loaded: VecDeque::new(),
+      requested: HashSet::new(),
+      detected_protocol: GraphicsProtocol::detect(),
+      placements: RefCell::new(vec![]),
+      written: vec![],
+    }
+  }
+}
+
+impl AlbumArt {
+  pub fn get(&self, url: &str) -> Option<&RgbImage> {
+    self.images.get(url)
+  }
+
+  pub fn insert(&mut self, url: String, image: RgbImage) {
+    if self.loaded.len() >= MAX_CACHED_IMAGES {
+      if let Some(oldest) = self.loaded.pop_front() {
+        self.images.remove(&oldest);
+        self.requested.remove(&oldest);
+      }
+    }
+    self.loaded.push_back(url.clone());
+    self.images.insert(url, image);
+  }
+
+  /// Returns true the first time an image is asked for
+  fn request(&mut self, url: &str) -> bool {
+    self.requested.insert(url.to_string())
+  }
+}
+
+pub fn get_protocol(app: &App) -> GraphicsProtocol {
+  app
+    .user_config
+    .behavior
+    .album_art_protocol
+    .unwrap_or(app.album_art.detected_protocol)
+}
+
+// Small terminals have no room to spare for covers
+pub fn is_shown(app: &App) -> bool {
+  app.user_config.behavior.show_album_art && app.size.height > SMALL_TERMINAL_HEIGHT
+}
+
+pub fn get_image_url(images: &[Image]) -> Option<&str> {
+  images
+    .iter()
+    .min_by_key(|image| (i64::from(image.width.unwrap_or(0)) - PREFERRED_WIDTH).abs())
+    .map(|image| image.url.as_str())
+}
+
+pub fn get_playing_image_url(app: &App) -> Option<&str> {
+  match app.current_playback_context.as_ref()?.item.as_ref()? {
+    PlayingItem::Track(track) => get_image_url(&track.album.images),
+    PlayingItem::Episode(episode) => get_image_url(&episode.images),
+  }
+}
+
+pub fn get_album_image_url(app: &App) -> Option<&str> {
+  match app.album_table_context {
+    AlbumTableContext::Simplified => app
+      .selected_album_simplified
+      .as_ref()
+      .and_then(|selected| get_image_url(&selected.album.images)),
+    AlbumTableContext::Full => app
+      .selected_album_full
+      .as_ref()
+      .and_then(|selected| get_image_url(&selected.album.images)),
+  }
+}
+
+/// Fetches the covers that the next frame shows
+pub fn request_images(app: &mut App) {
+  if !is_shown(app) {
+    return;
+  }
+  let mut urls = vec![get_playing_image_url(app).map(String::from)];
+  if app.get_current_route().id == RouteId::AlbumTracks {
+    urls.push(get_album_image_url(app).map(String::from));
+  }
+  for url in urls.into_iter().flatten() {
+    if app.album_art.request(&url) {
+      app.dispatch(IoEvent::GetAlbumArt(url));
+    }
+  }
+}
+
+/// The file of the disk cache for an image, named after the id at the end of its url
+pub fn get_cache_path(dir: &Path, url: &str) -> PathBuf {
+  let name: String = url
+    .rsplit('/')
+    .next()
+    .unwrap_or_default()
+    .chars()
+    .filter(|c| c.is_ascii_alphanumeric())
+    .collect();
+  dir.join(format!("{}.jpg", name))
+}
+
+pub fn decode(bytes: &[u8]) -> Result<RgbImage> {
+  let image = image::load_from_memory(bytes)?;
+  Ok(image.thumbnail(IMAGE_SIZE, IMAGE_SIZE).to_rgb8())
+}
+
+/// The largest square that fits into the area, with cells twice as high as they are wide
+pub fn fit(area: Rect) -> Rect {
+  let height = area.height.min(area.width / 2);
+  Rect {
+    width: height * 2,
+    height,
+    ..area
+  }
+}
+
+/// Draws the covers of the last frame with the graphics protocol of the terminal. Returns true
+/// if the screen was cleared to remove covers, so the frame has to be drawn again
+pub fn write_graphics<B>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<bool>
+where
+  B: Backend + Write,
+{
+  let protocol = get_protocol(app);
+  let album_art = &mut app.album_art;
+  let placements = album_art.placements.take();
+  if placements == album_art.written {
+    return Ok(false);
+  }
+
+  match protocol {
+    GraphicsProtocol::HalfBlocks => {}
+    GraphicsProtocol::Kitty => {
+      let out = terminal.backend_mut();
+      // Removes the covers shown before
+      write!(out, "\x1b_Ga=d,q=2\x1b\\")?;
+      for placement in &placements {
+        if let Some(image) = album_art.images.get(&placement.url) {
+          queue!(out, MoveTo(placement.area.x, placement.area.y))?;
+          write!(out, "{}", encode_kitty(image, placement.area))?;
+        }
+      }
+      Write::flush(out)?;
+    }
+    GraphicsProtocol::Sixel => {
+      // Sixel images are part of the text, only redrawing the cells removes them
+      if !album_art.written.is_empty() {
+        album_art.written.clear();
+        terminal.clear()?;
+        return Ok(true);
+      }
+      let (cell_width, cell_height) = get_cell_size();
+      let out = terminal.backend_mut();
+      for placement in &placements {
+        if let Some(image) = album_art.images.get(&placement.url) {
+          let image = image::imageops::resize(
+            image,
+            u32::from(placement.area.width) * cell_width,
+            u32::from(placement.area.height) * cell_height,
+            FilterType::Triangle,
+          );
+          queue!(out, MoveTo(placement.area.x, placement.area.y))?;
+          write!(out, "{}", encode_sixel(&image))?;
+        }
+      }
+      Write::flush(out)?;
+    }
+  }
+  album_art.written = placements;
+  Ok(false)
+}
+
+// Sends the pixels as RGB and lets the terminal scale them to the cells of the area
+fn encode_kitty(image: &RgbImage, area: Rect) -> String {
+  let data = base64::encode(image.as_raw());
+  let chunks: Vec<&[u8]> = data.as_bytes().chunks(KITTY_CHUNK_SIZE).collect();
+  let mut out = String::new();
+  for (i, chunk) in chunks.iter().enumerate() {
+    let more = if i + 1 < chunks.len() { 1 } else { 0 };
+    let chunk = String::from_utf8_lossy(chunk);
+    if i == 0 {
+      out.push_str(&format!(
+        "\x1b_Ga=T,f=24,s={},v={},c={},r={},C=1,q=2,m={};{}\x1b\\",
+        image.width(),
+        image.height(),
+        area.width,
+        area.height,
+        more,
+        chunk
+      ));
+    } else {
+      out.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+    }
+  }
+  out
+}
+
+// Uses the 216 colors of a 6x6x6 cube, which sixel terminals have enough registers for
+fn encode_sixel(image: &RgbImage) -> String {
+  let (width, height) = image.dimensions();
+  let level = |value: u8| (u32::from(value) * 5 + 127) / 255;
+  let colors: Vec<u32> = image
+    .pixels()
+    .map(|p| level(p[0]) * 36 + level(p[1]) * 6 + level(p[2]))
+    .collect();
+
+  let mut out = format!("\x1bPq\"1;1;{};{}", width, height);
+  let mut used: Vec<u32> = colors.clone();
+  used.sort_unstable();
+  used.dedup();
+  for color in &used {
+    let percent = |level: u32| level * 100 / 5;
+    out.push_str(&format!(
+      "#{};2;{};{};{}",
+      color,
+      percent(color / 36),
+      percent(color / 6 % 6),
+      percent(color % 6)
+    ));
+  }
+
+  for band in (0..height).step_by(6) {
+    let rows = (height - band).min(6);
+    let mut band_colors: Vec<u32> = (band..band + rows)
+      .flat_map(|y| &colors[(y * width) as usize..((y + 1) * width) as usize])
+      .copied()
+      .collect();
+    band_colors.sort_unstable();
+    band_colors.dedup();
+    for color in band_colors {
+      out.push_str(&format!("#{}", color));
+      let sixels = (0..width).map(|x| {
+        (0..rows).fold(0, |bits, row| {
+          if colors[((band + row) * width + x) as usize] == color {
+            bits | 1 << row
+          } else {
+            bits
+          }
+        })
+      });
+      push_run_length_encoded(&mut out, sixels);
+      out.push('$');
+    }
+    out.push('-');
+  }
+  out.push_str("\x1b\\");
+  out
+}
+
+fn push_run_length_encoded(out: &mut String, sixels: impl Iterator<Item = u32>) {
+  let mut push_run = |sixel: u32, count: usize| {
+    let c = char::from(63 + sixel as u8);
+    if count > 3 {
+      out.push_str(&format!("!{}{}", count, c));
+    } else {
+      for _ in 0..count {
+        out.push(c);
+      }
+    }
+  };
+  let mut run: Option<(u32, usize)> = None;
+  for sixel in sixels {
+    run = match run {
+      Some((last, count)) if last == sixel => Some((last, count + 1)),
+      Some((last, count)) => {
+        push_run(last, count);
+        Some((sixel, 1))
+      }
+      None => Some((sixel, 1)),
+    };
+  }
+  if let Some((last, count)) = run {
+    push_run(last, count);
+  }
+}
+
+#[cfg(unix)]
+fn get_cell_size() -> (u32, u32) {
+  let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+  let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+  if result == 0 && size.ws_col > 0 && size.ws_row > 0 && size.ws_xpixel > 0 {
+    (
+      u32::from(size.ws_xpixel / size.ws_col),
+      u32::from(size.ws_ypixel / size.ws_row),
+    )
+  } else {
+    DEFAULT_CELL_SIZE
+  }
+}
+
+#[cfg(not(unix))]
+fn get_cell_size() -> (u32, u32) {
+  DEFAULT_CELL_SIZE
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use image::Rgb;
+
+  #[test]
+  fn encodes_sixel_bands_with_run_lengths() {
+    let mut image = RgbImage::from_pixel(8, 7, Rgb([0, 0, 0]));
+    image.put_pixel(0, 6, Rgb([255, 255, 255]));
+    let sixel = encode_sixel(&image);
+
+    assert!(sixel.starts_with("\x1bPq\"1;1;8;7#0;2;0;0;0#215;2;100;100;100"));
+    // The first band is black, the second has a white pixel at the start
+    assert!(sixel.contains("#0!8~$-#0?!7@$#215@!7?$-"));
+    assert!(sixel.ends_with("\x1b\\"));
+  }
+
+  #[test]
+  fn fits_a_square_of_cells() {
+    let area = Rect::new(2, 3, 30, 8);
+    assert_eq!(fit(area), Rect::new(2, 3, 16, 8));
+    assert_eq!(fit(Rect::new(0, 0, 9, 8)), Rect::new(0, 0, 8, 4));
+  }
+
+  #[test]
+  fn caches_images_by_their_id() {
+    let path = get_cache_path(
+      Path::new("/cache"),
+      "https://i.scdn.co/image/ab67616d00001e02ff9ca10b55ce82ae553c8228",
+    );
+    assert_eq!(
+      path,
+      Path::new("/cache/ab67616d00001e02ff9ca10b55ce82ae553c8228.jpg")
+    );
+  }
+}
@@ -1,5 +1,15 @@
 use super::user_config::UserConfig;
+use crate::album_art::AlbumArt;
+use crate::command_palette::CommandPalette;
+use crate::device::DevicePayload;
+use crate::event::Key;
+use crate::filter::{self, ListFilter, VisibleIndices};
+use crate::history::{Period, Play, PlayTracker, Stats};
+use crate::lyrics::{Lyrics, LyricsQuery};
 use crate::network::IoEvent;
+use crate::scrobbler::{ScrobbleEvent, ScrobbleState};
+use crate::sleep_timer::{SleepAction, SleepMode, SleepTimer};
+use crate::sort::{self, TableSorts};
 use anyhow::anyhow;
 use rspotify::{
   model::{
@@ -7,7 +17,6 @@ use rspotify::{
     artist::FullArtist,
     audio::AudioAnalysis,
     context::CurrentlyPlaybackContext,
-    device::DevicePayload,
     page::{CursorBasedPage, Page},
     playing::PlayHistory,
     playlist::{PlaylistTrack, SimplifiedPlaylist},
@@ -21,6 +30,7 @@ use rspotify::{
 use std::str::FromStr;
 use std::sync::mpsc::Sender;
 use std::{
+  cell::RefCell,
   cmp::{max, min},
   collections::HashSet,
   time::{Instant, SystemTime},
@@ -112,6 +122,23 @@ pub enum ArtistBlock {
 pub enum DialogContext {
   PlaylistWindow,
   PlaylistSearch,
+  AddToPlaylist,
+  CreatePlaylist,
+  RenamePlaylist,
+  DescribePlaylist,
+  RemoveFromPlaylist,
+}
+
+impl DialogContext {
+  // Dialogs that take free text need every key press, including the global ones
+  pub fn is_text_input(self) -> bool {
+    matches!(
+      self,
+      DialogContext::CreatePlaylist
+        | DialogContext::RenamePlaylist
+        | DialogContext::DescribePlaylist
+    )
+  }
 }
 
 #[derive(Clone, Copy, PartialEq, Debug)]
@@ -138,6 +165,10 @@ pub enum ActiveBlock {
   Artists,
   BasicView,
   Dialog(DialogContext),
+  Queue,
+  Lyrics,
+  Stats,
+  CommandPalette,
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -159,6 +190,9 @@ pub enum RouteId {
   PodcastEpisodes,
   Recommendations,
   Dialog,
+  Queue,
+  Lyrics,
+  Stats,
 }
 
 #[derive(Debug)]
@@ -198,6 +232,18 @@ pub enum RecommendationsContext {
   Song,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum NetworkStatus {
+  Online,
+  // A request failed in a way that may go away and is tried again
+  Retrying(String),
+  // Requests keep failing, the actions of the user since then are sent once they work again
+  Offline {
+    error: String,
+    queued_actions: usize,
+  },
+}
+
 pub struct SearchResult {
   pub albums: Option<Page<SimplifiedAlbum>>,
   pub artists: Option<Page<FullArtist>>,
@@ -218,6 +264,187 @@ pub struct TrackTable {
   pub tracks: Vec<FullTrack>,
   pub selected_index: usize,
   pub context: Option<TrackTableContext>,
+  // The rows that are shown while the table is filtered or sorted, see `filter::get_visible_indices`
+  pub visible_indices: RefCell<Option<VisibleIndices>>,
+}
+
+impl TrackTable {
+  /// Drops the rows that are shown, for when the tracks change
+  pub fn reset_visible_indices(&mut self) {
+    *self.visible_indices.get_mut() = None;
+  }
+}
+
+#[derive(Default)]
+pub struct Queue {
+  pub currently_playing: Option<PlayingItem>,
+  pub items: Vec<PlayingItem>,
+  pub selected_index: usize,
+  // The Web API does not say where a queued item came from, so remember what was queued from spt
+  pub locally_added: Vec<String>,
+  // There is no endpoint to remove from the queue, removed items are skipped once they play
+  pub skipped: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct LyricsView {
+  // The item the lyrics were loaded for
+  pub item_uri: Option<String>,
+  pub lyrics: Option<Lyrics>,
+  pub is_loading: bool,
+  // `None` while the view follows the line that is being sung
+  pub selected_index: Option<usize>,
+}
+
+impl LyricsView {
+  pub fn get_current_line(&self, progress_ms: u128) -> Option<usize> {
+    self
+      .lyrics
+      .as_ref()
+      .and_then(|lyrics| lyrics.get_current_line(progress_ms))
+  }
+}
+
+#[derive(Default)]
+pub struct StatsView {
+  pub period: Period,
+  // Counted from the listening history when the view is shown
+  pub stats: Option<Stats>,
+}
+
+// Milliseconds since the unix epoch
+fn get_now_ms() -> i64 {
+  SystemTime::now()
+    .duration_since(SystemTime::UNIX_EPOCH)
+    .map_or(0, |now| now.as_millis() as i64)
+}
+
+pub fn get_playing_item_uri(item: &PlayingItem) -> &str {
+  match item {
+    PlayingItem::Track(track) => &track.uri,
+    PlayingItem::Episode(episode) => &episode.uri,
+  }
+}
+
+fn get_lyrics_query(item: &PlayingItem) -> LyricsQuery {
+  match item {
+    PlayingItem::Track(track) => LyricsQuery {
+      title: track.name.clone(),
+      artists: track.artists.iter().map(|a| a.name.clone()).collect(),
+    },
+    PlayingItem::Episode(episode) => LyricsQuery {
+      title: episode.name.clone(),
+      artists: vec![episode.show.publisher.clone()],
+    },
+  }
+}
+
+// Removes the first occurrence of `uri`, returning whether there was one
+fn remove_first_uri(uris: &mut Vec<String>, uri: &str) -> bool {
+  match uris.iter().position(|u| u == uri) {
+    Some(index) => {
+      uris.remove(index);
+      true
+    }
+    None => false,
+  }
+}
+
+impl Queue {
+  // Positions in `items` that are shown, i.e. not removed by the user. Every entry in
+  // `skipped` hides one occurrence of its uri
+  pub fn visible_positions(&self) -> Vec<usize> {
+    let mut skipped = self.skipped.clone();
+    self
+      .items
+      .iter()
+      .enumerate()
+      .filter(|(_, item)| !remove_first_uri(&mut skipped, get_playing_item_uri(item)))
+      .map(|(position, _)| position)
+      .collect()
+  }
+
+  // The rows of the queue view: the current item followed by the visible queue
+  pub fn rows(&self) -> Vec<&PlayingItem> {
+    self
+      .currently_playing
+      .iter()
+      .chain(self.visible_positions().into_iter().map(|p| &self.items[p]))
+      .collect()
+  }
+
+  // Maps a row of the queue view to its position in `items`
+  pub fn get_position_for_row(&self, row: usize) -> Option<usize> {
+    let row = if self.currently_playing.is_some() {
+      row.checked_sub(1)?
+    } else {
+      row
+    };
+    self.visible_positions().get(row).copied()
+  }
+
+  pub fn is_locally_added(&self, item: &PlayingItem) -> bool {
+    self
+      .locally_added
+      .iter()
+      .any(|uri| uri == get_playing_item_uri(item))
+  }
+
+  pub fn set_items(&mut self, currently_playing: Option<PlayingItem>, items: Vec<PlayingItem>) {
+    // Forget about entries that have left the queue
+    let mut remaining = items
+      .iter()
+      .map(|item| get_playing_item_uri(item).to_string())
+      .collect::<Vec<String>>();
+    self
+      .skipped
+      .retain(|uri| remove_first_uri(&mut remaining, uri));
+    let mut remaining = items
+      .iter()
+      .map(|item| get_playing_item_uri(item).to_string())
+      .collect::<Vec<String>>();
+    self
+      .locally_added
+      .retain(|uri| remove_first_uri(&mut remaining, uri));
+
+    self.currently_playing = currently_playing;
+    self.items = items;
+    self.selected_index = min(self.selected_index, self.rows().len().saturating_sub(1));
+  }
+
+  // Called when a new item starts playing. Returns true if it is the head of the queue and
+  // was removed by the user, so it should be skipped
+  pub fn take_skipped_head(&mut self, now_playing: &PlayingItem) -> bool {
+    let uri = get_playing_item_uri(now_playing).to_string();
+    let is_skipped_head = match self.items.first() {
+      Some(head) => get_playing_item_uri(head) == uri && !self.visible_positions().contains(&0),
+      None => false,
+    };
+    if is_skipped_head {
+      remove_first_uri(&mut self.skipped, &uri);
+      self.items.remove(0);
+    }
+    is_skipped_head
+  }
+
+  pub fn remove_row(&mut self, row: usize) {
+    if let Some(position) = self.get_position_for_row(row) {
+      let uri = get_playing_item_uri(&self.items[position]).to_string();
+      self.skipped.push(uri);
+      self.selected_index = min(self.selected_index, self.rows().len().saturating_sub(1));
+    }
+  }
+
+  pub fn clear_locally_added(&mut self) {
+    // Only hide as many occurrences as are not hidden already
+    let mut already_skipped = self.skipped.clone();
+    for uri in self.locally_added.clone() {
+      if !remove_first_uri(&mut already_skipped, &uri) {
+        self.skipped.push(uri);
+      }
+    }
+    self.selected_index = min(self.selected_index, self.rows().len().saturating_sub(1));
+  }
 }
 
 #[derive(Clone)]
@@ -267,7 +494,16 @@ pub struct App {
   pub album_table_context: AlbumTableContext,
   pub saved_album_tracks_index: usize,
   pub api_error: String,
+  // How many errors there were so far, so an error with the same message as the last one is seen
+  pub api_error_count: u64,
+  pub network_status: NetworkStatus,
+  // Shown in the help box, e.g. where the library was exported to
+  pub status_message: Option<String>,
   pub current_playback_context: Option<CurrentlyPlaybackContext>,
+  scrobble_state: ScrobbleState,
+  // Follows the playback to record the plays in the listening history, only in the UI
+  play_tracker: PlayTracker,
+  pub sleep_timer: Option<SleepTimer>,
   pub devices: Option<DevicePayload>,
   // Inputs:
   // input is the string for input;
@@ -287,6 +523,8 @@ pub struct App {
   pub playlist_offset: u32,
   pub made_for_you_offset: u32,
   pub playlist_tracks: Option<Page<PlaylistTrack>>,
+  // Tracks loaded out of the total while a whole playlist is fetched
+  pub playlist_tracks_progress: Option<(u32, u32)>,
   pub made_for_you_tracks: Option<Page<PlaylistTrack>>,
   pub playlists: Option<Page<SimplifiedPlaylist>>,
   pub recently_played: SpotifyResultAndSelectedIndex<Option<CursorBasedPage<PlayHistory>>>,
@@ -323,7 +561,22 @@ pub struct App {
   pub is_fetching_current_playback: bool,
   pub spotify_token_expiry: SystemTime,
   pub dialog: Option<String>,
+  pub dialog_input: Vec<char>,
   pub confirm: bool,
+  pub playlist_picker_index: usize,
+  pub tracks_to_add: Vec<String>,
+  pub queue: Queue,
+  pub lyrics: LyricsView,
+  pub stats: StatsView,
+  pub filter: Option<ListFilter>,
+  pub table_sorts: TableSorts,
+  // The start of a chord and the count typed before it, e.g. `5` and `g` of `5gj`
+  pub pending_keys: Vec<Key>,
+  pub key_count: Option<u32>,
+  pub command_palette: Option<CommandPalette>,
+  pub album_art: AlbumArt,
+  // Time and position of the last left click, used to detect double clicks
+  pub last_click: Option<(Instant, u16, u16)>,
 }
 
 impl Default for App {
@@ -362,7 +615,13 @@ impl Default for App {
       large_search_limit: 20,
       small_search_limit: 4,
       api_error: String::new(),
+      api_error_count: 0,
+      network_status: NetworkStatus::Online,
+      status_message: None,
       current_playback_context: None,
+      scrobble_state: ScrobbleState::default(),
+      play_tracker: PlayTracker::default(),
+      sleep_timer: None,
       devices: None,
       input: vec![],
       input_idx: 0,
@@ -370,6 +629,7 @@ impl Default for App {
       playlist_offset: 0,
       made_for_you_offset: 0,
       playlist_tracks: None,
+      playlist_tracks_progress: None,
       made_for_you_tracks: None,
       playlists: None,
       recommended_tracks: vec![],
@@ -410,7 +670,20 @@ impl Default for App {
       is_fetching_current_playback: false,
       spotify_token_expiry: SystemTime::now(),
       dialog: None,
+      dialog_input: vec![],
       confirm: false,
+      playlist_picker_index: 0,
+      tracks_to_add: vec![],
+      queue: Default::default(),
+      lyrics: Default::default(),
+      stats: Default::default(),
+      filter: None,
+      table_sorts: TableSorts::default(),
+      pending_keys: vec![],
+      key_count: None,
+      command_palette: None,
+      album_art: AlbumArt::default(),
+      last_click: None,
     }
   }
 }
@@ -483,6 +756,15 @@ impl App {
 
   pub fn update_on_tick(&mut self) {
     self.poll_current_playback();
+    self.update_song_progress();
+    self.update_scrobble_state();
+    self.update_play_tracker();
+    for event in self.get_sleep_timer_events() {
+      self.dispatch(event);
+    }
+  }
+
+  pub fn update_song_progress(&mut self) {
     if let Some(CurrentlyPlaybackContext {
       item: Some(item),
       progress_ms: Some(progress_ms),
@@ -514,6 +796,104 @@ impl App {
     }
   }
 
+  /// Starts the sleep timer, replacing the one that is running
+  pub fn set_sleep_timer(&mut self, mode: SleepMode) -> anyhow::Result<()> {
+    let timer = SleepTimer::new(mode, self.current_playback_context.as_ref(), Instant::now())?;
+    self.cancel_sleep_timer();
+    self.sleep_timer = Some(timer);
+    // The end of the album is noticed in the queue
+    if mode == SleepMode::EndOfAlbum {
+      self.dispatch(IoEvent::GetQueue);
+    }
+    Ok(())
+  }
+
+  pub fn cancel_sleep_timer(&mut self) {
+    let volume = self
+      .sleep_timer
+      .take()
+      .and_then(|timer| timer.get_volume_before_fade());
+    if let Some(volume) = volume {
+      self.dispatch(IoEvent::ChangeVolume(volume));
+    }
+  }
+
+  /// Moves the sleep timer on, returning the volume changes of the fade and the pause at its end
+  pub fn get_sleep_timer_events(&mut self) -> Vec<IoEvent> {
+    let action = match &mut self.sleep_timer {
+      Some(timer) => timer.update(
+        self.current_playback_context.as_ref(),
+        self.song_progress_ms as u32,
+        self.queue.items.first(),
+        Instant::now(),
+      ),
+      None => return vec![],
+    };
+    match action {
+      Some(SleepAction::SetVolume(volume)) => vec![IoEvent::ChangeVolume(volume)],
+      Some(SleepAction::Stop { pause, volume }) => {
+        self.sleep_timer = None;
+        let mut events = vec![];
+        if pause {
+          events.push(IoEvent::PausePlayback);
+        }
+        events.extend(volume.map(IoEvent::ChangeVolume));
+        events
+      }
+      None => vec![],
+    }
+  }
+
+  fn update_play_tracker(&mut self) {
+    let (track, is_playing) = match &self.current_playback_context {
+      Some(CurrentlyPlaybackContext {
+        item: Some(PlayingItem::Track(track)),
+        is_playing,
+        ..
+      }) => (Some(track), *is_playing),
+      _ => (None, false),
+    };
+    let play = self.play_tracker.update(
+      track,
+      is_playing,
+      self.song_progress_ms as u32,
+      get_now_ms(),
+    );
+    if let Some(play) = play {
+      self.dispatch(IoEvent::RecordPlay(play));
+    }
+  }
+
+  /// The play in progress when the app exits, if it counts
+  pub fn finish_play(&mut self) -> Option<Play> {
+    self.play_tracker.finish(get_now_ms())
+  }
+
+  fn update_scrobble_state(&mut self) {
+    if !self.user_config.scrobbling.is_enabled() {
+      return;
+    }
+    let (track, is_playing) = match &self.current_playback_context {
+      Some(CurrentlyPlaybackContext {
+        item: Some(PlayingItem::Track(track)),
+        is_playing,
+        ..
+      }) => (Some(track), *is_playing),
+      _ => (None, false),
+    };
+    let now = SystemTime::now()
+      .duration_since(SystemTime::UNIX_EPOCH)
+      .map_or(0, |now| now.as_secs() as i64);
+    let event = self
+      .scrobble_state
+      .update(track, is_playing, self.song_progress_ms as u32, now);
+    match event {
+      Some(ScrobbleEvent::NowPlaying(listen)) => self.dispatch(IoEvent::UpdateNowPlaying(listen)),
+      Some(ScrobbleEvent::Scrobble(listen)) => self.dispatch(IoEvent::Scrobble(listen)),
+      None => {}
+    }
+  }
+
   pub fn seek_forwards(&mut self) {
     if let Some(CurrentlyPlaybackContext {
       item: Some(item), ..
@@ -538,6 +918,20 @@ impl App {
     }
   }
 
+  pub fn seek_to(&mut self, position_ms: u32) {
+    if let Some(CurrentlyPlaybackContext {
+      item: Some(item), ..
+    }) = &self.current_playback_context
+    {
+      let duration_ms = match item {
+        PlayingItem::Track(track) => track.duration_ms,
+        PlayingItem::Episode(episode) => episode.duration_ms,
+      };
+
+      self.seek_ms = Some(min(position_ms, duration_ms) as u128);
+    }
+  }
+
   pub fn seek_backwards(&mut self) {
     let old_progress = match self.seek_ms {
       Some(seek_ms) => seek_ms,
@@ -602,6 +996,7 @@ impl App {
   pub fn handle_error(&mut self, e: anyhow::Error) {
     self.push_navigation_stack(RouteId::Error, ActiveBlock::Error);
     self.api_error = e.to_string();
+    self.api_error_count += 1;
   }
 
   pub fn toggle_playback(&mut self) {
@@ -627,6 +1022,7 @@ impl App {
   // The navigation_stack actually only controls the large block to the right of `library` and
   // `playlists`
   pub fn push_navigation_stack(&mut self, next_route_id: RouteId, next_active_block: ActiveBlock) {
+    filter::reset_route_filter(self, &next_route_id);
     if !self
       .navigation_stack
       .last()
@@ -645,7 +1041,11 @@ impl App {
     if self.navigation_stack.len() == 1 {
       None
     } else {
-      self.navigation_stack.pop()
+      let route = self.navigation_stack.pop();
+      if let Some(route) = &route {
+        filter::reset_route_filter(self, &route.id);
+      }
+      route
     }
   }
 
@@ -1045,6 +1445,221 @@ impl App {
     }
   }
 
+  // The playlists tracks can be added to, those of the user and the collaborative ones
+  pub fn get_editable_playlists(&self) -> Vec<&SimplifiedPlaylist> {
+    match (&self.playlists, &self.user) {
+      (Some(playlists), Some(user)) => playlists
+        .items
+        .iter()
+        .filter(|playlist| playlist.owner.id == user.id || playlist.collaborative)
+        .collect(),
+      _ => vec![],
+    }
+  }
+
+  pub fn open_add_to_playlist_dialog(&mut self, track_uris: Vec<String>) {
+    if !self.get_editable_playlists().is_empty() && !track_uris.is_empty() {
+      self.tracks_to_add = track_uris;
+      self.playlist_picker_index = 0;
+      self.push_navigation_stack(
+        RouteId::Dialog,
+        ActiveBlock::Dialog(DialogContext::AddToPlaylist),
+      );
+    }
+  }
+
+  pub fn add_tracks_to_picked_playlist(&mut self) {
+    if let Some(user) = &self.user {
+      if let Some(playlist) = self
+        .get_editable_playlists()
+        .get(self.playlist_picker_index)
+      {
+        let playlist_id = playlist.id.clone();
+        let user_id = user.id.clone();
+        let track_uris = std::mem::take(&mut self.tracks_to_add);
+        self.dispatch(IoEvent::AddTracksToPlaylist(
+          user_id,
+          playlist_id,
+          track_uris,
+        ));
+      }
+    }
+  }
+
+  pub fn open_playlist_text_dialog(&mut self, context: DialogContext) {
+    self.dialog_input = match context {
+      DialogContext::RenamePlaylist => match (&self.playlists, self.selected_playlist_index) {
+        (Some(playlists), Some(selected_index)) => match playlists.items.get(selected_index) {
+          Some(playlist) => playlist.name.chars().collect(),
+          None => return,
+        },
+        _ => return,
+      },
+      DialogContext::DescribePlaylist if self.selected_playlist_index.is_none() => return,
+      _ => vec![],
+    };
+    self.push_navigation_stack(RouteId::Dialog, ActiveBlock::Dialog(context));
+  }
+
+  pub fn submit_playlist_text_dialog(&mut self, context: DialogContext) {
+    let text: String = self.dialog_input.drain(..).collect();
+    let user_id = match &self.user {
+      Some(user) => user.id.clone(),
+      None => return,
+    };
+    let selected_playlist_id = match (&self.playlists, self.selected_playlist_index) {
+      (Some(playlists), Some(selected_index)) => playlists
+        .items
+        .get(selected_index)
+        .map(|playlist| playlist.id.clone()),
+      _ => None,
+    };
+
+    match context {
+      DialogContext::CreatePlaylist if !text.trim().is_empty() => {
+        self.dispatch(IoEvent::CreatePlaylist(user_id, text));
+      }
+      DialogContext::RenamePlaylist if !text.trim().is_empty() => {
+        if let Some(playlist_id) = selected_playlist_id {
+          self.dispatch(IoEvent::ChangePlaylistDetail(
+            user_id,
+            playlist_id,
+            Some(text),
+            None,
+          ));
+        }
+      }
+      // An empty description is valid, it clears the current one
+      DialogContext::DescribePlaylist => {
+        if let Some(playlist_id) = selected_playlist_id {
+          self.dispatch(IoEvent::ChangePlaylistDetail(
+            user_id,
+            playlist_id,
+            None,
+            Some(text),
+          ));
+        }
+      }
+      _ => {}
+    }
+  }
+
+  fn get_active_playlist_id(&self) -> Option<String> {
+    match (&self.playlists, self.active_playlist_index) {
+      (Some(playlists), Some(active_index)) => playlists
+        .items
+        .get(active_index)
+        .map(|playlist| playlist.id.clone()),
+      _ => None,
+    }
+  }
+
+  pub fn move_playlist_track(&mut self, up: bool) {
+    if self.track_table.context != Some(TrackTableContext::MyPlaylists) {
+      return;
+    }
+    // The rows don't show the order of the playlist while it is sorted or filtered
+    let is_filtered =
+      matches!(&self.filter, Some(filter) if filter.block == ActiveBlock::TrackTable);
+    if is_filtered || sort::get_sort(self, ActiveBlock::TrackTable).is_some() {
+      self.status_message = Some("Clear the sort and the filter to move tracks".to_string());
+      return;
+    }
+    let index = self.track_table.selected_index;
+    let target = if up {
+      match index.checked_sub(1) {
+        Some(target) => target,
+        None => return,
+      }
+    } else if index + 1 < self.track_table.tracks.len() {
+      index + 1
+    } else {
+      return;
+    };
+
+    let (position, target_position) = match (
+      self.get_playlist_position(index),
+      self.get_playlist_position(target),
+    ) {
+      (Some(position), Some(target_position)) => (position, target_position),
+      _ => return,
+    };
+    if let (Some(playlist_id), Some(user), Some(playlist_tracks)) = (
+      self.get_active_playlist_id(),
+      &self.user,
+      &mut self.playlist_tracks,
+    ) {
+      let user_id = user.id.clone();
+      // The api inserts the range before the given position, so moving
+      // down means skipping over the target track as well
+      let insert_before = if up {
+        target_position
+      } else {
+        target_position + 1
+      };
+
+      let offset = playlist_tracks.offset as usize;
+      playlist_tracks
+        .items
+        .swap(position - offset, target_position - offset);
+      self.track_table.tracks.swap(index, target);
+      self.track_table.reset_visible_indices();
+      self.track_table.selected_index = target;
+      self.dispatch(IoEvent::ReorderPlaylistTracks(
+        user_id,
+        playlist_id,
+        position,
+        insert_before,
+      ));
+    }
+  }
+
+  // Whether the track table holds every track of the playlist, rather than a page of them
+  pub fn has_full_playlist(&self) -> bool {
+    match &self.playlist_tracks {
+      Some(playlist_tracks) => {
+        playlist_tracks.offset == 0 && playlist_tracks.items.len() as u32 >= playlist_tracks.total
+      }
+      None => false,
+    }
+  }
+
+  // The position in the playlist of a row of the track table, which leaves out the
+  // unavailable tracks
+  fn get_playlist_position(&self, index: usize) -> Option<usize> {
+    let playlist_tracks = self.playlist_tracks.as_ref()?;
+    playlist_tracks
+      .items
+      .iter()
+      .enumerate()
+      .filter(|(_, item)| item.track.is_some())
+      .nth(index)
+      .map(|(position, _)| playlist_tracks.offset as usize + position)
+  }
+
+  pub fn remove_track_from_playlist(&mut self) {
+    let (playlists, active_index) = match (&self.playlists, self.active_playlist_index) {
+      (Some(playlists), Some(active_index)) => (playlists, active_index),
+      _ => return,
+    };
+    let index = self.track_table.selected_index;
+    if let (Some(playlist), Some(track), Some(position)) = (
+      playlists.items.get(active_index),
+      self.track_table.tracks.get(index),
+      self.get_playlist_position(index),
+    ) {
+      // Only the selected row goes, not the other copies of the track. The snapshot makes the
+      // api refuse the removal if the playlist changed meanwhile
+      let event = IoEvent::RemovePlaylistTrack(
+        playlist.id.clone(),
+        track.uri.clone(),
+        position,
+        playlist.snapshot_id.clone(),
+      );
+      self.dispatch(event);
+    }
+  }
+
   pub fn user_follow_show(&mut self, block: ActiveBlock) {
     match block {
       ActiveBlock::SearchResultBlock => {
@@ -1159,6 +1774,46 @@ impl App {
     }
   }
 
+  pub fn show_lyrics(&mut self) {
+    if self.get_current_route().id != RouteId::Lyrics {
+      self.push_navigation_stack(RouteId::Lyrics, ActiveBlock::Lyrics);
+    }
+    self.update_lyrics();
+  }
+
+  // Loads the lyrics of the playing item, unless they were loaded already
+  pub fn update_lyrics(&mut self) {
+    let (uri, query) = match &self.current_playback_context {
+      Some(CurrentlyPlaybackContext {
+        item: Some(item), ..
+      }) => (
+        get_playing_item_uri(item).to_string(),
+        get_lyrics_query(item),
+      ),
+      _ => return,
+    };
+    if self.lyrics.item_uri.as_ref() != Some(&uri) {
+      self.lyrics = LyricsView {
+        item_uri: Some(uri.clone()),
+        is_loading: true,
+        ..Default::default()
+      };
+      self.dispatch(IoEvent::GetLyrics(uri, query));
+    }
+  }
+
+  pub fn show_stats(&mut self) {
+    if self.get_current_route().id != RouteId::Stats {
+      self.push_navigation_stack(RouteId::Stats, ActiveBlock::Stats);
+    }
+    self.dispatch(IoEvent::GetStats(self.stats.period));
+  }
+
+  pub fn cycle_stats_period(&mut self) {
+    self.stats.period = self.stats.period.next();
+    self.dispatch(IoEvent::GetStats(self.stats.period));
+  }
+
   pub fn repeat(&mut self) {
     if let Some(context) = &self.current_playback_context.clone() {
       self.dispatch(IoEvent::Repeat(context.repeat_state));
@@ -0,0 +1,256 @@
+use crate::app::App;
+use anyhow::Result;
+use rspotify::model::{
+  album::SavedAlbum,
+  artist::CursorPageFullArtists,
+  page::Page,
+  playlist::{PlaylistTrack, SimplifiedPlaylist},
+  show::Show,
+  track::SavedTrack,
+  user::PrivateUser,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+  collections::{HashMap, HashSet},
+  fs,
+  path::PathBuf,
+};
+
+/// A response together with the request it answered and the `ETag` it was sent with
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Cached<T> {
+  pub path: String,
+  pub etag: Option<String>,
+  pub value: T,
+}
+
+// Playlists are dropped from the cache, the least recently fetched first, while it holds more
+// tracks than this
+const MAX_CACHED_PLAYLIST_TRACKS: usize = 5000;
+
+/// The first page of tracks of a playlist, which stays valid as long as its snapshot does
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CachedPlaylistTracks {
+  pub snapshot_id: String,
+  pub tracks: Page<PlaylistTrack>,
+  // Counts up with every playlist that is cached, to drop the oldest first
+  #[serde(default)]
+  fetched: u64,
+}
+
+/// Library data that is kept on disk between runs, so the UI can show it before the API has
+/// answered. Responses are revalidated in the background and replaced when they changed
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Cache {
+  #[serde(skip)]
+  path: Option<PathBuf>,
+  #[serde(skip)]
+  is_changed: bool,
+  pub user: Option<Cached<PrivateUser>>,
+  pub playlists: Option<Cached<Page<SimplifiedPlaylist>>>,
+  pub saved_tracks: Option<Cached<Page<SavedTrack>>>,
+  pub saved_albums: Option<Cached<Page<SavedAlbum>>>,
+  pub saved_artists: Option<Cached<CursorPageFullArtists>>,
+  pub saved_shows: Option<Cached<Page<Show>>>,
+  pub playlist_tracks: HashMap<String, CachedPlaylistTracks>,
+  liked_song_ids: HashSet<String>,
+  followed_artist_ids: HashSet<String>,
+  saved_album_ids: HashSet<String>,
+  saved_show_ids: HashSet<String>,
+}
+
+impl Cache {
+  /// Reads the cache at `path`, a missing or outdated file gives an empty cache
+  pub fn load(path: PathBuf) -> Cache {
+    let mut cache = fs::read_to_string(&path)
+      .ok()
+      .and_then(|content| serde_json::from_str::<Cache>(&content).ok())
+      .unwrap_or_default();
+    cache.path = Some(path);
+    cache
+  }
+
+  pub fn mark_changed(&mut self) {
+    self.is_changed = true;
+  }
+
+  /// Writes the cache together with the current ID sets of the app, if anything changed
+  pub fn save(&mut self, app: &App) -> Result<()> {
+    let ids_changed = self.liked_song_ids != app.liked_song_ids_set
+      || self.followed_artist_ids != app.followed_artist_ids_set
+      || self.saved_album_ids != app.saved_album_ids_set
+      || self.saved_show_ids != app.saved_show_ids_set;
+    if !self.is_changed && !ids_changed {
+      return Ok(());
+    }
+
+    if let Some(path) = &self.path {
+      self.liked_song_ids = app.liked_song_ids_set.clone();
+      self.followed_artist_ids = app.followed_artist_ids_set.clone();
+      self.saved_album_ids = app.saved_album_ids_set.clone();
+      self.saved_show_ids = app.saved_show_ids_set.clone();
+      fs::write(path, serde_json::to_string(self)?)?;
+      self.is_changed = false;
+    }
+    Ok(())
+  }
+
+  /// Fills the app with everything that was cached, so it can be drawn straight away
+  pub fn apply(&self, app: &mut App) {
+    if let Some(user) = &self.user {
+      app.user = Some(user.value.clone());
+    }
+    if let Some(playlists) = &self.playlists {
+      app.playlists = Some(playlists.value.clone());
+      app.selected_playlist_index = Some(0);
+    }
+    if let Some(saved_tracks) = &self.saved_tracks {
+      app
+        .library
+        .saved_tracks
+        .add_pages(saved_tracks.value.clone());
+    }
+    if let Some(saved_albums) = &self.saved_albums {
+      app
+        .library
+        .saved_albums
+        .add_pages(saved_albums.value.clone());
+    }
+    if let Some(saved_artists) = &self.saved_artists {
+      app.artists = saved_artists.value.artists.items.clone();
+      app
+        .library
+        .saved_artists
+        .add_pages(saved_artists.value.artists.clone());
+    }
+    if let Some(saved_shows) = &self.saved_shows {
+      app.library.saved_shows.add_pages(saved_shows.value.clone());
+    }
+    app
+      .liked_song_ids_set
+      .extend(self.liked_song_ids.iter().cloned());
+    app
+      .followed_artist_ids_set
+      .extend(self.followed_artist_ids.iter().cloned());
+    app
+      .saved_album_ids_set
+      .extend(self.saved_album_ids.iter().cloned());
+    app
+      .saved_show_ids_set
+      .extend(self.saved_show_ids.iter().cloned());
+  }
+
+  /// Caches the tracks of a playlist, making room for them if the cache is full
+  pub fn insert_playlist_tracks(
+    &mut self,
+    playlist_id: String,
+    snapshot_id: String,
+    tracks: Page<PlaylistTrack>,
+  ) {
+    self.remove_playlist_tracks(&playlist_id);
+    if tracks.items.len() > MAX_CACHED_PLAYLIST_TRACKS {
+      return;
+    }
+    let mut size = tracks.items.len()
+      + self
+        .playlist_tracks
+        .values()
+        .map(|cached| cached.tracks.items.len())
+        .sum::<usize>();
+    while size > MAX_CACHED_PLAYLIST_TRACKS {
+      let oldest = self
+        .playlist_tracks
+        .iter()
+        .min_by_key(|(_, cached)| cached.fetched)
+        .map(|(id, _)| id.clone());
+      if let Some(cached) = oldest.and_then(|id| self.playlist_tracks.remove(&id)) {
+        size -= cached.tracks.items.len();
+      }
+    }
+
+    let fetched = self
+      .playlist_tracks
+      .values()
+      .map(|cached| cached.fetched + 1)
+      .max()
+      .unwrap_or_default();
+    self.playlist_tracks.insert(
+      playlist_id,
+      CachedPlaylistTracks {
+        snapshot_id,
+        tracks,
+        fetched,
+      },
+    );
+    self.mark_changed();
+  }
+
+  /// Drops the cached tracks of a playlist that was changed
+  pub fn remove_playlist_tracks(&mut self, playlist_id: &str) {
+    if self.playlist_tracks.remove(playlist_id).is_some() {
+      self.mark_changed();
+    }
+  }
+
+  /// The cached tracks of a playlist, if they were fetched at the given snapshot
+  pub fn get_playlist_tracks(
+    &self,
+    playlist_id: &str,
+    snapshot_id: &str,
+  ) -> Option<&Page<PlaylistTrack>> {
+    self
+      .playlist_tracks
+      .get(playlist_id)
+      .filter(|cached| cached.snapshot_id == snapshot_id)
+      .map(|cached| &cached.tracks)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn ids_survive_a_restart() {
+    let path = std::env::temp_dir().join(format!("spt-cache-test-{}.json", std::process::id()));
+    let mut app = App::default();
+    app.liked_song_ids_set.insert("liked".to_string());
+    app.saved_show_ids_set.insert("show".to_string());
+
+    let mut cache = Cache::load(path.clone());
+    assert!(cache.user.is_none());
+    cache.save(&app).unwrap();
+
+    let mut restarted_app = App::default();
+    Cache::load(path.clone()).apply(&mut restarted_app);
+    fs::remove_file(path).unwrap();
+
+    assert!(restarted_app.liked_song_ids_set.contains("liked"));
+    assert!(restarted_app.saved_show_ids_set.contains("show"));
+    assert!(restarted_app.saved_album_ids_set.is_empty());
+  }
+
+  #[test]
+  fn drops_the_oldest_playlists_when_full() {
+    let mut cache = Cache::default();
+    let track = serde_json::json!({
+      "added_at": "2020-01-01T00:00:00Z", "added_by": null, "is_local": false, "track": null
+    });
+    let tracks: Page<PlaylistTrack> = serde_json::from_value(serde_json::json!({
+      "href": "", "items": vec![track; MAX_CACHED_PLAYLIST_TRACKS / 2], "limit": 100,
+      "next": null, "offset": 0, "previous": null, "total": MAX_CACHED_PLAYLIST_TRACKS / 2
+    }))
+    .unwrap();
+
+    cache.insert_playlist_tracks("a".to_string(), "1".to_string(), tracks.clone());
+    cache.insert_playlist_tracks("b".to_string(), "1".to_string(), tracks.clone());
+    assert!(cache.get_playlist_tracks("a", "1").is_some());
+    cache.insert_playlist_tracks("c".to_string(), "1".to_string(), tracks);
+    assert!(cache.get_playlist_tracks("a", "1").is_none());
+    assert!(cache.get_playlist_tracks("b", "1").is_some());
+    assert!(cache.get_playlist_tracks("c", "1").is_some());
+
+    cache.remove_playlist_tracks("b");
+    assert!(cache.get_playlist_tracks("b", "1").is_none());
+  }
+}
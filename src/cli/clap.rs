@@ -1,5 +1,54 @@
+use crate::banner::BANNER;
 use clap::{App, Arg, ArgGroup, SubCommand};
 
+pub fn clap_app() -> App<'static, 'static> {
+  App::new(env!("CARGO_PKG_NAME"))
+    .version(env!("CARGO_PKG_VERSION"))
+    .author(env!("CARGO_PKG_AUTHORS"))
+    .about(env!("CARGO_PKG_DESCRIPTION"))
+    .usage("Press `?` while running the app to see keybindings")
+    .before_help(BANNER)
+    .after_help(
+      "Your spotify Client ID and Client Secret are stored in $HOME/.config/spotify-tui/client.yml",
+    )
+    .arg(
+      Arg::with_name("tick-rate")
+        .short("t")
+        .long("tick-rate")
+        .help("Set the tick rate (milliseconds): the lower the number the higher the FPS.")
+        .long_help(
+          "Specify the tick rate in milliseconds: the lower the number the \
+higher the FPS. It can be nicer to have a lower value when you want to use the audio analysis view \
+of the app. Beware that this comes at a CPU cost!",
+        )
+        .takes_value(true),
+    )
+    .arg(
+      Arg::with_name("config")
+        .short("c")
+        .long("config")
+        .help("Specify configuration file path.")
+        .takes_value(true),
+    )
+    .arg(
+      Arg::with_name("completions")
+        .long("completions")
+        .help("Generates completions for your preferred shell")
+        .takes_value(true)
+        .possible_values(&["bash", "zsh", "fish", "power-shell", "elvish"])
+        .value_name("SHELL"),
+    )
+    // Control spotify from the command line
+    .subcommand(playback_subcommand())
+    .subcommand(play_subcommand())
+    .subcommand(list_subcommand())
+    .subcommand(search_subcommand())
+    .subcommand(playlist_subcommand())
+    .subcommand(export_subcommand())
+    .subcommand(import_subcommand())
+    .subcommand(stats_subcommand())
+}
+
 fn device_arg() -> Arg<'static, 'static> {
   Arg::with_name("device")
     .short("d")
@@ -23,6 +72,22 @@ Example: spt pb -s -f 'playing on %d at %v%'",
     )
 }
 
+fn json_args() -> Vec<Arg<'static, 'static>> {
+  vec![
+    Arg::with_name("json")
+      .long("json")
+      .conflicts_with("jsonl")
+      .help("Prints the output as json"),
+    Arg::with_name("jsonl")
+      .long("jsonl")
+      .help("Prints the output as json, one object per line")
+      .long_help(
+        "Prints every item as a json object on its own line. Every object has a `type`: \
+track, episode, album, artist, playlist, show, device, playback or share.",
+      ),
+  ]
+}
+
 pub fn playback_subcommand() -> App<'static, 'static> {
   SubCommand::with_name("playback")
     .version(env!("CARGO_PKG_VERSION"))
@@ -38,8 +103,8 @@ spt will display the updated playback. The output format is configurable with th
 Here's a list:
 
 * `--next` and `--previous` cannot be used with other options
-* `--status`, `--toggle`, `--transfer`, `--volume`, `--like`, `--repeat` and `--shuffle` \
-can be used together
+* `--status`, `--toggle`, `--transfer`, `--volume`, `--sleep`, `--like`, `--repeat` and \
+`--shuffle` can be used together
 * `--share-track` and `--share-album` cannot be used with other options",
     )
     .visible_alias("pb")
@@ -53,6 +118,7 @@ can be used together
           ("transfer", None, "%f %s %t - %a on %d"),
         ]),
     )
+    .args(&json_args())
     .arg(
       Arg::with_name("toggle")
         .short("t")
@@ -65,6 +131,19 @@ can be used together
         .long("status")
         .help("Prints out the current status of a device (default)"),
     )
+    .arg(
+      Arg::with_name("sleep")
+        .long("sleep")
+        .takes_value(true)
+        .value_name("WHEN")
+        .help("Pauses the playback after a while, fading the volume out")
+        .long_help(
+          "Pauses the playback after WHEN, a number of minutes or a duration like `1h30m` or \
+`45s`, or at the end of the `track` or `album`, fading the volume out over the last 30 seconds. \
+While the UI is running the timer runs there and `off` cancels it, otherwise `spt` waits until \
+the playback is paused.",
+        ),
+    )
     .arg(
       Arg::with_name("share-track")
         .long("share-track")
@@ -164,7 +243,7 @@ seconds backwards and `spt pb --seek 10` to the tenth second of the track.",
     )
     .group(
       ArgGroup::with_name("actions")
-        .args(&["toggle", "status", "transfer", "volume"])
+        .args(&["toggle", "status", "transfer", "volume", "sleep"])
         .multiple(true)
         .conflicts_with_all(&["single", "jumps"]),
     )
@@ -192,6 +271,7 @@ The same function as found in `playback` will be called.",
     .visible_alias("p")
     .arg(device_arg())
     .arg(format_arg().default_value("%f %s %t - %a"))
+    .args(&json_args())
     .arg(
       Arg::with_name("uri")
         .short("u")
@@ -285,6 +365,7 @@ be applied to every item found.",
       ("liked", None, "%t - %a (%u)"),
       ("playlists", None, "%p (%u)"),
     ]))
+    .args(&json_args())
     .arg(
       Arg::with_name("devices")
         .short("d")
@@ -335,6 +416,7 @@ specify it.",
       ("albums", None, "%b - %a (%u)"),
       ("shows", None, "%h - %a (%u)"),
     ]))
+    .args(&json_args())
     .arg(
       Arg::with_name("search")
         .required(true)
@@ -385,3 +467,210 @@ specify it.",
         .multiple(false),
     )
 }
+
+pub fn playlist_subcommand() -> App<'static, 'static> {
+  SubCommand::with_name("playlist")
+    .version(env!("CARGO_PKG_VERSION"))
+    .author(env!("CARGO_PKG_AUTHORS"))
+    .about("Creates playlists and changes their tracks")
+    .long_about(
+      "Changes one of your playlists, found by name, uri or id. With `--create` the playlist \
+is created unless you already have one with that name. Tracks are given as uris, links or ids, \
+`--add-search` adds the first track found. `--import` replaces the tracks with the uris read \
+from stdin, one per line, and `--export` prints them the same way. The actions run in the order \
+create, import, add, remove and dedupe. Local files can't be added with the Web API, so \
+`--import` drops them, and `--dedupe` leaves them and unavailable tracks where they are.",
+    )
+    .visible_alias("pl")
+    .arg(
+      Arg::with_name("playlist")
+        .required(true)
+        .takes_value(true)
+        .value_name("PLAYLIST")
+        .help("Specifies the playlist by name, uri or id"),
+    )
+    .arg(
+      Arg::with_name("create")
+        .short("c")
+        .long("create")
+        .help("Creates the playlist if you have none with that name"),
+    )
+    .arg(
+      Arg::with_name("add")
+        .short("a")
+        .long("add")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .value_name("URI")
+        .help("Adds a track, can be used multiple times"),
+    )
+    .arg(
+      Arg::with_name("add-search")
+        .short("s")
+        .long("add-search")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .value_name("SEARCH")
+        .help("Adds the first track found, can be used multiple times"),
+    )
+    .arg(
+      Arg::with_name("remove")
+        .short("r")
+        .long("remove")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .value_name("URI")
+        .help("Removes every occurrence of a track, can be used multiple times"),
+    )
+    .arg(
+      Arg::with_name("dedupe")
+        .long("dedupe")
+        .help("Removes repeated tracks, keeping the first occurrence"),
+    )
+    .arg(
+      Arg::with_name("import")
+        .short("i")
+        .long("import")
+        .help("Replaces the tracks with the uris read from stdin"),
+    )
+    .arg(
+      Arg::with_name("export")
+        .short("e")
+        .long("export")
+        .conflicts_with_all(&["create", "add", "add-search", "remove", "dedupe", "import"])
+        .help("Prints the uris of the tracks, one per line"),
+    )
+    .group(
+      ArgGroup::with_name("actions")
+        .args(&[
+          "create",
+          "add",
+          "add-search",
+          "remove",
+          "dedupe",
+          "import",
+          "export",
+        ])
+        .required(true)
+        .multiple(true),
+    )
+}
+
+fn export_format_arg(formats: &'static [&'static str]) -> Arg<'static, 'static> {
+  Arg::with_name("format")
+    .short("f")
+    .long("format")
+    .takes_value(true)
+    .value_name("FORMAT")
+    .possible_values(formats)
+    .help("Specifies the format, otherwise it is taken from the file extension")
+}
+
+pub fn export_subcommand() -> App<'static, 'static> {
+  SubCommand::with_name("export")
+    .version(env!("CARGO_PKG_VERSION"))
+    .author(env!("CARGO_PKG_AUTHORS"))
+    .about("Exports your library")
+    .long_about(
+      "Exports your liked songs, saved albums, followed artists, saved shows and playlists. \
+JSON and CSV exports have everything and can be imported again with `spt import`, they are \
+printed if no `--output` file is given. M3U and XSPF exports are a directory with a file for \
+the liked songs and for each playlist, for use with other players.",
+    )
+    .arg(export_format_arg(&["json", "csv", "m3u", "xspf"]))
+    .arg(
+      Arg::with_name("output")
+        .short("o")
+        .long("output")
+        .takes_value(true)
+        .value_name("PATH")
+        .help("Specifies the file, or the directory for M3U and XSPF, to write to"),
+    )
+}
+
+pub fn import_subcommand() -> App<'static, 'static> {
+  SubCommand::with_name("import")
+    .version(env!("CARGO_PKG_VERSION"))
+    .author(env!("CARGO_PKG_AUTHORS"))
+    .about("Imports a library exported with `spt export`")
+    .long_about(
+      "Saves the tracks, albums and shows and follows the artists and playlists of a JSON or \
+CSV export that are not in your library yet, printing each of them. Nothing is removed from \
+your library. Use `--dry-run` to only see what would be imported.",
+    )
+    .arg(
+      Arg::with_name("file")
+        .required(true)
+        .takes_value(true)
+        .value_name("FILE")
+        .help("Specifies the export to import"),
+    )
+    .arg(export_format_arg(&["json", "csv"]))
+    .arg(
+      Arg::with_name("dry-run")
+        .short("n")
+        .long("dry-run")
+        .help("Prints what would be imported without changing anything"),
+    )
+}
+
+pub fn stats_subcommand() -> App<'static, 'static> {
+  SubCommand::with_name("stats")
+    .version(env!("CARGO_PKG_VERSION"))
+    .author(env!("CARGO_PKG_AUTHORS"))
+    .about("Prints statistics of your listening history")
+    .long_about(
+      "Prints the statistics of the plays recorded while the UI runs. Without a flag it prints \
+the number of plays, the listening time and the streak of days in a row with plays, with \
+`--tracks`, `--artists` or `--albums` the most played ones. Their `--format` has \
+`%c` for the number of plays and `%l` for the listening time.",
+    )
+    .arg(format_arg().default_value_ifs(&[
+      ("tracks", None, "%c %t - %a (%l)"),
+      ("artists", None, "%c %a (%l)"),
+      ("albums", None, "%c %b - %a (%l)"),
+    ]))
+    .arg(
+      Arg::with_name("period")
+        .short("p")
+        .long("period")
+        .takes_value(true)
+        .value_name("PERIOD")
+        .possible_values(&["week", "month", "year", "all"])
+        .default_value("month")
+        .help("Specifies the period, the last 7, 30 or 365 days or all time"),
+    )
+    .arg(
+      Arg::with_name("tracks")
+        .short("t")
+        .long("tracks")
+        .help("Prints the most played tracks"),
+    )
+    .arg(
+      Arg::with_name("artists")
+        .short("a")
+        .long("artists")
+        .help("Prints the most played artists"),
+    )
+    .arg(
+      Arg::with_name("albums")
+        .short("b")
+        .long("albums")
+        .help("Prints the most played albums"),
+    )
+    .arg(
+      Arg::with_name("limit")
+        .long("limit")
+        .takes_value(true)
+        .default_value("10")
+        .help("Specifies the maximum number of results"),
+    )
+    .group(
+      ArgGroup::with_name("rankable")
+        .args(&["tracks", "artists", "albums"])
+        .multiple(false),
+    )
+}
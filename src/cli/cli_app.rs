@@ -1,11 +1,27 @@
-use crate::network::{IoEvent, Network};
+use crate::export::{ExportFormat, Library};
+use crate::history::{self, Count, Period, Stats};
+use crate::network::{IoEvent, Network, MAX_PLAYLIST_TRACKS_LIMIT};
+use crate::sleep_timer::SleepMode;
 use crate::user_config::UserConfig;
 
-use super::util::{Flag, Format, FormatType, JumpDirection, Type};
+use super::json;
+use super::util::{
+  format_output, get_duplicate_positions, parse_track_uri, Flag, Format, FormatType, JumpDirection,
+  Output, Type,
+};
 
 use anyhow::{anyhow, Result};
-use rand::{thread_rng, Rng};
+use chrono::Local;
 use rspotify::model::{context::CurrentlyPlaybackContext, PlayingItem};
+use std::{
+  fs,
+  path::Path,
+  time::{Duration, Instant},
+};
+
+// How often the sleep timer is moved on and the playback is fetched while waiting for it
+const SLEEP_TICK: Duration = Duration::from_secs(1);
+const SLEEP_POLL_INTERVAL: Duration = Duration::from_secs(5);
 
 pub struct CliApp<'a> {
   pub net: Network<'a>,
@@ -32,15 +48,76 @@ impl<'a> CliApp<'a> {
     self.net.app.lock().await.liked_song_ids_set.contains(id)
   }
 
-  pub fn format_output(&self, mut format: String, values: Vec<Format>) -> String {
-    for val in values {
-      format = format.replace(val.get_placeholder(), &val.inner(self.config.clone()));
+  pub fn format_output(&self, format: String, values: Vec<Format>) -> String {
+    format_output(format, values, &self.config)
+  }
+
+  // Prints every item with the format, or as json
+  fn format_items<T>(
+    &self,
+    output: &Output,
+    items: &[T],
+    to_format: impl Fn(&T) -> Vec<Format>,
+    to_json: impl Fn(&T) -> json::Item,
+  ) -> String {
+    match output {
+      Output::Format(format) => items
+        .iter()
+        .map(|i| self.format_output(format.clone(), to_format(i)))
+        .collect::<Vec<String>>()
+        .join("\n"),
+      Output::Json => {
+        serde_json::to_string(&items.iter().map(to_json).collect::<Vec<_>>()).unwrap_or_default()
+      }
+      Output::JsonLines => items
+        .iter()
+        .map(|i| serde_json::to_string(&to_json(i)).unwrap_or_default())
+        .collect::<Vec<String>>()
+        .join("\n"),
     }
-    // Replace unsupported flags with 'None'
-    for p in &["%a", "%b", "%t", "%p", "%h", "%u", "%d", "%v", "%f", "%s"] {
-      format = format.replace(p, "None");
+  }
+
+  // The message if nothing was found, an empty list for json
+  fn format_empty(output: &Output, message: String) -> String {
+    match output {
+      Output::Format(_) => message,
+      Output::Json => "[]".to_string(),
+      Output::JsonLines => String::new(),
+    }
+  }
+
+  // spt playback --sleep, without a running UI the timer runs here until it pauses the playback
+  pub async fn sleep(&mut self, mode: SleepMode) -> Result<()> {
+    self.net.app.lock().await.set_sleep_timer(mode)?;
+    let mut last_poll: Option<Instant> = None;
+    loop {
+      if last_poll.is_none_or(|poll| poll.elapsed() >= SLEEP_POLL_INTERVAL) {
+        if last_poll.is_some() {
+          self
+            .net
+            .handle_network_event(IoEvent::GetCurrentPlayback)
+            .await;
+        }
+        // The end of the album is noticed in the queue
+        if mode == SleepMode::EndOfAlbum {
+          self.net.handle_network_event(IoEvent::GetQueue).await;
+        }
+        last_poll = Some(Instant::now());
+      }
+
+      let events = {
+        let mut app = self.net.app.lock().await;
+        app.update_song_progress();
+        app.get_sleep_timer_events()
+      };
+      for event in events {
+        self.net.handle_network_event(event).await;
+      }
+      if self.net.app.lock().await.sleep_timer.is_none() {
+        return Ok(());
+      }
+      tokio::time::delay_for(SLEEP_TICK).await;
     }
-    format.trim().to_string()
   }
 
   // spt playback -t
@@ -59,53 +136,15 @@ impl<'a> CliApp<'a> {
   }
 
   // spt pb --share-track (share the current playing song)
-  // Basically copy-pasted the 'copy_song_url' function
-  pub async fn share_track_or_episode(&mut self) -> Result<String> {
-    let app = self.net.app.lock().await;
-    if let Some(CurrentlyPlaybackContext {
-      item: Some(item), ..
-    }) = &app.current_playback_context
-    {
-      match item {
-        PlayingItem::Track(track) => Ok(format!(
-          "https://open.spotify.com/track/{}",
-          track.id.to_owned().unwrap_or_default()
-        )),
-        PlayingItem::Episode(episode) => Ok(format!(
-          "https://open.spotify.com/episode/{}",
-          episode.id.to_owned()
-        )),
-      }
-    } else {
-      Err(anyhow!(
-        "failed to generate a shareable url for the current song"
-      ))
-    }
+  pub async fn share_track_or_episode(&mut self, output: &Output) -> Result<String> {
+    let context = self.net.app.lock().await.current_playback_context.clone();
+    format_share(get_track_url(context.as_ref()), output)
   }
 
   // spt pb --share-album (share the current album)
-  // Basically copy-pasted the 'copy_album_url' function
-  pub async fn share_album_or_show(&mut self) -> Result<String> {
-    let app = self.net.app.lock().await;
-    if let Some(CurrentlyPlaybackContext {
-      item: Some(item), ..
-    }) = &app.current_playback_context
-    {
-      match item {
-        PlayingItem::Track(track) => Ok(format!(
-          "https://open.spotify.com/album/{}",
-          track.album.id.to_owned().unwrap_or_default()
-        )),
-        PlayingItem::Episode(episode) => Ok(format!(
-          "https://open.spotify.com/show/{}",
-          episode.show.id.to_owned()
-        )),
-      }
-    } else {
-      Err(anyhow!(
-        "failed to generate a shareable url for the current song"
-      ))
-    }
+  pub async fn share_album_or_show(&mut self, output: &Output) -> Result<String> {
+    let context = self.net.app.lock().await.current_playback_context.clone();
+    format_share(get_album_url(context.as_ref()), output)
   }
 
   // spt ... -d ... (specify device to control)
@@ -177,44 +216,36 @@ impl<'a> CliApp<'a> {
   }
 
   // spt query -l ...
-  pub async fn list(&mut self, item: Type, format: &str) -> String {
+  pub async fn list(&mut self, item: Type, output: &Output) -> String {
     match item {
       Type::Device => {
         if let Some(devices) = &self.net.app.lock().await.devices {
-          devices
-            .devices
-            .iter()
-            .map(|d| {
-              self.format_output(
-                format.to_string(),
-                vec![
-                  Format::Device(d.name.clone()),
-                  Format::Volume(d.volume_percent),
-                ],
-              )
-            })
-            .collect::<Vec<String>>()
-            .join("\n")
+          self.format_items(
+            output,
+            &devices.devices,
+            |d| {
+              vec![
+                Format::Device(d.name.clone()),
+                Format::Volume(d.volume_percent.unwrap_or(0)),
+              ]
+            },
+            |d| json::Item::Device(json::Device::from(d)),
+          )
         } else {
-          "No devices available".to_string()
+          Self::format_empty(output, "No devices available".to_string())
         }
       }
       Type::Playlist => {
         self.net.handle_network_event(IoEvent::GetPlaylists).await;
         if let Some(playlists) = &self.net.app.lock().await.playlists {
-          playlists
-            .items
-            .iter()
-            .map(|p| {
-              self.format_output(
-                format.to_string(),
-                Format::from_type(FormatType::Playlist(Box::new(p.clone()))),
-              )
-            })
-            .collect::<Vec<String>>()
-            .join("\n")
+          self.format_items(
+            output,
+            &playlists.items,
+            |p| Format::from_type(FormatType::Playlist(Box::new(p.clone()))),
+            |p| json::Item::Playlist(json::Playlist::from(p)),
+          )
         } else {
-          "No playlists found".to_string()
+          Self::format_empty(output, "No playlists found".to_string())
         }
       }
       Type::Liked => {
@@ -222,26 +253,17 @@ impl<'a> CliApp<'a> {
           .net
           .handle_network_event(IoEvent::GetCurrentSavedTracks(None))
           .await;
-        let liked_songs = self
-          .net
-          .app
-          .lock()
-          .await
-          .track_table
-          .tracks
-          .iter()
-          .map(|t| {
-            self.format_output(
-              format.to_string(),
-              Format::from_type(FormatType::Track(Box::new(t.clone()))),
-            )
-          })
-          .collect::<Vec<String>>();
+        let app = self.net.app.lock().await;
         // Check if there are any liked songs
-        if liked_songs.is_empty() {
-          "No liked songs found".to_string()
+        if app.track_table.tracks.is_empty() {
+          Self::format_empty(output, "No liked songs found".to_string())
         } else {
-          liked_songs.join("\n")
+          self.format_items(
+            output,
+            &app.track_table.tracks,
+            |t| Format::from_type(FormatType::Track(Box::new(t.clone()))),
+            |t| json::Item::Track(json::Track::from(t)),
+          )
         }
       }
       // Enforced by clap
@@ -274,11 +296,6 @@ impl<'a> CliApp<'a> {
   }
 
   pub async fn seek(&mut self, seconds_str: String) -> Result<()> {
-    let seconds = match seconds_str.parse::<i32>() {
-      Ok(s) => s.abs() as u32,
-      Err(_) => return Err(anyhow!("failed to convert seconds to i32")),
-    };
-
     let (current_pos, duration) = {
       self
         .net
@@ -302,34 +319,15 @@ impl<'a> CliApp<'a> {
       }
     };
 
-    // Convert secs to ms
-    let ms = seconds * 1000;
-    // Calculate new positon
-    let position_to_seek = if seconds_str.starts_with('+') {
-      current_pos + ms
-    } else if seconds_str.starts_with('-') {
-      // Jump to the beginning if the position_to_seek would be
-      // negative, must be checked before the calculation to avoid
-      // an 'underflow'
-      if ms > current_pos {
-        0u32
-      } else {
-        current_pos - ms
-      }
-    } else {
-      // Absolute value of the track
-      seconds * 1000
-    };
-
-    // Check if position_to_seek is greater than duration (next track)
-    if position_to_seek > duration {
-      self.jump(&JumpDirection::Next).await;
-    } else {
+    match get_seek_position(&seconds_str, current_pos, duration)? {
       // This seeks to a position in the current song
-      self
-        .net
-        .handle_network_event(IoEvent::Seek(position_to_seek))
-        .await;
+      Some(position_to_seek) => {
+        self
+          .net
+          .handle_network_event(IoEvent::Seek(position_to_seek))
+          .await
+      }
+      None => self.jump(&JumpDirection::Next).await,
     }
 
     Ok(())
@@ -390,7 +388,7 @@ impl<'a> CliApp<'a> {
   }
 
   // spt playback -s
-  pub async fn get_status(&mut self, format: String) -> Result<String> {
+  pub async fn get_status(&mut self, output: &Output) -> Result<String> {
     // Update info on current playback
     self
       .net
@@ -401,169 +399,42 @@ impl<'a> CliApp<'a> {
       .handle_network_event(IoEvent::GetCurrentSavedTracks(None))
       .await;
 
-    let context = self
-      .net
-      .app
-      .lock()
-      .await
-      .current_playback_context
-      .clone()
-      .ok_or_else(|| anyhow!("no context available"))?;
-
-    let playing_item = context.item.ok_or_else(|| anyhow!("no track playing"))?;
+    let context = self.net.app.lock().await.current_playback_context.clone();
 
-    let mut hs = match playing_item {
-      PlayingItem::Track(track) => {
+    let is_liked = match context.as_ref().and_then(|context| context.item.as_ref()) {
+      Some(PlayingItem::Track(track)) => {
         let id = track.id.clone().unwrap_or_default();
-        let mut hs = Format::from_type(FormatType::Track(Box::new(track.clone())));
-        if let Some(ms) = context.progress_ms {
-          hs.push(Format::Position((ms, track.duration_ms)))
-        }
-        hs.push(Format::Flags((
-          context.repeat_state,
-          context.shuffle_state,
-          self.is_a_saved_track(&id).await,
-        )));
-        hs
-      }
-      PlayingItem::Episode(episode) => {
-        let mut hs = Format::from_type(FormatType::Episode(Box::new(episode.clone())));
-        if let Some(ms) = context.progress_ms {
-          hs.push(Format::Position((ms, episode.duration_ms)))
-        }
-        hs.push(Format::Flags((
-          context.repeat_state,
-          context.shuffle_state,
-          false,
-        )));
-        hs
+        self.is_a_saved_track(&id).await
       }
+      _ => false,
     };
 
-    hs.push(Format::Device(context.device.name));
-    hs.push(Format::Volume(context.device.volume_percent));
-    hs.push(Format::Playing(context.is_playing));
-
-    Ok(self.format_output(format, hs))
+    format_playback(context.as_ref(), is_liked, output, &self.config)
   }
 
   // spt play -u URI
   pub async fn play_uri(&mut self, uri: String, queue: bool, random: bool) {
-    let offset = if random {
-      // Only works with playlists for now
-      if uri.contains("spotify:playlist:") {
-        let id = uri.split(':').last().unwrap();
-        match self.net.spotify.playlist(id, None, None).await {
-          Ok(p) => {
-            let num = p.tracks.total;
-            Some(thread_rng().gen_range(0..num) as usize)
-          }
-          Err(e) => {
-            self
-              .net
-              .app
-              .lock()
-              .await
-              .handle_error(anyhow!(e.to_string()));
-            return;
-          }
-        }
-      } else {
-        None
-      }
-    } else {
-      None
-    };
-
-    if uri.contains("spotify:track:") {
-      if queue {
-        self
-          .net
-          .handle_network_event(IoEvent::AddItemToQueue(uri))
-          .await;
-      } else {
-        self
-          .net
-          .handle_network_event(IoEvent::StartPlayback(
-            None,
-            Some(vec![uri.clone()]),
-            Some(0),
-          ))
-          .await;
-      }
-    } else {
-      self
-        .net
-        .handle_network_event(IoEvent::StartPlayback(Some(uri.clone()), None, offset))
-        .await;
-    }
+    self
+      .net
+      .handle_network_event(IoEvent::PlayUri(uri, queue, random))
+      .await;
   }
 
   // spt play -n NAME ...
-  pub async fn play(&mut self, name: String, item: Type, queue: bool, random: bool) -> Result<()> {
+  pub async fn play(&mut self, name: String, item: Type, queue: bool, random: bool) {
     self
       .net
-      .handle_network_event(IoEvent::GetSearchResults(name.clone(), None))
+      .handle_network_event(IoEvent::PlaySearchResult(
+        name,
+        item.search_type(),
+        queue,
+        random,
+      ))
       .await;
-    // Get the uri of the first found
-    // item + the offset or return an error message
-    let uri = {
-      let results = &self.net.app.lock().await.search_results;
-      match item {
-        Type::Track => {
-          if let Some(r) = &results.tracks {
-            r.items[0].uri.clone()
-          } else {
-            return Err(anyhow!("no tracks with name '{}'", name));
-          }
-        }
-        Type::Album => {
-          if let Some(r) = &results.albums {
-            let album = &r.items[0];
-            if let Some(uri) = &album.uri {
-              uri.clone()
-            } else {
-              return Err(anyhow!("album {} has no uri", album.name));
-            }
-          } else {
-            return Err(anyhow!("no albums with name '{}'", name));
-          }
-        }
-        Type::Artist => {
-          if let Some(r) = &results.artists {
-            r.items[0].uri.clone()
-          } else {
-            return Err(anyhow!("no artists with name '{}'", name));
-          }
-        }
-        Type::Show => {
-          if let Some(r) = &results.shows {
-            r.items[0].uri.clone()
-          } else {
-            return Err(anyhow!("no shows with name '{}'", name));
-          }
-        }
-        Type::Playlist => {
-          if let Some(r) = &results.playlists {
-            let p = &r.items[0];
-            // For a random song, create a random offset
-            p.uri.clone()
-          } else {
-            return Err(anyhow!("no playlists with name '{}'", name));
-          }
-        }
-        _ => unreachable!(),
-      }
-    };
-
-    // Play or queue the uri
-    self.play_uri(uri, queue, random).await;
-
-    Ok(())
   }
 
   // spt query -s SEARCH ...
-  pub async fn query(&mut self, search: String, format: String, item: Type) -> String {
+  pub async fn query(&mut self, search: String, output: &Output, item: Type) -> String {
     self
       .net
       .handle_network_event(IoEvent::GetSearchResults(search.clone(), None))
@@ -573,91 +444,449 @@ impl<'a> CliApp<'a> {
     match item {
       Type::Playlist => {
         if let Some(results) = &app.search_results.playlists {
-          results
-            .items
-            .iter()
-            .map(|r| {
-              self.format_output(
-                format.clone(),
-                Format::from_type(FormatType::Playlist(Box::new(r.clone()))),
-              )
-            })
-            .collect::<Vec<String>>()
-            .join("\n")
+          self.format_items(
+            output,
+            &results.items,
+            |r| Format::from_type(FormatType::Playlist(Box::new(r.clone()))),
+            |r| json::Item::Playlist(json::Playlist::from(r)),
+          )
         } else {
-          format!("no playlists with name '{}'", search)
+          Self::format_empty(output, format!("no playlists with name '{}'", search))
         }
       }
       Type::Track => {
         if let Some(results) = &app.search_results.tracks {
-          results
-            .items
-            .iter()
-            .map(|r| {
-              self.format_output(
-                format.clone(),
-                Format::from_type(FormatType::Track(Box::new(r.clone()))),
-              )
-            })
-            .collect::<Vec<String>>()
-            .join("\n")
+          self.format_items(
+            output,
+            &results.items,
+            |r| Format::from_type(FormatType::Track(Box::new(r.clone()))),
+            |r| json::Item::Track(json::Track::from(r)),
+          )
         } else {
-          format!("no tracks with name '{}'", search)
+          Self::format_empty(output, format!("no tracks with name '{}'", search))
         }
       }
       Type::Artist => {
         if let Some(results) = &app.search_results.artists {
-          results
-            .items
-            .iter()
-            .map(|r| {
-              self.format_output(
-                format.clone(),
-                Format::from_type(FormatType::Artist(Box::new(r.clone()))),
-              )
-            })
-            .collect::<Vec<String>>()
-            .join("\n")
+          self.format_items(
+            output,
+            &results.items,
+            |r| Format::from_type(FormatType::Artist(Box::new(r.clone()))),
+            |r| json::Item::Artist(json::Artist::from(r)),
+          )
         } else {
-          format!("no artists with name '{}'", search)
+          Self::format_empty(output, format!("no artists with name '{}'", search))
         }
       }
       Type::Show => {
         if let Some(results) = &app.search_results.shows {
-          results
-            .items
-            .iter()
-            .map(|r| {
-              self.format_output(
-                format.clone(),
-                Format::from_type(FormatType::Show(Box::new(r.clone()))),
-              )
-            })
-            .collect::<Vec<String>>()
-            .join("\n")
+          self.format_items(
+            output,
+            &results.items,
+            |r| Format::from_type(FormatType::Show(Box::new(r.clone()))),
+            |r| json::Item::Show(json::Show::from(r)),
+          )
         } else {
-          format!("no shows with name '{}'", search)
+          Self::format_empty(output, format!("no shows with name '{}'", search))
         }
       }
       Type::Album => {
         if let Some(results) = &app.search_results.albums {
-          results
-            .items
-            .iter()
-            .map(|r| {
-              self.format_output(
-                format.clone(),
-                Format::from_type(FormatType::Album(Box::new(r.clone()))),
-              )
-            })
-            .collect::<Vec<String>>()
-            .join("\n")
+          self.format_items(
+            output,
+            &results.items,
+            |r| Format::from_type(FormatType::Album(Box::new(r.clone()))),
+            |r| json::Item::Album(json::Album::from(r)),
+          )
         } else {
-          format!("no albums with name '{}'", search)
+          Self::format_empty(output, format!("no albums with name '{}'", search))
         }
       }
       // Enforced by clap
       _ => unreachable!(),
     }
   }
+
+  // Fails with the error of the last network event, if there was one
+  async fn check_api_error(&self) -> Result<()> {
+    let api_error = self.net.app.lock().await.api_error.clone();
+    if api_error.is_empty() {
+      Ok(())
+    } else {
+      Err(anyhow!("{}", api_error))
+    }
+  }
+
+  async fn get_user_id(&mut self) -> Result<String> {
+    self.net.handle_network_event(IoEvent::GetUser).await;
+    self.check_api_error().await?;
+    let app = self.net.app.lock().await;
+    app
+      .user
+      .as_ref()
+      .map(|user| user.id.clone())
+      .ok_or_else(|| anyhow!("failed to get the current user"))
+  }
+
+  // spt playlist PLAYLIST
+  // Finds one of the user's playlists by name, uri or id
+  pub async fn find_playlist(&mut self, playlist: &str) -> Result<Option<String>> {
+    let id = self
+      .net
+      .get_all_playlists()
+      .await?
+      .into_iter()
+      .find(|p| p.id == playlist || p.uri == playlist || p.name.eq_ignore_ascii_case(playlist))
+      .map(|p| p.id);
+    // Playlists of others that the user doesn't follow aren't listed, so trust their uris
+    Ok(id.or_else(|| playlist.strip_prefix("spotify:playlist:").map(String::from)))
+  }
+
+  // spt playlist PLAYLIST --create
+  pub async fn create_playlist(&mut self, name: &str) -> Result<String> {
+    let user_id = self.get_user_id().await?;
+    let playlist = self
+      .net
+      .spotify
+      .user_playlist_create(&user_id, name, None, None)
+      .await
+      .map_err(|e| anyhow!("failed to create playlist '{}': {}", name, e))?;
+    Ok(playlist.id)
+  }
+
+  // spt playlist PLAYLIST --export
+  pub async fn get_playlist_uris(&mut self, playlist_id: &str) -> Result<Vec<String>> {
+    let mut uris = self.get_playlist_positions(playlist_id).await?;
+    uris.retain(|uri| !uri.is_empty());
+    Ok(uris)
+  }
+
+  // The uri at every position of the playlist, empty for unavailable tracks
+  async fn get_playlist_positions(&mut self, playlist_id: &str) -> Result<Vec<String>> {
+    let mut uris = Vec::new();
+    loop {
+      let page = self
+        .net
+        .get_playlist_tracks_page(playlist_id, uris.len() as u32, MAX_PLAYLIST_TRACKS_LIMIT)
+        .await?;
+      // Unavailable tracks have no track object, but still take up a position
+      uris.extend(page.items.iter().map(|item| {
+        item
+          .track
+          .as_ref()
+          .map(|track| track.uri.clone())
+          .unwrap_or_default()
+      }));
+      if page.next.is_none() || page.items.is_empty() {
+        break;
+      }
+    }
+    Ok(uris)
+  }
+
+  // spt playlist PLAYLIST --add-search SEARCH
+  pub async fn find_track_uri(&mut self, search: &str) -> Result<String> {
+    // The uri of every result on its own line
+    let uris = self
+      .query(
+        search.to_string(),
+        &Output::Format("%u".to_string()),
+        Type::Track,
+      )
+      .await;
+    uris
+      .lines()
+      .next()
+      .and_then(parse_track_uri)
+      .ok_or_else(|| anyhow!("no tracks with name '{}'", search))
+  }
+
+  // Sends the tracks in batches as large as the api takes
+  async fn change_tracks(
+    &mut self,
+    playlist_id: &str,
+    uris: &[String],
+    event: fn(String, String, Vec<String>) -> IoEvent,
+  ) -> Result<()> {
+    let user_id = self.get_user_id().await?;
+    for chunk in uris.chunks(MAX_PLAYLIST_TRACKS_LIMIT as usize) {
+      self
+        .net
+        .handle_network_event(event(
+          user_id.clone(),
+          playlist_id.to_string(),
+          chunk.to_vec(),
+        ))
+        .await;
+      self.check_api_error().await?;
+    }
+    Ok(())
+  }
+
+  // spt playlist PLAYLIST --add URI
+  pub async fn add_tracks(&mut self, playlist_id: &str, uris: &[String]) -> Result<()> {
+    self
+      .change_tracks(playlist_id, uris, IoEvent::AddTracksToPlaylist)
+      .await
+  }
+
+  // spt playlist PLAYLIST --remove URI
+  pub async fn remove_tracks(&mut self, playlist_id: &str, uris: &[String]) -> Result<()> {
+    self
+      .change_tracks(playlist_id, uris, IoEvent::RemoveTracksFromPlaylist)
+      .await
+  }
+
+  // spt playlist PLAYLIST --import
+  pub async fn replace_tracks(&mut self, playlist_id: &str, uris: &[String]) -> Result<()> {
+    let user_id = self.get_user_id().await?;
+    let (first, rest) = uris.split_at(uris.len().min(MAX_PLAYLIST_TRACKS_LIMIT as usize));
+    self
+      .net
+      .spotify
+      .user_playlist_replace_tracks(&user_id, playlist_id, first)
+      .await
+      .map_err(|e| anyhow!(e))?;
+    // Replacing takes as many tracks as adding does, the rest is appended
+    self.add_tracks(playlist_id, rest).await
+  }
+
+  // spt playlist PLAYLIST --dedupe
+  // Returns how many tracks were removed
+  pub async fn dedupe_playlist(&mut self, playlist_id: &str) -> Result<usize> {
+    // The positions are those of this snapshot
+    let mut snapshot_id = self
+      .net
+      .spotify
+      .playlist(playlist_id, None, None)
+      .await
+      .map_err(|e| anyhow!(e))?
+      .snapshot_id;
+    let uris = self.get_playlist_positions(playlist_id).await?;
+    let mut positions = get_duplicate_positions(&uris);
+    // Removing the last positions first leaves the earlier ones where they are, so every batch
+    // can go against the snapshot the one before returned
+    positions.reverse();
+    for chunk in positions.chunks(MAX_PLAYLIST_TRACKS_LIMIT as usize) {
+      let tracks = chunk
+        .iter()
+        .map(|&position| (uris[position].clone(), vec![position]))
+        .collect::<Vec<_>>();
+      snapshot_id = self
+        .net
+        .remove_playlist_positions(playlist_id, &tracks, &snapshot_id)
+        .await?;
+    }
+    Ok(positions.len())
+  }
+
+  // spt export --format FORMAT --output PATH
+  pub async fn export_library(
+    &mut self,
+    format: ExportFormat,
+    output: Option<&Path>,
+  ) -> Result<String> {
+    let library = self.net.get_library(true).await?;
+    match output {
+      Some(path) => {
+        library.write_to(format, path)?;
+        Ok(format!("exported the library to {}", path.display()))
+      }
+      None if format.is_playlist_format() => Err(anyhow!(
+        "{} exports are a directory, specify it with --output",
+        format.extension()
+      )),
+      None => library.export(format),
+    }
+  }
+
+  // spt import FILE --dry-run
+  pub async fn import_library(
+    &mut self,
+    path: &Path,
+    format: ExportFormat,
+    dry_run: bool,
+  ) -> Result<String> {
+    let backup = Library::parse(format, &fs::read_to_string(path)?)?;
+    let current = self.net.get_library(false).await?;
+    let missing = backup.get_missing(&current);
+    if missing.is_empty() {
+      return Ok("nothing to import, the library has everything in the export".to_string());
+    }
+
+    let mut lines = missing.describe();
+    if dry_run {
+      lines.push(format!(
+        "dry run, {} items would be imported",
+        missing.len()
+      ));
+    } else {
+      self.net.import_library(&missing).await?;
+      lines.push(format!("imported {} items", missing.len()));
+    }
+    Ok(lines.join("\n"))
+  }
+
+  // spt stats
+  pub fn get_stats(
+    &self,
+    period: Period,
+    item: Option<Type>,
+    limit: usize,
+    format: Option<&str>,
+  ) -> Result<String> {
+    let paths = self.net.client_config.get_or_build_paths()?;
+    let plays = history::load(&paths.history_path)?;
+    let stats = Stats::new(&plays, period, Local::now().timestamp());
+
+    let (counts, to_format): (_, fn(&Count) -> Vec<Format>) = match item {
+      None => return Ok(stats.summary()),
+      Some(Type::Track) => (&stats.top_tracks, |c| {
+        let mut values = vec![
+          Format::Track(c.name.clone()),
+          Format::Artist(c.artists.clone()),
+        ];
+        values.extend(c.uri.clone().map(Format::Uri));
+        values
+      }),
+      Some(Type::Artist) => (&stats.top_artists, |c| vec![Format::Artist(c.name.clone())]),
+      Some(Type::Album) => (&stats.top_albums, |c| {
+        let mut values = vec![
+          Format::Album(c.name.clone()),
+          Format::Artist(c.artists.clone()),
+        ];
+        values.extend(c.uri.clone().map(Format::Uri));
+        values
+      }),
+      // Enforced by clap
+      _ => unreachable!(),
+    };
+    if counts.is_empty() {
+      return Ok(format!(
+        "Nothing was played in the period ({})",
+        period.label()
+      ));
+    }
+    // The format has a default for each of the items
+    let format = format.unwrap_or_default();
+    Ok(
+      counts
+        .iter()
+        .take(limit)
+        .map(|count| {
+          let mut values = to_format(count);
+          values.push(Format::Plays(count.plays));
+          values.push(Format::ListeningTime(count.listened_ms));
+          self.format_output(format.to_string(), values)
+        })
+        .collect::<Vec<String>>()
+        .join("\n"),
+    )
+  }
+}
+
+// The url of the playing track or episode
+pub fn get_track_url(context: Option<&CurrentlyPlaybackContext>) -> Result<String> {
+  match context.and_then(|context| context.item.as_ref()) {
+    Some(PlayingItem::Track(track)) => Ok(format!(
+      "https://open.spotify.com/track/{}",
+      track.id.to_owned().unwrap_or_default()
+    )),
+    Some(PlayingItem::Episode(episode)) => Ok(format!(
+      "https://open.spotify.com/episode/{}",
+      episode.id.to_owned()
+    )),
+    None => Err(anyhow!(
+      "failed to generate a shareable url for the current song"
+    )),
+  }
+}
+
+// The url of the album of the playing track, or the show of the playing episode
+pub fn get_album_url(context: Option<&CurrentlyPlaybackContext>) -> Result<String> {
+  match context.and_then(|context| context.item.as_ref()) {
+    Some(PlayingItem::Track(track)) => Ok(format!(
+      "https://open.spotify.com/album/{}",
+      track.album.id.to_owned().unwrap_or_default()
+    )),
+    Some(PlayingItem::Episode(episode)) => Ok(format!(
+      "https://open.spotify.com/show/{}",
+      episode.show.id.to_owned()
+    )),
+    None => Err(anyhow!(
+      "failed to generate a shareable url for the current song"
+    )),
+  }
+}
+
+// Where `spt playback --seek` goes from the current position, `None` if that is past the end
+// of the item and it should skip to the next one
+pub fn get_seek_position(
+  seconds_str: &str,
+  current_pos: u32,
+  duration: u32,
+) -> Result<Option<u32>> {
+  let seconds = match seconds_str.parse::<i32>() {
+    Ok(s) => s.unsigned_abs(),
+    Err(_) => return Err(anyhow!("failed to convert seconds to i32")),
+  };
+
+  // Convert secs to ms
+  let ms = seconds * 1000;
+  // Calculate new positon
+  let position_to_seek = if seconds_str.starts_with('+') {
+    current_pos + ms
+  } else if seconds_str.starts_with('-') {
+    // Jump to the beginning if the position would be negative
+    current_pos.saturating_sub(ms)
+  } else {
+    // Absolute value of the track
+    ms
+  };
+
+  // Check if position_to_seek is greater than duration (next track)
+  if position_to_seek > duration {
+    Ok(None)
+  } else {
+    Ok(Some(position_to_seek))
+  }
+}
+
+// The url of `--share-track` and `--share-album`, in json a `share` object with a null url if
+// nothing is playing
+pub fn format_share(url: Result<String>, output: &Output) -> Result<String> {
+  match output {
+    Output::Format(_) => url,
+    Output::Json | Output::JsonLines => {
+      Ok(serde_json::to_string(&json::Item::Share(json::Share {
+        url: url.ok(),
+      }))?)
+    }
+  }
+}
+
+// The status of `spt playback` and `spt play`. The format needs something to be playing, json
+// prints the playback with a null item otherwise
+pub fn format_playback(
+  context: Option<&CurrentlyPlaybackContext>,
+  is_liked: bool,
+  output: &Output,
+  config: &UserConfig,
+) -> Result<String> {
+  Ok(match output {
+    Output::Format(format) => {
+      let context = context.ok_or_else(|| anyhow!("no context available"))?;
+      if context.item.is_none() {
+        return Err(anyhow!("no track playing"));
+      }
+      format_output(
+        format.clone(),
+        Format::from_context(context, is_liked),
+        config,
+      )
+    }
+    Output::Json | Output::JsonLines => serde_json::to_string(&json::Item::Playback(
+      json::Playback::new(context, is_liked),
+    ))?,
+  })
 }
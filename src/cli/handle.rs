@@ -1,13 +1,17 @@
+use crate::export::ExportFormat;
+use crate::history::Period;
 use crate::network::{IoEvent, Network};
+use crate::sleep_timer::SleepMode;
 use crate::user_config::UserConfig;
 
 use super::{
-  util::{Flag, JumpDirection, Type},
+  util::{parse_track_uris, Flag, JumpDirection, Output, Type},
   CliApp,
 };
 
 use anyhow::{anyhow, Result};
 use clap::ArgMatches;
+use std::{io::Read, path::Path};
 
 // Handle the different subcommands
 pub async fn handle_matches(
@@ -49,13 +53,13 @@ pub async fn handle_matches(
   // Evalute the subcommand
   let output = match cmd.as_str() {
     "playback" => {
-      let format = matches.value_of("format").unwrap();
+      let output = Output::from_matches(matches);
 
       // Commands that are 'single'
       if matches.is_present("share-track") {
-        return cli.share_track_or_episode().await;
+        return cli.share_track_or_episode(&output).await;
       } else if matches.is_present("share-album") {
-        return cli.share_album_or_show().await;
+        return cli.share_album_or_show(&output).await;
       }
 
       // Run the action, and print out the status
@@ -85,26 +89,37 @@ pub async fn handle_matches(
       if let Some(secs) = matches.value_of("seek") {
         cli.seek(secs.to_string()).await?;
       }
+      if let Some(when) = matches.value_of("sleep") {
+        match SleepMode::parse(when) {
+          Some(mode) => cli.sleep(mode).await?,
+          None if when == "off" => {
+            return Err(anyhow!(
+              "there is no running UI with a sleep timer to cancel"
+            ))
+          }
+          None => return Err(get_sleep_mode_error(when)),
+        }
+      }
 
       // Print out the status if no errors were found
-      cli.get_status(format.to_string()).await
+      cli.get_status(&output).await
     }
     "play" => {
       let queue = matches.is_present("queue");
       let random = matches.is_present("random");
-      let format = matches.value_of("format").unwrap();
+      let output = Output::from_matches(matches);
 
       if let Some(uri) = matches.value_of("uri") {
         cli.play_uri(uri.to_string(), queue, random).await;
       } else if let Some(name) = matches.value_of("name") {
         let category = Type::play_from_matches(matches);
-        cli.play(name.to_string(), category, queue, random).await?;
+        cli.play(name.to_string(), category, queue, random).await;
       }
 
-      cli.get_status(format.to_string()).await
+      cli.get_status(&output).await
     }
     "list" => {
-      let format = matches.value_of("format").unwrap().to_string();
+      let output = Output::from_matches(matches);
 
       // Update the limits for the list and search functions
       // I think the small and big search limits are very confusing
@@ -114,10 +129,10 @@ pub async fn handle_matches(
       }
 
       let category = Type::list_from_matches(matches);
-      Ok(cli.list(category, &format).await)
+      Ok(cli.list(category, &output).await)
     }
     "search" => {
-      let format = matches.value_of("format").unwrap().to_string();
+      let output = Output::from_matches(matches);
 
       // Update the limits for the list and search functions
       // I think the small and big search limits are very confusing
@@ -131,12 +146,101 @@ pub async fn handle_matches(
         cli
           .query(
             matches.value_of("search").unwrap().to_string(),
-            format,
+            &output,
             category,
           )
           .await,
       )
     }
+    "playlist" => {
+      let playlist = matches.value_of("playlist").unwrap();
+      // Prints what was changed, one change per line
+      let mut changes = Vec::new();
+
+      let playlist_id = match cli.find_playlist(playlist).await? {
+        Some(id) => id,
+        None if matches.is_present("create") => {
+          let id = cli.create_playlist(playlist).await?;
+          changes.push(format!("created playlist '{}'", playlist));
+          id
+        }
+        None => return Err(anyhow!("no playlist with name '{}'", playlist)),
+      };
+
+      if matches.is_present("export") {
+        return Ok(cli.get_playlist_uris(&playlist_id).await?.join("\n"));
+      }
+      if matches.is_present("import") {
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input)?;
+        let uris = parse_track_uris(input.lines())?;
+        // Most likely whatever produced the uris failed
+        if uris.is_empty() {
+          return Err(anyhow!(
+            "no track uris on stdin, leaving the playlist as it is"
+          ));
+        }
+        cli.replace_tracks(&playlist_id, &uris).await?;
+        changes.push(format!("imported {} tracks", uris.len()));
+      }
+
+      let mut added = parse_track_uris(matches.values_of("add").into_iter().flatten())?;
+      for search in matches.values_of("add-search").into_iter().flatten() {
+        added.push(cli.find_track_uri(search).await?);
+      }
+      if !added.is_empty() {
+        cli.add_tracks(&playlist_id, &added).await?;
+        changes.push(format!("added {} tracks", added.len()));
+      }
+
+      let removed = parse_track_uris(matches.values_of("remove").into_iter().flatten())?;
+      if !removed.is_empty() {
+        cli.remove_tracks(&playlist_id, &removed).await?;
+        changes.push(format!("removed {} tracks", removed.len()));
+      }
+
+      if matches.is_present("dedupe") {
+        let duplicates = cli.dedupe_playlist(&playlist_id).await?;
+        changes.push(format!("removed {} duplicates", duplicates));
+      }
+
+      Ok(changes.join("\n"))
+    }
+    "export" => {
+      let output = matches.value_of("output").map(Path::new);
+      let format = matches
+        .value_of("format")
+        .and_then(ExportFormat::parse)
+        .or_else(|| output.and_then(ExportFormat::from_path))
+        .unwrap_or(ExportFormat::Json);
+      cli.export_library(format, output).await
+    }
+    "import" => {
+      let path = Path::new(matches.value_of("file").unwrap());
+      let format = matches
+        .value_of("format")
+        .and_then(ExportFormat::parse)
+        .or_else(|| ExportFormat::from_path(path))
+        .ok_or_else(|| anyhow!("can't tell the format of the export, specify it with --format"))?;
+      cli
+        .import_library(path, format, matches.is_present("dry-run"))
+        .await
+    }
+    "stats" => {
+      // Clap enforces the possible values and the defaults
+      let period = Period::parse(matches.value_of("period").unwrap()).unwrap();
+      let limit = matches
+        .value_of("limit")
+        .unwrap()
+        .parse()
+        .map_err(|_| anyhow!("the limit must be a number"))?;
+      cli.get_stats(
+        period,
+        Type::stats_from_matches(matches),
+        limit,
+        matches.value_of("format"),
+      )
+    }
     // Clap enforces that one of the things above is specified
     _ => unreachable!(),
   };
@@ -149,3 +253,10 @@ pub async fn handle_matches(
     Err(anyhow!("{}", api_error))
   }
 }
+
+pub fn get_sleep_mode_error(when: &str) -> anyhow::Error {
+  anyhow!(
+    "can't tell when to sleep from `{}`, use minutes, a duration like 1h30m, `track` or `album`",
+    when
+  )
+}
@@ -0,0 +1,348 @@
+// The objects printed with `--json` and `--jsonl`. Scripts depend on these, so fields may be
+// added but never renamed or removed. Ids and uris are null for local files
+use crate::device::Device as UserDevice;
+
+use rspotify::{
+  model::{
+    album::SimplifiedAlbum,
+    artist::{FullArtist, SimplifiedArtist},
+    context::CurrentlyPlaybackContext,
+    device::Device as PlaybackDevice,
+    playlist::SimplifiedPlaylist,
+    show::{FullEpisode, SimplifiedShow},
+    track::FullTrack,
+    PlayingItem,
+  },
+  senum::{DeviceType, RepeatState},
+};
+use serde::Serialize;
+
+/// Every printed object, tagged with its `type`
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Item {
+  Track(Track),
+  Episode(Episode),
+  Album(Album),
+  Artist(Artist),
+  Playlist(Playlist),
+  Show(Show),
+  Device(Device),
+  Playback(Playback),
+  Share(Share),
+}
+
+#[derive(Debug, Serialize)]
+pub struct Artist {
+  pub id: Option<String>,
+  pub uri: Option<String>,
+  pub name: String,
+  // Only known for artists that were searched for
+  pub genres: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Album {
+  pub id: Option<String>,
+  pub uri: Option<String>,
+  pub name: String,
+  pub album_type: Option<String>,
+  pub release_date: Option<String>,
+  pub artists: Vec<Artist>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Track {
+  pub id: Option<String>,
+  pub uri: String,
+  pub name: String,
+  pub duration_ms: u32,
+  pub track_number: u32,
+  pub explicit: bool,
+  pub is_local: bool,
+  pub album: Album,
+  pub artists: Vec<Artist>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Show {
+  pub id: String,
+  pub uri: String,
+  pub name: String,
+  pub publisher: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Episode {
+  pub id: String,
+  pub uri: String,
+  pub name: String,
+  pub duration_ms: u32,
+  pub release_date: String,
+  pub explicit: bool,
+  pub show: Show,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Playlist {
+  pub id: String,
+  pub uri: String,
+  pub name: String,
+  pub owner: String,
+  pub collaborative: bool,
+  pub public: Option<bool>,
+  pub total_tracks: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Device {
+  // Null if the device cannot be controlled
+  pub id: Option<String>,
+  pub name: String,
+  pub device_type: String,
+  pub is_active: bool,
+  pub is_restricted: bool,
+  pub volume_percent: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Playback {
+  pub is_playing: bool,
+  pub progress_ms: Option<u32>,
+  pub shuffle: bool,
+  pub repeat: RepeatState,
+  pub liked: bool,
+  // Null if no device is playing
+  pub device: Option<Device>,
+  // Null if nothing is playing, otherwise a track or an episode tagged with its `type`
+  pub item: Option<Box<Item>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Share {
+  // Null if nothing is playing
+  pub url: Option<String>,
+}
+
+impl From<&SimplifiedArtist> for Artist {
+  fn from(artist: &SimplifiedArtist) -> Self {
+    Artist {
+      id: artist.id.clone(),
+      uri: artist.uri.clone(),
+      name: artist.name.clone(),
+      genres: vec![],
+    }
+  }
+}
+
+impl From<&FullArtist> for Artist {
+  fn from(artist: &FullArtist) -> Self {
+    Artist {
+      id: Some(artist.id.clone()),
+      uri: Some(artist.uri.clone()),
+      name: artist.name.clone(),
+      genres: artist.genres.clone(),
+    }
+  }
+}
+
+impl From<&SimplifiedAlbum> for Album {
+  fn from(album: &SimplifiedAlbum) -> Self {
+    Album {
+      id: album.id.clone(),
+      uri: album.uri.clone(),
+      name: album.name.clone(),
+      album_type: album.album_type.clone(),
+      release_date: album.release_date.clone(),
+      artists: album.artists.iter().map(Artist::from).collect(),
+    }
+  }
+}
+
+impl From<&FullTrack> for Track {
+  fn from(track: &FullTrack) -> Self {
+    Track {
+      id: track.id.clone(),
+      uri: track.uri.clone(),
+      name: track.name.clone(),
+      duration_ms: track.duration_ms,
+      track_number: track.track_number,
+      explicit: track.explicit,
+      is_local: track.is_local,
+      album: Album::from(&track.album),
+      artists: track.artists.iter().map(Artist::from).collect(),
+    }
+  }
+}
+
+impl From<&SimplifiedShow> for Show {
+  fn from(show: &SimplifiedShow) -> Self {
+    Show {
+      id: show.id.clone(),
+      uri: show.uri.clone(),
+      name: show.name.clone(),
+      publisher: show.publisher.clone(),
+    }
+  }
+}
+
+impl From<&FullEpisode> for Episode {
+  fn from(episode: &FullEpisode) -> Self {
+    Episode {
+      id: episode.id.clone(),
+      uri: episode.uri.clone(),
+      name: episode.name.clone(),
+      duration_ms: episode.duration_ms,
+      release_date: episode.release_date.clone(),
+      explicit: episode.explicit,
+      show: Show::from(&episode.show),
+    }
+  }
+}
+
+impl From<&SimplifiedPlaylist> for Playlist {
+  fn from(playlist: &SimplifiedPlaylist) -> Self {
+    Playlist {
+      id: playlist.id.clone(),
+      uri: playlist.uri.clone(),
+      name: playlist.name.clone(),
+      owner: playlist
+        .owner
+        .display_name
+        .clone()
+        .unwrap_or_else(|| playlist.owner.id.clone()),
+      collaborative: playlist.collaborative,
+      public: playlist.public,
+      total_tracks: playlist.tracks.get("total").and_then(|t| t.as_u64()),
+    }
+  }
+}
+
+impl From<&UserDevice> for Device {
+  fn from(device: &UserDevice) -> Self {
+    Device {
+      id: Some(device.id.clone()).filter(|id| !id.is_empty()),
+      name: device.name.clone(),
+      device_type: device.device_type.clone(),
+      is_active: device.is_active,
+      is_restricted: device.is_restricted,
+      volume_percent: device.volume_percent,
+    }
+  }
+}
+
+// The names the api gives the types, as `list --devices` prints them
+fn get_device_type(device_type: &DeviceType) -> &'static str {
+  match device_type {
+    DeviceType::Computer => "Computer",
+    DeviceType::Tablet => "Tablet",
+    DeviceType::Smartphone => "Smartphone",
+    DeviceType::Speaker => "Speaker",
+    DeviceType::TV => "TV",
+    DeviceType::AVR => "AVR",
+    DeviceType::STB => "STB",
+    DeviceType::AudioDongle => "AudioDongle",
+    DeviceType::GameConsole => "GameConsole",
+    DeviceType::CastVideo => "CastVideo",
+    DeviceType::CastAudio => "CastAudio",
+    DeviceType::Automobile => "Automobile",
+    DeviceType::Unknown => "Unknown",
+  }
+}
+
+impl From<&PlaybackDevice> for Device {
+  fn from(device: &PlaybackDevice) -> Self {
+    Device {
+      id: Some(device.id.clone()).filter(|id| !id.is_empty()),
+      name: device.name.clone(),
+      device_type: get_device_type(&device._type).to_string(),
+      is_active: device.is_active,
+      is_restricted: device.is_restricted,
+      volume_percent: Some(device.volume_percent),
+    }
+  }
+}
+
+impl Playback {
+  /// The playback with `item` null if nothing is playing
+  pub fn new(context: Option<&CurrentlyPlaybackContext>, is_liked: bool) -> Self {
+    Playback {
+      is_playing: context.is_some_and(|context| context.is_playing),
+      progress_ms: context.and_then(|context| context.progress_ms),
+      shuffle: context.is_some_and(|context| context.shuffle_state),
+      repeat: context.map_or(RepeatState::Off, |context| context.repeat_state),
+      liked: is_liked,
+      device: context.map(|context| Device::from(&context.device)),
+      item: context
+        .and_then(|context| context.item.as_ref())
+        .map(|item| {
+          Box::new(match item {
+            PlayingItem::Track(track) => Item::Track(Track::from(track)),
+            PlayingItem::Episode(episode) => Item::Episode(Episode::from(episode)),
+          })
+        }),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn tags_items_with_their_type() {
+    let device = UserDevice {
+      name: "Kitchen".to_string(),
+      device_type: "Speaker".to_string(),
+      is_restricted: true,
+      ..Default::default()
+    };
+    assert_eq!(
+      serde_json::to_value(Item::Device(Device::from(&device))).unwrap(),
+      json!({
+        "type": "device",
+        "id": null,
+        "name": "Kitchen",
+        "device_type": "Speaker",
+        "is_active": false,
+        "is_restricted": true,
+        "volume_percent": null,
+      })
+    );
+
+    let artist = Artist {
+      id: Some("1".to_string()),
+      uri: Some("spotify:artist:1".to_string()),
+      name: "Artist".to_string(),
+      genres: vec![],
+    };
+    assert_eq!(
+      serde_json::to_value(Item::Artist(artist)).unwrap()["type"],
+      "artist"
+    );
+  }
+
+  #[test]
+  fn names_device_types_like_the_api() {
+    assert_eq!(get_device_type(&DeviceType::CastAudio), "CastAudio");
+    assert_eq!(get_device_type(&DeviceType::TV), "TV");
+  }
+
+  #[test]
+  fn prints_the_playback_without_an_item_if_nothing_plays() {
+    assert_eq!(
+      serde_json::to_value(Item::Playback(Playback::new(None, false))).unwrap(),
+      json!({
+        "type": "playback",
+        "is_playing": false,
+        "progress_ms": null,
+        "shuffle": false,
+        "repeat": "off",
+        "liked": false,
+        "device": null,
+        "item": null,
+      })
+    );
+  }
+}
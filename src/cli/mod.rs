@@ -1,8 +1,14 @@
 mod clap;
 mod cli_app;
 mod handle;
+mod json;
+#[cfg(unix)]
+mod socket;
 mod util;
 
-pub use self::clap::{list_subcommand, play_subcommand, playback_subcommand, search_subcommand};
+pub use self::clap::clap_app;
 use cli_app::CliApp;
 pub use handle::handle_matches;
+#[cfg(unix)]
+pub use socket::{forward_to_running_instance, start_socket_server};
+pub use util::{format_output, Format};
@@ -0,0 +1,322 @@
+use crate::app::App;
+use crate::network::IoEvent;
+use crate::sleep_timer::SleepMode;
+use crate::user_config::UserConfig;
+
+use super::{
+  clap::clap_app,
+  cli_app::{format_playback, format_share, get_album_url, get_seek_position, get_track_url},
+  handle::get_sleep_mode_error,
+  util::{Flag, JumpDirection, Output, Type},
+};
+
+use anyhow::{anyhow, Result};
+use clap::ArgMatches;
+use rspotify::model::PlayingItem;
+use std::{fs, path::PathBuf, sync::Arc, time::Duration};
+use tokio::{
+  io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+  net::{UnixListener, UnixStream},
+  sync::{Mutex, Notify},
+  time::timeout,
+};
+
+const SOCKET_FILE: &str = "spotify-tui.sock";
+// How long a command waits for the UI to send its requests before answering with the status
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Requests are the arguments `spt` was called with, responses the output it should print.
+// Both are sent as a single line of json
+type Response = std::result::Result<String, String>;
+
+fn get_socket_path() -> Option<PathBuf> {
+  dirs::runtime_dir()
+    .or_else(|| dirs::home_dir().map(|home| home.join(".config").join("spotify-tui")))
+    .map(|dir| dir.join(SOCKET_FILE))
+}
+
+/// Runs the command in the instance of the TUI that is already running, returns `None` when
+/// there is no such instance
+pub async fn forward_to_running_instance(args: Vec<String>) -> Option<Result<String>> {
+  let stream = UnixStream::connect(get_socket_path()?).await.ok()?;
+  Some(send_request(stream, args).await)
+}
+
+async fn send_request(mut stream: UnixStream, args: Vec<String>) -> Result<String> {
+  let mut request = serde_json::to_string(&args)?;
+  request.push('\n');
+  stream.write_all(request.as_bytes()).await?;
+
+  let mut line = String::new();
+  BufReader::new(stream).read_line(&mut line).await?;
+  let response: Response = serde_json::from_str(&line)?;
+  response.map_err(|e| anyhow!(e))
+}
+
+/// Serves the `playback` and `play` commands of other `spt` invocations until the TUI exits
+#[tokio::main]
+pub async fn start_socket_server(app: Arc<Mutex<App>>, user_config: UserConfig) {
+  if let Err(e) = serve(&app, &user_config).await {
+    app
+      .lock()
+      .await
+      .handle_error(anyhow!("Could not serve the control socket: {}", e));
+  }
+}
+
+async fn serve(app: &Arc<Mutex<App>>, user_config: &UserConfig) -> Result<()> {
+  let path = get_socket_path().ok_or_else(|| anyhow!("no directory to create it in"))?;
+  // Another instance is serving the socket already
+  if UnixStream::connect(&path).await.is_ok() {
+    return Ok(());
+  }
+  // The socket is left behind when the app exits, so an old one may still be there
+  if path.exists() {
+    fs::remove_file(&path)?;
+  }
+
+  let mut listener = UnixListener::bind(&path)?;
+  loop {
+    let (stream, _) = listener.accept().await?;
+    let app = Arc::clone(app);
+    let user_config = user_config.clone();
+    // A client hanging up early is no reason to stop serving the others
+    tokio::spawn(async move {
+      let _ = handle_connection(stream, &app, &user_config).await;
+    });
+  }
+}
+
+async fn handle_connection(
+  mut stream: UnixStream,
+  app: &Arc<Mutex<App>>,
+  user_config: &UserConfig,
+) -> Result<()> {
+  let (reader, mut writer) = stream.split();
+  let mut line = String::new();
+  BufReader::new(reader).read_line(&mut line).await?;
+
+  let response: Response = match serde_json::from_str(&line) {
+    Ok(args) => run_command(args, app, user_config)
+      .await
+      .map_err(|e| e.to_string()),
+    Err(e) => Err(format!("invalid request: {}", e)),
+  };
+
+  let mut response = serde_json::to_string(&response)?;
+  response.push('\n');
+  writer.write_all(response.as_bytes()).await?;
+  Ok(())
+}
+
+// Runs the command with the network of the UI and answers with the playback it ends up at
+async fn run_command(
+  args: Vec<String>,
+  app: &Arc<Mutex<App>>,
+  user_config: &UserConfig,
+) -> Result<String> {
+  let matches = clap_app().get_matches_from_safe(args)?;
+  let (cmd, m) = match matches.subcommand() {
+    (cmd, Some(m)) if cmd == "playback" || cmd == "play" => (cmd, m),
+    _ => {
+      return Err(anyhow!(
+        "only `playback` and `play` can be sent to a running instance"
+      ))
+    }
+  };
+  let output = Output::from_matches(m);
+
+  let notify = Arc::new(Notify::new());
+  let previous_error_count = {
+    let mut app = app.lock().await;
+    if m.is_present("share-track") {
+      return format_share(
+        get_track_url(app.current_playback_context.as_ref()),
+        &output,
+      );
+    } else if m.is_present("share-album") {
+      return format_share(
+        get_album_url(app.current_playback_context.as_ref()),
+        &output,
+      );
+    }
+
+    for event in get_events(cmd, m, &mut app)? {
+      app.dispatch(event);
+    }
+    app.dispatch(IoEvent::GetCurrentPlayback);
+    app.dispatch(IoEvent::Notify(Arc::clone(&notify)));
+    app.api_error_count
+  };
+  // The UI handles its events in order, so the playback is up to date once it gets here
+  let _ = timeout(RESPONSE_TIMEOUT, notify.notified()).await;
+
+  let app = app.lock().await;
+  if app.api_error_count != previous_error_count {
+    return Err(anyhow!("{}", app.api_error));
+  }
+  let context = app.current_playback_context.as_ref();
+  let is_liked = match context.and_then(|context| context.item.as_ref()) {
+    Some(PlayingItem::Track(track)) => track
+      .id
+      .as_ref()
+      .is_some_and(|id| app.liked_song_ids_set.contains(id)),
+    _ => false,
+  };
+  format_playback(context, is_liked, &output, user_config)
+}
+
+fn get_device_id(app: &App, name: &str) -> Result<String> {
+  app
+    .devices
+    .as_ref()
+    .and_then(|devices| devices.devices.iter().find(|device| device.name == name))
+    .map(|device| device.id.clone())
+    .ok_or_else(|| anyhow!("no device with name '{}'", name))
+}
+
+// The events that run the command, in the order `spt` runs its flags. Everything is checked
+// against the state of the UI before any of them is sent, so a command runs whole or not at all
+fn get_events(cmd: &str, m: &ArgMatches<'_>, app: &mut App) -> Result<Vec<IoEvent>> {
+  let mut events = Vec::new();
+  let context = app.current_playback_context.as_ref();
+
+  if let Some(device) = m.value_of("device") {
+    let id = get_device_id(app, device)?;
+    if context.map(|context| &context.device.id) != Some(&id) {
+      events.push(IoEvent::TransferPlaybackToDevice(id));
+    }
+  }
+
+  if cmd == "play" {
+    let queue = m.is_present("queue");
+    let random = m.is_present("random");
+    if let Some(uri) = m.value_of("uri") {
+      events.push(IoEvent::PlayUri(uri.to_string(), queue, random));
+    } else if let Some(name) = m.value_of("name") {
+      let search_type = Type::play_from_matches(m).search_type();
+      events.push(IoEvent::PlaySearchResult(
+        name.to_string(),
+        search_type,
+        queue,
+        random,
+      ));
+    }
+    return Ok(events);
+  }
+
+  if m.is_present("toggle") {
+    events.push(if context.is_some_and(|context| context.is_playing) {
+      IoEvent::PausePlayback
+    } else {
+      IoEvent::StartPlayback(None, None, None)
+    });
+  }
+  if let Some(device) = m.value_of("transfer") {
+    events.push(IoEvent::TransferPlaybackToDevice(get_device_id(
+      app, device,
+    )?));
+  }
+  if m.is_present("flags") {
+    let context = context.ok_or_else(|| anyhow!("no context available"))?;
+    for flag in Flag::from_matches(m) {
+      match flag {
+        Flag::Like(like) => {
+          let id = match &context.item {
+            Some(PlayingItem::Track(track)) => {
+              track.id.clone().ok_or_else(|| anyhow!("item has no id"))?
+            }
+            Some(PlayingItem::Episode(_)) => {
+              return Err(anyhow!("saving episodes not yet implemented"))
+            }
+            None => return Err(anyhow!("no item playing")),
+          };
+          if like != app.liked_song_ids_set.contains(&id) {
+            events.push(IoEvent::ToggleSaveTrack(id));
+          }
+        }
+        Flag::Shuffle => events.push(IoEvent::Shuffle(context.shuffle_state)),
+        Flag::Repeat => events.push(IoEvent::Repeat(context.repeat_state)),
+      }
+    }
+  }
+  if m.is_present("jumps") {
+    let (direction, amount) = JumpDirection::from_matches(m);
+    for _ in 0..amount {
+      events.push(match direction {
+        JumpDirection::Next => IoEvent::NextTrack,
+        JumpDirection::Previous => IoEvent::PreviousTrack,
+      });
+    }
+  }
+  if let Some(volume) = m.value_of("volume") {
+    match volume.parse::<u8>() {
+      Ok(volume) if volume <= 100 => events.push(IoEvent::ChangeVolume(volume)),
+      _ => return Err(anyhow!("volume must be between 0 and 100")),
+    }
+  }
+  if let Some(seconds) = m.value_of("seek") {
+    let duration = match context.and_then(|context| context.item.as_ref()) {
+      Some(PlayingItem::Track(track)) => track.duration_ms,
+      Some(PlayingItem::Episode(episode)) => episode.duration_ms,
+      None => return Err(anyhow!("no context available")),
+    };
+    // The progress the UI shows is more recent than the one of the last poll
+    let progress = app.song_progress_ms as u32;
+    events.push(match get_seek_position(seconds, progress, duration)? {
+      Some(position) => IoEvent::Seek(position),
+      None => IoEvent::NextTrack,
+    });
+  }
+  if let Some(when) = m.value_of("sleep") {
+    match SleepMode::parse(when) {
+      // The UI runs the timer, so it fades and shows it
+      Some(mode) => app.set_sleep_timer(mode)?,
+      None if when == "off" => app.cancel_sleep_timer(),
+      None => return Err(get_sleep_mode_error(when)),
+    }
+  }
+  Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn request_gets_the_output_of_the_command() {
+    let (client, mut server) = UnixStream::pair().unwrap();
+    let instance = tokio::spawn(async move {
+      let (reader, mut writer) = server.split();
+      let mut line = String::new();
+      BufReader::new(reader).read_line(&mut line).await.unwrap();
+      let args: Vec<String> = serde_json::from_str(&line).unwrap();
+      let response: Response = Ok(args.join(" "));
+      let response = format!("{}\n", serde_json::to_string(&response).unwrap());
+      writer.write_all(response.as_bytes()).await.unwrap();
+    });
+
+    let args = vec!["spt".to_string(), "pb".to_string(), "-t".to_string()];
+    let output = send_request(client, args).await.unwrap();
+    instance.await.unwrap();
+    assert_eq!(output, "spt pb -t");
+  }
+
+  #[test]
+  fn commands_become_events_of_the_ui() {
+    let (tx, _rx) = std::sync::mpsc::channel();
+    let mut app = App::new(tx, UserConfig::new(), std::time::SystemTime::now());
+    let args = ["spt", "playback", "--next", "--next"];
+    let matches = clap_app().get_matches_from(args);
+    let m = matches.subcommand_matches("playback").unwrap();
+    let events = get_events("playback", m, &mut app).unwrap();
+    assert!(matches!(
+      events.as_slice(),
+      [IoEvent::NextTrack, IoEvent::NextTrack]
+    ));
+
+    let matches = clap_app().get_matches_from(["spt", "playback", "--transfer", "Kitchen"]);
+    let m = matches.subcommand_matches("playback").unwrap();
+    assert!(get_events("playback", m, &mut app).is_err());
+  }
+}
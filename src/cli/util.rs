@@ -2,9 +2,10 @@ use clap::ArgMatches;
 use rspotify::{
   model::{
     album::SimplifiedAlbum, artist::FullArtist, artist::SimplifiedArtist,
-    playlist::SimplifiedPlaylist, show::FullEpisode, show::SimplifiedShow, track::FullTrack,
+    context::CurrentlyPlaybackContext, playlist::SimplifiedPlaylist, show::FullEpisode,
+    show::SimplifiedShow, track::FullTrack, PlayingItem,
   },
-  senum::RepeatState,
+  senum::{RepeatState, SearchType},
 };
 
 use crate::user_config::UserConfig;
@@ -58,6 +59,32 @@ impl Type {
     }
   }
 
+  // What to search for to play a result of this type
+  pub fn search_type(&self) -> SearchType {
+    match self {
+      Self::Playlist => SearchType::Playlist,
+      Self::Track => SearchType::Track,
+      Self::Artist => SearchType::Artist,
+      Self::Album => SearchType::Album,
+      Self::Show => SearchType::Show,
+      // Enforced by clap
+      Self::Device | Self::Liked => unreachable!(),
+    }
+  }
+
+  // Nothing is the summary of the stats
+  pub fn stats_from_matches(m: &ArgMatches<'_>) -> Option<Self> {
+    if m.is_present("tracks") {
+      Some(Self::Track)
+    } else if m.is_present("artists") {
+      Some(Self::Artist)
+    } else if m.is_present("albums") {
+      Some(Self::Album)
+    } else {
+      None
+    }
+  }
+
   pub fn list_from_matches(m: &ArgMatches<'_>) -> Self {
     if m.is_present("playlists") {
       Self::Playlist
@@ -127,6 +154,28 @@ impl JumpDirection {
   }
 }
 
+// How the results are printed (-f / --format, --json and --jsonl flags)
+pub enum Output {
+  Format(String),
+  // A single json value, an array for lists
+  Json,
+  // A json object on each line
+  JsonLines,
+}
+
+impl Output {
+  pub fn from_matches(m: &ArgMatches<'_>) -> Self {
+    if m.is_present("json") {
+      Self::Json
+    } else if m.is_present("jsonl") {
+      Self::JsonLines
+    // Every subcommand with the json flags has a default format
+    } else {
+      Self::Format(m.value_of("format").unwrap().to_string())
+    }
+  }
+}
+
 // For fomatting (-f / --format flag)
 
 // Types to create a Format enum from
@@ -157,6 +206,23 @@ pub enum Format {
   // This is a bit long, should it be splitted up?
   Flags((RepeatState, bool, bool)),
   Playing(bool),
+  // The number of times something was played
+  Plays(usize),
+  ListeningTime(u64),
+}
+
+// Replaces the placeholders of the format with the values
+pub fn format_output(mut format: String, values: Vec<Format>, conf: &UserConfig) -> String {
+  for val in values {
+    format = format.replace(val.get_placeholder(), &val.inner(conf.clone()));
+  }
+  // Replace unsupported flags with 'None'
+  for p in &[
+    "%a", "%b", "%t", "%p", "%h", "%u", "%d", "%v", "%f", "%s", "%c", "%l",
+  ] {
+    format = format.replace(p, "None");
+  }
+  format.trim().to_string()
 }
 
 pub fn join_artists(a: Vec<SimplifiedArtist>) -> String {
@@ -203,6 +269,36 @@ impl Format {
     }
   }
 
+  // Extract the information about the playing item and the device
+  pub fn from_context(context: &CurrentlyPlaybackContext, is_liked: bool) -> Vec<Self> {
+    let mut values = match &context.item {
+      Some(PlayingItem::Track(track)) => {
+        let mut values = Self::from_type(FormatType::Track(Box::new(track.clone())));
+        if let Some(ms) = context.progress_ms {
+          values.push(Self::Position((ms, track.duration_ms)))
+        }
+        values
+      }
+      Some(PlayingItem::Episode(episode)) => {
+        let mut values = Self::from_type(FormatType::Episode(Box::new(episode.clone())));
+        if let Some(ms) = context.progress_ms {
+          values.push(Self::Position((ms, episode.duration_ms)))
+        }
+        values
+      }
+      None => vec![],
+    };
+    values.push(Self::Flags((
+      context.repeat_state,
+      context.shuffle_state,
+      is_liked,
+    )));
+    values.push(Self::Device(context.device.name.clone()));
+    values.push(Self::Volume(context.device.volume_percent));
+    values.push(Self::Playing(context.is_playing));
+    values
+  }
+
   // Is there a better way?
   pub fn inner(&self, conf: UserConfig) -> String {
     match self {
@@ -252,6 +348,8 @@ impl Format {
           conf.behavior.paused_icon
         }
       }
+      Self::Plays(s) => s.to_string(),
+      Self::ListeningTime(ms) => crate::history::format_listening_time(*ms),
     }
   }
 
@@ -268,6 +366,97 @@ impl Format {
       Self::Position(_) => "%r",
       Self::Flags(_) => "%f",
       Self::Playing(_) => "%s",
+      Self::Plays(_) => "%c",
+      Self::ListeningTime(_) => "%l",
     }
   }
 }
+
+// For the playlist subcommand
+
+// Accepts `spotify:track:ID`, `https://open.spotify.com/track/ID` and plain ids
+pub fn parse_track_uri(s: &str) -> Option<String> {
+  let s = s.trim();
+  let id = if let Some(id) = s.strip_prefix("spotify:track:") {
+    id
+  } else if let Some(path) = s
+    .strip_prefix("https://open.spotify.com/track/")
+    .or_else(|| s.strip_prefix("http://open.spotify.com/track/"))
+  {
+    path.split(['?', '/']).next().unwrap_or_default()
+  } else {
+    s
+  };
+  if !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric()) {
+    Some(format!("spotify:track:{}", id))
+  } else {
+    None
+  }
+}
+
+// Reads one uri per line, skipping empty lines and `#` comments like the ones in m3u files
+pub fn parse_track_uris<'a>(lines: impl Iterator<Item = &'a str>) -> anyhow::Result<Vec<String>> {
+  lines
+    .map(str::trim)
+    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+    .map(|line| {
+      parse_track_uri(line).ok_or_else(|| anyhow::anyhow!("'{}' is not a track uri", line))
+    })
+    .collect()
+}
+
+// The positions of the tracks that came before, local files and unavailable tracks are skipped
+pub fn get_duplicate_positions(uris: &[String]) -> Vec<usize> {
+  let mut seen = std::collections::HashSet::new();
+  uris
+    .iter()
+    .enumerate()
+    .filter(|(_, uri)| parse_track_uri(uri).is_some() && !seen.insert(uri.as_str()))
+    .map(|(position, _)| position)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parses_track_uris_links_and_ids() {
+    let uri = "spotify:track:4uLU6hMCjMI75M1A2tKUQC";
+    assert_eq!(parse_track_uri(uri).as_deref(), Some(uri));
+    assert_eq!(
+      parse_track_uri("https://open.spotify.com/track/4uLU6hMCjMI75M1A2tKUQC?si=abc").as_deref(),
+      Some(uri)
+    );
+    assert_eq!(
+      parse_track_uri(" 4uLU6hMCjMI75M1A2tKUQC ").as_deref(),
+      Some(uri)
+    );
+    assert_eq!(
+      parse_track_uri("spotify:album:4uLU6hMCjMI75M1A2tKUQC"),
+      None
+    );
+
+    let uris = parse_track_uris("#EXTM3U\n\nspotify:track:a\n  spotify:track:b\n".lines()).unwrap();
+    assert_eq!(uris, vec!["spotify:track:a", "spotify:track:b"]);
+    assert!(parse_track_uris(vec!["spotify:local:::song:1"].into_iter()).is_err());
+  }
+
+  #[test]
+  fn finds_the_positions_of_repeated_tracks() {
+    let uris = vec![
+      "spotify:track:a",
+      "spotify:track:b",
+      "spotify:track:a",
+      "",
+      "",
+      "spotify:local:::song:1",
+      "spotify:local:::song:1",
+      "spotify:track:b",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect::<Vec<_>>();
+    assert_eq!(get_duplicate_positions(&uris), vec![2, 7]);
+  }
+}
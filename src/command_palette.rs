@@ -0,0 +1,236 @@
+use crate::app::{ActiveBlock, App};
+use crate::event::Key;
+use crate::export::ExportFormat;
+use crate::filter::fuzzy_match;
+use crate::sleep_timer::SleepMode;
+
+/// The actions of the current route, narrowed down by what is typed
+pub struct CommandPalette {
+  pub query: Vec<char>,
+  pub selected_index: usize,
+  // The block the actions run in once the palette is closed
+  pub previous_block: ActiveBlock,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+  // Runs the action bound to the key, as if it was pressed
+  Binding(Key),
+  SetVolume(u8),
+  Seek(u32),
+  TransferPlayback(String),
+  ExportLibrary(ExportFormat),
+  SetSleepTimer(SleepMode),
+  CancelSleepTimer,
+  // Replaces the query, for commands that still need an argument
+  Complete(&'static str),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommandEntry {
+  pub title: String,
+  pub key: Option<Key>,
+  pub command: Command,
+}
+
+const SET_VOLUME: &str = "set volume ";
+const SEEK: &str = "seek ";
+const SLEEP: &str = "sleep in ";
+
+// The argument typed after the name of a command, "set" can be left out
+fn get_argument<'a>(query: &'a str, command: &str) -> Option<&'a str> {
+  let command = command.trim_start_matches("set ");
+  let query = query.trim_start();
+  let query = query.strip_prefix("set ").unwrap_or(query);
+  let argument = query.strip_prefix(command)?.trim();
+  if argument.is_empty() {
+    None
+  } else {
+    Some(argument)
+  }
+}
+
+/// Parses a position like `90`, `1:30` or `1:02:30` into milliseconds
+pub fn parse_position(position: &str) -> Option<u32> {
+  position
+    .split(':')
+    .try_fold(0u32, |seconds, part| {
+      let part = part.trim().parse::<u32>().ok()?;
+      seconds.checked_mul(60)?.checked_add(part)
+    })
+    .and_then(|seconds| seconds.checked_mul(1000))
+}
+
+fn format_position(position_ms: u32) -> String {
+  let seconds = position_ms / 1000;
+  format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// The commands that match the query, the ones with an argument typed out first
+pub fn get_entries(app: &App) -> Vec<CommandEntry> {
+  let palette = match &app.command_palette {
+    Some(palette) => palette,
+    None => return vec![],
+  };
+  let query = palette.query.iter().collect::<String>().to_lowercase();
+  let mut entries = vec![];
+
+  let volume = get_argument(&query, SET_VOLUME).and_then(|volume| volume.parse::<u8>().ok());
+  match volume {
+    Some(volume) if volume <= 100 => entries.push(CommandEntry {
+      title: format!("Set volume to {}%", volume),
+      key: None,
+      command: Command::SetVolume(volume),
+    }),
+    _ => {}
+  }
+  if let Some(position_ms) = get_argument(&query, SEEK).and_then(parse_position) {
+    entries.push(CommandEntry {
+      title: format!("Seek to {}", format_position(position_ms)),
+      key: None,
+      command: Command::Seek(position_ms),
+    });
+  }
+  let sleep_mode = get_argument(&query, SLEEP).and_then(SleepMode::parse);
+  if let Some(SleepMode::After(duration)) = sleep_mode {
+    entries.push(CommandEntry {
+      title: format!(
+        "Pause playback in {}",
+        format_position(duration.as_millis() as u32)
+      ),
+      key: None,
+      command: Command::SetSleepTimer(SleepMode::After(duration)),
+    });
+  }
+  let has_argument = !entries.is_empty();
+
+  let mut candidates = vec![];
+  if !has_argument {
+    candidates.push(CommandEntry {
+      title: String::from("set volume <0-100>"),
+      key: None,
+      command: Command::Complete(SET_VOLUME),
+    });
+    candidates.push(CommandEntry {
+      title: String::from("seek <m:ss>"),
+      key: None,
+      command: Command::Complete(SEEK),
+    });
+    candidates.push(CommandEntry {
+      title: String::from("sleep in <minutes, or e.g. 1h30m>"),
+      key: None,
+      command: Command::Complete(SLEEP),
+    });
+  }
+  candidates.push(CommandEntry {
+    title: String::from("sleep at the end of the track"),
+    key: None,
+    command: Command::SetSleepTimer(SleepMode::EndOfTrack),
+  });
+  candidates.push(CommandEntry {
+    title: String::from("sleep at the end of the album"),
+    key: None,
+    command: Command::SetSleepTimer(SleepMode::EndOfAlbum),
+  });
+  if app.sleep_timer.is_some() {
+    candidates.push(CommandEntry {
+      title: String::from("cancel the sleep timer"),
+      key: None,
+      command: Command::CancelSleepTimer,
+    });
+  }
+  candidates.extend(ExportFormat::ALL.iter().map(|format| CommandEntry {
+    title: format!("export library to {}", format.extension()),
+    key: None,
+    command: Command::ExportLibrary(*format),
+  }));
+  if let Some(devices) = &app.devices {
+    candidates.extend(
+      devices
+        .devices
+        .iter()
+        .filter(|d| d.is_controllable())
+        .map(|device| CommandEntry {
+          title: format!(
+            "transfer to {}",
+            app.user_config.devices.display_name(&device.name)
+          ),
+          key: None,
+          command: Command::TransferPlayback(device.id.to_owned()),
+        }),
+    );
+  }
+  let keys = &app.user_config.keys;
+  candidates.extend(
+    keys
+      .get_bindings()
+      .into_iter()
+      // Going back may exit, which only the main loop can do
+      .filter(|binding| binding.key != keys.back && binding.key != keys.command_palette)
+      .filter(|binding| {
+        binding.blocks.is_empty() || binding.blocks.contains(&palette.previous_block)
+      })
+      .map(|binding| CommandEntry {
+        title: binding.description.to_string(),
+        key: Some(binding.key.clone()),
+        command: Command::Binding(binding.key),
+      }),
+  );
+
+  if !has_argument {
+    entries.extend(
+      candidates
+        .into_iter()
+        .filter(|entry| fuzzy_match(&query, &entry.title)),
+    );
+  }
+  entries
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn open_with_query(app: &mut App, query: &str) {
+    app.command_palette = Some(CommandPalette {
+      query: query.chars().collect(),
+      selected_index: 0,
+      previous_block: ActiveBlock::TrackTable,
+    });
+  }
+
+  #[test]
+  fn parses_positions() {
+    assert_eq!(parse_position("90"), Some(90_000));
+    assert_eq!(parse_position("1:30"), Some(90_000));
+    assert_eq!(parse_position("1:02:03"), Some(3_723_000));
+    assert_eq!(parse_position("1:x"), None);
+  }
+
+  #[test]
+  fn lists_commands_with_arguments() {
+    let mut app = App::default();
+
+    open_with_query(&mut app, "set volume 40");
+    assert_eq!(get_entries(&app)[0].command, Command::SetVolume(40));
+    open_with_query(&mut app, "volume 140");
+    assert!(get_entries(&app).is_empty());
+    open_with_query(&mut app, "seek 1:30");
+    assert_eq!(get_entries(&app)[0].command, Command::Seek(90_000));
+    open_with_query(&mut app, "sleep in 45");
+    assert_eq!(
+      get_entries(&app)[0].command,
+      Command::SetSleepTimer(SleepMode::After(std::time::Duration::from_secs(45 * 60)))
+    );
+
+    // Actions of the track table are listed, the ones of other blocks aren't
+    open_with_query(&mut app, "random song");
+    let entries = get_entries(&app);
+    assert_eq!(
+      entries[0].command,
+      Command::Binding(app.user_config.keys.play_random_song.clone())
+    );
+    open_with_query(&mut app, "clear entries queued");
+    assert!(get_entries(&app).is_empty());
+  }
+}
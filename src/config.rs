@@ -12,6 +12,13 @@ const FILE_NAME: &str = "client.yml";
 const CONFIG_DIR: &str = ".config";
 const APP_CONFIG_DIR: &str = "spotify-tui";
 const TOKEN_CACHE_FILE: &str = ".spotify_token_cache.json";
+const LIBRARY_CACHE_FILE: &str = ".spotify_library_cache.json";
+const ALBUM_ART_DIR: &str = "album_art";
+const EXPORT_DIR: &str = "exports";
+const SCROBBLE_QUEUE_FILE: &str = ".scrobble_queue.json";
+const REJECTED_SCROBBLES_FILE: &str = "rejected_scrobbles.jsonl";
+const HISTORY_FILE: &str = "listening_history.jsonl";
+const TABLE_SORT_FILE: &str = ".table_sort.json";
 
 #[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ClientConfig {
@@ -25,6 +32,13 @@ pub struct ClientConfig {
 pub struct ConfigPaths {
   pub config_file_path: PathBuf,
   pub token_cache_path: PathBuf,
+  pub library_cache_path: PathBuf,
+  pub album_art_dir: PathBuf,
+  pub export_dir: PathBuf,
+  pub scrobble_queue_path: PathBuf,
+  pub rejected_scrobbles_path: PathBuf,
+  pub history_path: PathBuf,
+  pub table_sort_path: PathBuf,
 }
 
 impl ClientConfig {
@@ -62,10 +76,24 @@ impl ClientConfig {
 
         let config_file_path = &app_config_dir.join(FILE_NAME);
         let token_cache_path = &app_config_dir.join(TOKEN_CACHE_FILE);
+        let library_cache_path = &app_config_dir.join(LIBRARY_CACHE_FILE);
+        let album_art_dir = &app_config_dir.join(ALBUM_ART_DIR);
+        let export_dir = &app_config_dir.join(EXPORT_DIR);
+        let scrobble_queue_path = &app_config_dir.join(SCROBBLE_QUEUE_FILE);
+        let rejected_scrobbles_path = &app_config_dir.join(REJECTED_SCROBBLES_FILE);
+        let history_path = &app_config_dir.join(HISTORY_FILE);
+        let table_sort_path = &app_config_dir.join(TABLE_SORT_FILE);
 
         let paths = ConfigPaths {
           config_file_path: config_file_path.to_path_buf(),
           token_cache_path: token_cache_path.to_path_buf(),
+          library_cache_path: library_cache_path.to_path_buf(),
+          album_art_dir: album_art_dir.to_path_buf(),
+          export_dir: export_dir.to_path_buf(),
+          scrobble_queue_path: scrobble_queue_path.to_path_buf(),
+          rejected_scrobbles_path: rejected_scrobbles_path.to_path_buf(),
+          history_path: history_path.to_path_buf(),
+          table_sort_path: table_sort_path.to_path_buf(),
         };
 
         Ok(paths)
@@ -0,0 +1,91 @@
+use crate::user_config::UserConfig;
+use std::{
+  fs,
+  path::PathBuf,
+  time::{Duration, Instant, SystemTime},
+};
+
+// Looking at the files on every tick is not needed to pick up edits
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Notices changes to the config file and the theme files by comparing their modification times
+pub struct ConfigWatcher {
+  files: Vec<(PathBuf, SystemTime)>,
+  last_check: Instant,
+}
+
+impl ConfigWatcher {
+  pub fn new(user_config: &UserConfig) -> ConfigWatcher {
+    ConfigWatcher {
+      files: get_files(user_config),
+      last_check: Instant::now(),
+    }
+  }
+
+  /// Whether a file was changed, added or removed since the last time it returned true
+  pub fn has_changed(&mut self, user_config: &UserConfig) -> bool {
+    if self.last_check.elapsed() < CHECK_INTERVAL {
+      return false;
+    }
+    self.last_check = Instant::now();
+
+    let files = get_files(user_config);
+    if files == self.files {
+      return false;
+    }
+    self.files = files;
+    true
+  }
+}
+
+fn get_files(user_config: &UserConfig) -> Vec<(PathBuf, SystemTime)> {
+  let mut paths = vec![];
+  if let Some(paths_to_config) = &user_config.path_to_config {
+    paths.push(paths_to_config.config_file_path.clone());
+  }
+  if let Some(Ok(entries)) = user_config.get_themes_dir().map(fs::read_dir) {
+    paths.extend(
+      entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path()),
+    );
+  }
+
+  let mut files: Vec<(PathBuf, SystemTime)> = paths
+    .into_iter()
+    .filter_map(|path| {
+      let modified = fs::metadata(&path).and_then(|metadata| metadata.modified());
+      modified.ok().map(|modified| (path, modified))
+    })
+    .collect();
+  files.sort();
+  files
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::user_config::UserConfigPaths;
+
+  #[test]
+  fn notices_changed_config_file() {
+    let dir = std::env::temp_dir().join(format!("spt-config-watcher-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let config_file_path = dir.join("config.yml");
+    fs::write(&config_file_path, "").unwrap();
+    let mut user_config = UserConfig::new();
+    user_config.path_to_config = Some(UserConfigPaths { config_file_path });
+
+    let mut watcher = ConfigWatcher::new(&user_config);
+    watcher.last_check -= CHECK_INTERVAL;
+    assert!(!watcher.has_changed(&user_config));
+
+    fs::create_dir_all(dir.join("themes")).unwrap();
+    fs::write(dir.join("themes").join("mine.yml"), "text: Red").unwrap();
+    assert!(!watcher.has_changed(&user_config), "checked too early");
+    watcher.last_check -= CHECK_INTERVAL;
+    assert!(watcher.has_changed(&user_config));
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+}
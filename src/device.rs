@@ -0,0 +1,115 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+
+// The device object of the Web API. rspotify leaves out the private session and fails on the
+// devices that report no id or volume, so the devices are fetched with this instead
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Device {
+  // Empty if the device cannot be controlled with the Web API
+  #[serde(deserialize_with = "null_as_default")]
+  pub id: String,
+  pub is_active: bool,
+  #[serde(default)]
+  pub is_private_session: bool,
+  pub is_restricted: bool,
+  pub name: String,
+  #[serde(rename = "type")]
+  pub device_type: String,
+  // `None` if the volume of the device cannot be changed
+  pub volume_percent: Option<u32>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct DevicePayload {
+  pub devices: Vec<Device>,
+}
+
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+  D: Deserializer<'de>,
+  T: Default + Deserialize<'de>,
+{
+  Ok(Option::deserialize(deserializer)?.unwrap_or_default())
+}
+
+impl Device {
+  pub fn is_controllable(&self) -> bool {
+    !self.id.is_empty() && !self.is_restricted
+  }
+}
+
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeviceConfig {
+  // Device names or types, the first one matching an available device is where playback is
+  // moved on startup and when the active device goes away
+  #[serde(default)]
+  pub preferred: Vec<String>,
+  // Names shown instead of the ones the devices report
+  #[serde(default)]
+  pub names: BTreeMap<String, String>,
+}
+
+impl DeviceConfig {
+  pub fn display_name<'a>(&'a self, name: &'a str) -> &'a str {
+    self.names.get(name).map_or(name, String::as_str)
+  }
+
+  /// The available device that comes first in the preferred devices, matching either its name
+  /// or its type regardless of case
+  pub fn find_preferred<'a>(&self, devices: &'a [Device]) -> Option<&'a Device> {
+    self.preferred.iter().find_map(|rule| {
+      devices.iter().find(|device| {
+        device.is_controllable()
+          && (device.name.eq_ignore_ascii_case(rule)
+            || device.device_type.eq_ignore_ascii_case(rule)
+            || self.display_name(&device.name).eq_ignore_ascii_case(rule))
+      })
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn finds_preferred_device_by_name_or_type() {
+    let payload: DevicePayload = serde_json::from_str(
+      r#"{"devices": [
+        {"id": "1", "is_active": true, "is_private_session": false, "is_restricted": false,
+         "name": "Laptop", "type": "Computer", "volume_percent": 50},
+        {"id": null, "is_active": false, "is_private_session": false, "is_restricted": true,
+         "name": "Kitchen", "type": "Speaker", "volume_percent": null},
+        {"id": "3", "is_active": false, "is_private_session": true, "is_restricted": false,
+         "name": "Living Room", "type": "Speaker", "volume_percent": 20}
+      ]}"#,
+    )
+    .unwrap();
+    let mut config = DeviceConfig {
+      preferred: vec!["kitchen".to_string(), "speaker".to_string()],
+      ..Default::default()
+    };
+    // The kitchen speaker cannot be controlled
+    assert_eq!(
+      config
+        .find_preferred(&payload.devices)
+        .map(|d| d.id.as_str()),
+      Some("3")
+    );
+
+    config.preferred = vec!["desk".to_string(), "Computer".to_string()];
+    config
+      .names
+      .insert("Living Room".to_string(), "Desk".to_string());
+    assert_eq!(
+      config
+        .find_preferred(&payload.devices)
+        .map(|d| d.id.as_str()),
+      Some("3")
+    );
+
+    config.preferred = vec!["TV".to_string()];
+    assert!(config.find_preferred(&payload.devices).is_none());
+  }
+}
@@ -1,8 +1,15 @@
 use crate::event::Key;
 use crossterm::event;
-use std::{sync::mpsc, thread, time::Duration};
+use std::{
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    mpsc, Arc,
+  },
+  thread,
+  time::Duration,
+};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 /// Configuration for event handling.
 pub struct EventConfig {
   /// The key that is used to exit the application.
@@ -24,6 +31,8 @@ impl Default for EventConfig {
 pub enum Event<I> {
   /// An input event occurred.
   Input(I),
+  /// A mouse event occurred.
+  Mouse(event::MouseEvent),
   /// An tick event occurred.
   Tick,
 }
@@ -34,6 +43,8 @@ pub struct Events {
   rx: mpsc::Receiver<Event<Key>>,
   // Need to be kept around to prevent disposing the sender side.
   _tx: mpsc::Sender<Event<Key>>,
+  // In milliseconds, shared with the thread so it can change while running
+  tick_rate: Arc<AtomicU64>,
 }
 
 impl Events {
@@ -48,16 +59,26 @@ impl Events {
   /// Constructs an new instance of `Events` from given config.
   pub fn with_config(config: EventConfig) -> Events {
     let (tx, rx) = mpsc::channel();
+    let tick_rate = Arc::new(AtomicU64::new(config.tick_rate.as_millis() as u64));
 
     let event_tx = tx.clone();
+    let thread_tick_rate = Arc::clone(&tick_rate);
     thread::spawn(move || {
       loop {
         // poll for tick rate duration, if no event, sent tick event.
-        if event::poll(config.tick_rate).unwrap() {
-          if let event::Event::Key(key) = event::read().unwrap() {
-            let key = Key::from(key);
+        let tick_rate = Duration::from_millis(thread_tick_rate.load(Ordering::Relaxed));
+        if event::poll(tick_rate).unwrap() {
+          match event::read().unwrap() {
+            event::Event::Key(key) => {
+              let key = Key::from(key);
 
-            event_tx.send(Event::Input(key)).unwrap();
+              event_tx.send(Event::Input(key)).unwrap();
+            }
+            // Plain cursor movement is reported too, skip it to not redraw on every move
+            event::Event::Mouse(mouse) if mouse.kind != event::MouseEventKind::Moved => {
+              event_tx.send(Event::Mouse(mouse)).unwrap();
+            }
+            _ => {}
           }
         }
 
@@ -65,7 +86,16 @@ impl Events {
       }
     });
 
-    Events { rx, _tx: tx }
+    Events {
+      rx,
+      _tx: tx,
+      tick_rate,
+    }
+  }
+
+  /// Changes the tick rate from the next tick on
+  pub fn set_tick_rate(&self, tick_rate: u64) {
+    self.tick_rate.store(tick_rate, Ordering::Relaxed);
   }
 
   /// Attempts to read an event.
@@ -2,7 +2,7 @@ use crossterm::event;
 use std::fmt;
 
 /// Represents an key.
-#[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
+#[derive(PartialEq, Eq, Clone, Hash, Debug)]
 pub enum Key {
   /// Both Enter (or Return) and numpad Enter
   Enter,
@@ -64,10 +64,25 @@ pub enum Key {
   Char(char),
   Ctrl(char),
   Alt(char),
+  /// Keys pressed one after another to trigger a single binding, e.g. `g g`
+  Chord(Vec<Key>),
   Unknown,
 }
 
 impl Key {
+  /// Returns the chord of the given keys
+  pub fn chord(keys: &[Key]) -> Key {
+    Key::Chord(keys.to_vec())
+  }
+
+  /// The keys that have to be pressed for this key, more than one for a chord
+  pub fn get_keys(&self) -> &[Key] {
+    match self {
+      Key::Chord(keys) => keys,
+      key => std::slice::from_ref(key),
+    }
+  }
+
   /// Returns the function key corresponding to the given number
   ///
   /// 1 -> F1, etc...
@@ -97,13 +112,17 @@ impl Key {
 
 impl fmt::Display for Key {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    match *self {
+    match self {
       Key::Alt(' ') => write!(f, "<Alt+Space>"),
       Key::Ctrl(' ') => write!(f, "<Ctrl+Space>"),
       Key::Char(' ') => write!(f, "<Space>"),
       Key::Alt(c) => write!(f, "<Alt+{}>", c),
       Key::Ctrl(c) => write!(f, "<Ctrl+{}>", c),
       Key::Char(c) => write!(f, "{}", c),
+      Key::Chord(keys) => {
+        let keys: Vec<String> = keys.iter().map(|key| key.to_string()).collect();
+        write!(f, "{}", keys.join(" "))
+      }
       Key::Left | Key::Right | Key::Up | Key::Down => write!(f, "<{:?} Arrow Key>", self),
       Key::Enter
       | Key::Tab
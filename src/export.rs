@@ -0,0 +1,549 @@
+use anyhow::{anyhow, Result};
+use rspotify::model::{
+  album::FullAlbum, artist::FullArtist, playlist::SimplifiedPlaylist, show::SimplifiedShow,
+  track::FullTrack,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+  collections::HashSet,
+  fmt::Write,
+  fs,
+  path::{Path, PathBuf},
+};
+
+const LIKED_SONGS: &str = "Liked Songs";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+  Json,
+  Csv,
+  M3u,
+  Xspf,
+}
+
+impl ExportFormat {
+  pub const ALL: [ExportFormat; 4] = [Self::Json, Self::Csv, Self::M3u, Self::Xspf];
+
+  pub fn parse(format: &str) -> Option<Self> {
+    Self::ALL
+      .iter()
+      .copied()
+      .find(|f| f.extension().eq_ignore_ascii_case(format))
+  }
+
+  /// The format of an export, judging by the extension of the file
+  pub fn from_path(path: &Path) -> Option<Self> {
+    Self::parse(path.extension()?.to_str()?)
+  }
+
+  pub fn extension(self) -> &'static str {
+    match self {
+      Self::Json => "json",
+      Self::Csv => "csv",
+      Self::M3u => "m3u",
+      Self::Xspf => "xspf",
+    }
+  }
+
+  // M3U and XSPF have a file for each playlist, the others one file for the whole library
+  pub fn is_playlist_format(self) -> bool {
+    matches!(self, Self::M3u | Self::Xspf)
+  }
+}
+
+/// A track, album, artist or show of the library
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Item {
+  pub uri: String,
+  pub name: String,
+  // The artists of tracks and albums, the publisher of shows
+  #[serde(default)]
+  pub artists: Vec<String>,
+  #[serde(default)]
+  pub album: Option<String>,
+  #[serde(default)]
+  pub duration_ms: Option<u32>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Playlist {
+  pub uri: String,
+  pub name: String,
+  pub owner_id: String,
+  #[serde(default)]
+  pub tracks: Vec<Item>,
+}
+
+/// Everything an import can save or follow again
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Library {
+  #[serde(default)]
+  pub liked_tracks: Vec<Item>,
+  #[serde(default)]
+  pub saved_albums: Vec<Item>,
+  #[serde(default)]
+  pub followed_artists: Vec<Item>,
+  #[serde(default)]
+  pub saved_shows: Vec<Item>,
+  #[serde(default)]
+  pub playlists: Vec<Playlist>,
+}
+
+impl Item {
+  pub fn get_id(&self) -> &str {
+    self.uri.rsplit(':').next().unwrap_or_default()
+  }
+
+  fn describe(&self) -> String {
+    if self.artists.is_empty() {
+      self.name.clone()
+    } else {
+      format!("{} - {}", self.artists.join(", "), self.name)
+    }
+  }
+}
+
+impl From<&FullTrack> for Item {
+  fn from(track: &FullTrack) -> Self {
+    Item {
+      uri: track.uri.clone(),
+      name: track.name.clone(),
+      artists: track.artists.iter().map(|a| a.name.clone()).collect(),
+      album: Some(track.album.name.clone()),
+      duration_ms: Some(track.duration_ms),
+    }
+  }
+}
+
+impl From<&FullAlbum> for Item {
+  fn from(album: &FullAlbum) -> Self {
+    Item {
+      uri: album.uri.clone(),
+      name: album.name.clone(),
+      artists: album.artists.iter().map(|a| a.name.clone()).collect(),
+      ..Default::default()
+    }
+  }
+}
+
+impl From<&FullArtist> for Item {
+  fn from(artist: &FullArtist) -> Self {
+    Item {
+      uri: artist.uri.clone(),
+      name: artist.name.clone(),
+      ..Default::default()
+    }
+  }
+}
+
+impl From<&SimplifiedShow> for Item {
+  fn from(show: &SimplifiedShow) -> Self {
+    Item {
+      uri: show.uri.clone(),
+      name: show.name.clone(),
+      artists: vec![show.publisher.clone()],
+      ..Default::default()
+    }
+  }
+}
+
+impl Playlist {
+  pub fn new(playlist: &SimplifiedPlaylist, tracks: Vec<Item>) -> Self {
+    Playlist {
+      uri: playlist.uri.clone(),
+      name: playlist.name.clone(),
+      owner_id: playlist.owner.id.clone(),
+      tracks,
+    }
+  }
+
+  pub fn get_id(&self) -> &str {
+    self.uri.rsplit(':').next().unwrap_or_default()
+  }
+}
+
+// A row of a csv export, which has every section of the library in one table. Tracks of
+// playlists refer to the playlist by its uri
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CsvRow {
+  section: String,
+  playlist: String,
+  uri: String,
+  name: String,
+  artists: String,
+  album: String,
+  duration_ms: Option<u32>,
+  owner_id: String,
+}
+
+const ARTIST_SEPARATOR: &str = "; ";
+
+impl CsvRow {
+  fn new(section: &str, playlist: &str, item: &Item) -> Self {
+    CsvRow {
+      section: section.to_string(),
+      playlist: playlist.to_string(),
+      uri: item.uri.clone(),
+      name: item.name.clone(),
+      artists: item.artists.join(ARTIST_SEPARATOR),
+      album: item.album.clone().unwrap_or_default(),
+      duration_ms: item.duration_ms,
+      owner_id: String::new(),
+    }
+  }
+
+  fn into_item(self) -> Item {
+    Item {
+      uri: self.uri,
+      name: self.name,
+      artists: self
+        .artists
+        .split(ARTIST_SEPARATOR)
+        .filter(|artist| !artist.is_empty())
+        .map(String::from)
+        .collect(),
+      album: Some(self.album).filter(|album| !album.is_empty()),
+      duration_ms: self.duration_ms,
+    }
+  }
+}
+
+fn escape_xml(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+    .replace('\'', "&apos;")
+}
+
+// A file name for the playlist that is valid on every platform
+fn get_file_name(name: &str) -> String {
+  let name: String = name
+    .chars()
+    .map(|c| match c {
+      '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+      c if c.is_control() => '_',
+      c => c,
+    })
+    .collect();
+  let name = name.trim().trim_start_matches('.');
+  if name.is_empty() {
+    "playlist".to_string()
+  } else {
+    name.to_string()
+  }
+}
+
+fn to_m3u(name: &str, tracks: &[Item]) -> String {
+  let mut m3u = format!("#EXTM3U\n#PLAYLIST:{}\n", name);
+  for track in tracks {
+    let seconds = track.duration_ms.map_or(-1, |ms| i64::from(ms / 1000));
+    let _ = writeln!(
+      m3u,
+      "#EXTINF:{},{}\n{}",
+      seconds,
+      track.describe(),
+      track.uri
+    );
+  }
+  m3u
+}
+
+fn to_xspf(name: &str, tracks: &[Item]) -> String {
+  let mut xspf = format!(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+<playlist version=\"1\" xmlns=\"http://xspf.org/ns/0/\">\n  <title>{}</title>\n  <trackList>\n",
+    escape_xml(name)
+  );
+  for track in tracks {
+    let _ = writeln!(xspf, "    <track>");
+    let _ = writeln!(
+      xspf,
+      "      <location>{}</location>",
+      escape_xml(&track.uri)
+    );
+    let _ = writeln!(xspf, "      <title>{}</title>", escape_xml(&track.name));
+    if !track.artists.is_empty() {
+      let artists = escape_xml(&track.artists.join(", "));
+      let _ = writeln!(xspf, "      <creator>{}</creator>", artists);
+    }
+    if let Some(album) = &track.album {
+      let _ = writeln!(xspf, "      <album>{}</album>", escape_xml(album));
+    }
+    if let Some(duration_ms) = track.duration_ms {
+      let _ = writeln!(xspf, "      <duration>{}</duration>", duration_ms);
+    }
+    let _ = writeln!(xspf, "    </track>");
+  }
+  xspf.push_str("  </trackList>\n</playlist>\n");
+  xspf
+}
+
+impl Library {
+  /// The library as json or csv
+  pub fn export(&self, format: ExportFormat) -> Result<String> {
+    match format {
+      ExportFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+      ExportFormat::Csv => {
+        let mut writer = csv::Writer::from_writer(vec![]);
+        let sections = [
+          ("liked_track", &self.liked_tracks),
+          ("saved_album", &self.saved_albums),
+          ("followed_artist", &self.followed_artists),
+          ("saved_show", &self.saved_shows),
+        ];
+        for (section, items) in sections.iter() {
+          for item in items.iter() {
+            writer.serialize(CsvRow::new(section, "", item))?;
+          }
+        }
+        for playlist in &self.playlists {
+          writer.serialize(CsvRow {
+            section: "playlist".to_string(),
+            playlist: playlist.uri.clone(),
+            uri: playlist.uri.clone(),
+            name: playlist.name.clone(),
+            owner_id: playlist.owner_id.clone(),
+            ..Default::default()
+          })?;
+          for track in &playlist.tracks {
+            writer.serialize(CsvRow::new("playlist_track", &playlist.uri, track))?;
+          }
+        }
+        Ok(String::from_utf8(writer.into_inner()?)?)
+      }
+      ExportFormat::M3u | ExportFormat::Xspf => Err(anyhow!(
+        "{} exports are a directory with a file for each playlist",
+        format.extension()
+      )),
+    }
+  }
+
+  /// Writes the liked songs and every playlist to their own M3U or XSPF file in the directory
+  pub fn export_playlists(&self, format: ExportFormat, dir: &Path) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir)?;
+    let playlists = std::iter::once((LIKED_SONGS, &self.liked_tracks)).chain(
+      self
+        .playlists
+        .iter()
+        .map(|playlist| (playlist.name.as_str(), &playlist.tracks)),
+    );
+    let mut file_names = HashSet::new();
+    let mut paths = vec![];
+    for (name, tracks) in playlists {
+      // Playlists can have the same name
+      let base_name = get_file_name(name);
+      let mut file_name = base_name.clone();
+      let mut count = 1;
+      while !file_names.insert(file_name.to_lowercase()) {
+        count += 1;
+        file_name = format!("{} {}", base_name, count);
+      }
+
+      let contents = match format {
+        ExportFormat::Xspf => to_xspf(name, tracks),
+        _ => to_m3u(name, tracks),
+      };
+      let path = dir.join(format!("{}.{}", file_name, format.extension()));
+      fs::write(&path, contents)?;
+      paths.push(path);
+    }
+    Ok(paths)
+  }
+
+  /// Writes a json or csv export to the file, or the M3U or XSPF files to the directory
+  pub fn write_to(&self, format: ExportFormat, path: &Path) -> Result<()> {
+    if format.is_playlist_format() {
+      self.export_playlists(format, path)?;
+    } else {
+      if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+      }
+      fs::write(path, self.export(format)?)?;
+    }
+    Ok(())
+  }
+
+  /// Reads a json or csv export
+  pub fn parse(format: ExportFormat, input: &str) -> Result<Self> {
+    match format {
+      ExportFormat::Json => Ok(serde_json::from_str(input)?),
+      ExportFormat::Csv => {
+        let mut library = Library::default();
+        let mut reader = csv::Reader::from_reader(input.as_bytes());
+        for row in reader.deserialize() {
+          let row: CsvRow = row?;
+          match row.section.as_str() {
+            "liked_track" => library.liked_tracks.push(row.into_item()),
+            "saved_album" => library.saved_albums.push(row.into_item()),
+            "followed_artist" => library.followed_artists.push(row.into_item()),
+            "saved_show" => library.saved_shows.push(row.into_item()),
+            "playlist" => library.playlists.push(Playlist {
+              uri: row.uri,
+              name: row.name,
+              owner_id: row.owner_id,
+              tracks: vec![],
+            }),
+            "playlist_track" => {
+              let playlist = library
+                .playlists
+                .iter_mut()
+                .find(|playlist| playlist.uri == row.playlist)
+                .ok_or_else(|| anyhow!("track of unknown playlist '{}'", row.playlist))?;
+              playlist.tracks.push(row.into_item());
+            }
+            section => return Err(anyhow!("unknown section '{}'", section)),
+          }
+        }
+        Ok(library)
+      }
+      ExportFormat::M3u | ExportFormat::Xspf => Err(anyhow!(
+        "only json and csv exports can be imported, not {}",
+        format.extension()
+      )),
+    }
+  }
+
+  /// What an import of this library adds to the current one, it never removes anything
+  pub fn get_missing(&self, current: &Library) -> Library {
+    fn missing(items: &[Item], current: &[Item]) -> Vec<Item> {
+      let uris = current.iter().map(|i| &i.uri).collect::<HashSet<_>>();
+      items
+        .iter()
+        .filter(|item| !uris.contains(&item.uri))
+        .cloned()
+        .collect()
+    }
+    let playlist_uris = current
+      .playlists
+      .iter()
+      .map(|p| &p.uri)
+      .collect::<HashSet<_>>();
+    Library {
+      liked_tracks: missing(&self.liked_tracks, &current.liked_tracks),
+      saved_albums: missing(&self.saved_albums, &current.saved_albums),
+      followed_artists: missing(&self.followed_artists, &current.followed_artists),
+      saved_shows: missing(&self.saved_shows, &current.saved_shows),
+      playlists: self
+        .playlists
+        .iter()
+        .filter(|playlist| !playlist_uris.contains(&playlist.uri))
+        .cloned()
+        .collect(),
+    }
+  }
+
+  pub fn len(&self) -> usize {
+    self.liked_tracks.len()
+      + self.saved_albums.len()
+      + self.followed_artists.len()
+      + self.saved_shows.len()
+      + self.playlists.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// A line for each item, like a diff of what an import adds
+  pub fn describe(&self) -> Vec<String> {
+    let sections = [
+      ("liked track", &self.liked_tracks),
+      ("saved album", &self.saved_albums),
+      ("followed artist", &self.followed_artists),
+      ("saved show", &self.saved_shows),
+    ];
+    let mut lines = vec![];
+    for (section, items) in sections.iter() {
+      lines.extend(
+        items
+          .iter()
+          .map(|item| format!("+ {} {} ({})", section, item.describe(), item.uri)),
+      );
+    }
+    lines.extend(
+      self
+        .playlists
+        .iter()
+        .map(|playlist| format!("+ playlist {} ({})", playlist.name, playlist.uri)),
+    );
+    lines
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn track(id: &str, name: &str) -> Item {
+    Item {
+      uri: format!("spotify:track:{}", id),
+      name: name.to_string(),
+      artists: vec!["Artist".to_string(), "Other, Artist".to_string()],
+      album: Some("Album".to_string()),
+      duration_ms: Some(215_000),
+    }
+  }
+
+  fn get_library() -> Library {
+    Library {
+      liked_tracks: vec![track("1", "One"), track("2", "Two \"2\"")],
+      followed_artists: vec![Item {
+        uri: "spotify:artist:3".to_string(),
+        name: "Artist".to_string(),
+        ..Default::default()
+      }],
+      playlists: vec![Playlist {
+        uri: "spotify:playlist:4".to_string(),
+        name: "Mix/Tape".to_string(),
+        owner_id: "user".to_string(),
+        tracks: vec![track("1", "One")],
+      }],
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn imports_json_and_csv_exports() {
+    let library = get_library();
+    for format in &[ExportFormat::Json, ExportFormat::Csv] {
+      let export = library.export(*format).unwrap();
+      assert_eq!(Library::parse(*format, &export).unwrap(), library);
+    }
+    assert!(library.export(ExportFormat::M3u).is_err());
+  }
+
+  #[test]
+  fn writes_playlist_files() {
+    let library = get_library();
+    assert_eq!(
+      to_m3u("Liked Songs", &library.liked_tracks[..1]),
+      "#EXTM3U\n#PLAYLIST:Liked Songs\n\
+#EXTINF:215,Artist, Other, Artist - One\nspotify:track:1\n"
+    );
+    let xspf = to_xspf("Mix", &library.liked_tracks[1..]);
+    assert!(xspf.contains("<title>Two &quot;2&quot;</title>"));
+    assert!(xspf.contains("<duration>215000</duration>"));
+
+    assert_eq!(get_file_name("Mix/Tape"), "Mix_Tape");
+    assert_eq!(get_file_name(" ..."), "playlist");
+  }
+
+  #[test]
+  fn imports_only_what_is_missing() {
+    let backup = get_library();
+    let current = Library {
+      liked_tracks: vec![track("2", "Two")],
+      ..Default::default()
+    };
+    let missing = backup.get_missing(&current);
+    assert_eq!(missing.liked_tracks, vec![track("1", "One")]);
+    assert_eq!(missing.len(), 3);
+    assert_eq!(
+      missing.describe()[0],
+      "+ liked track Artist, Other, Artist - One (spotify:track:1)"
+    );
+    assert!(backup.get_missing(&backup).is_empty());
+  }
+}
@@ -0,0 +1,328 @@
+use crate::app::{ActiveBlock, App, RouteId};
+use crate::sort::{self, TableSort};
+use crate::ui::util::create_artist_string;
+
+/// Narrows the rows of a list or table to the ones matching the query
+pub struct ListFilter {
+  pub block: ActiveBlock,
+  pub query: Vec<char>,
+  // Typed keys go to the query while it is edited
+  pub is_editing: bool,
+}
+
+/// Whether the characters of every word of the query appear in order in the text, ignoring case
+pub fn fuzzy_match(query: &str, text: &str) -> bool {
+  let text = text.to_lowercase();
+  query.to_lowercase().split_whitespace().all(|word| {
+    let mut chars = text.chars();
+    word.chars().all(|c| chars.any(|t| t == c))
+  })
+}
+
+/// The rows of the track table that are shown, kept as long as the query and the sort are the
+/// ones they were found with and the tracks don't change
+pub struct VisibleIndices {
+  query: Option<String>,
+  sort: Option<TableSort>,
+  indices: Vec<usize>,
+}
+
+pub fn is_filterable(block: ActiveBlock) -> bool {
+  matches!(
+    block,
+    ActiveBlock::TrackTable
+      | ActiveBlock::AlbumList
+      | ActiveBlock::Artists
+      | ActiveBlock::Podcasts
+      | ActiveBlock::MyPlaylists
+      | ActiveBlock::RecentlyPlayed
+      | ActiveBlock::EpisodeTable
+  )
+}
+
+// The text of each row the query is matched against
+fn get_rows(app: &App, block: ActiveBlock) -> Option<Vec<String>> {
+  let rows = match block {
+    ActiveBlock::TrackTable => app
+      .track_table
+      .tracks
+      .iter()
+      .map(|track| {
+        format!(
+          "{} {} {}",
+          track.name,
+          create_artist_string(&track.artists),
+          track.album.name
+        )
+      })
+      .collect(),
+    ActiveBlock::AlbumList => match app.library.saved_albums.get_results(None) {
+      Some(albums) => albums
+        .items
+        .iter()
+        .map(|saved| {
+          format!(
+            "{} {}",
+            saved.album.name,
+            create_artist_string(&saved.album.artists)
+          )
+        })
+        .collect(),
+      None => vec![],
+    },
+    ActiveBlock::Artists => app
+      .artists
+      .iter()
+      .map(|artist| artist.name.to_owned())
+      .collect(),
+    ActiveBlock::Podcasts => match app.library.saved_shows.get_results(None) {
+      Some(shows) => shows
+        .items
+        .iter()
+        .map(|saved| format!("{} {}", saved.show.name, saved.show.publisher))
+        .collect(),
+      None => vec![],
+    },
+    ActiveBlock::MyPlaylists => match &app.playlists {
+      Some(playlists) => playlists
+        .items
+        .iter()
+        .map(|playlist| playlist.name.to_owned())
+        .collect(),
+      None => vec![],
+    },
+    ActiveBlock::RecentlyPlayed => match &app.recently_played.result {
+      Some(recently_played) => recently_played
+        .items
+        .iter()
+        .map(|history| {
+          format!(
+            "{} {}",
+            history.track.name,
+            create_artist_string(&history.track.artists)
+          )
+        })
+        .collect(),
+      None => vec![],
+    },
+    ActiveBlock::EpisodeTable => match app.library.show_episodes.get_results(None) {
+      Some(episodes) => episodes
+        .items
+        .iter()
+        .map(|episode| episode.name.to_owned())
+        .collect(),
+      None => vec![],
+    },
+    _ => return None,
+  };
+  Some(rows)
+}
+
+pub fn get_selected_index(app: &App, block: ActiveBlock) -> usize {
+  match block {
+    ActiveBlock::TrackTable => app.track_table.selected_index,
+    ActiveBlock::AlbumList => app.album_list_index,
+    ActiveBlock::Artists => app.artists_list_index,
+    ActiveBlock::Podcasts => app.shows_list_index,
+    ActiveBlock::MyPlaylists => app.selected_playlist_index.unwrap_or(0),
+    ActiveBlock::RecentlyPlayed => app.recently_played.index,
+    ActiveBlock::EpisodeTable => app.episode_list_index,
+    _ => 0,
+  }
+}
+
+pub fn set_selected_index(app: &mut App, block: ActiveBlock, index: usize) {
+  match block {
+    ActiveBlock::TrackTable => app.track_table.selected_index = index,
+    ActiveBlock::AlbumList => app.album_list_index = index,
+    ActiveBlock::Artists => app.artists_list_index = index,
+    ActiveBlock::Podcasts => app.shows_list_index = index,
+    ActiveBlock::MyPlaylists => app.selected_playlist_index = Some(index),
+    ActiveBlock::RecentlyPlayed => app.recently_played.index = index,
+    ActiveBlock::EpisodeTable => app.episode_list_index = index,
+    _ => {}
+  }
+}
+
+// Routes that are shown on top of the others and leave their contents alone
+fn is_overlay_route(route_id: &RouteId) -> bool {
+  matches!(
+    route_id,
+    RouteId::Dialog
+      | RouteId::Error
+      | RouteId::SelectedDevice
+      | RouteId::Analysis
+      | RouteId::BasicView
+  )
+}
+
+/// Indices of the rows of `block` that match its filter, in the order of its sort. `None` when
+/// it is neither filtered nor sorted
+pub fn get_visible_indices(app: &App, block: ActiveBlock) -> Option<Vec<usize>> {
+  let query = app
+    .filter
+    .as_ref()
+    .filter(|filter| filter.block == block)
+    .map(|filter| filter.query.iter().collect::<String>());
+  let sort = sort::get_sort(app, block);
+  if query.is_none() && sort.is_none() {
+    return None;
+  }
+  if block != ActiveBlock::TrackTable {
+    return find_visible_indices(app, block, query.as_deref());
+  }
+  // The track table can hold whole playlists, which are too long to match and sort every frame
+  if let Some(cached) = &*app.track_table.visible_indices.borrow() {
+    if cached.query == query && cached.sort == sort {
+      return Some(cached.indices.clone());
+    }
+  }
+  let indices = find_visible_indices(app, block, query.as_deref())?;
+  *app.track_table.visible_indices.borrow_mut() = Some(VisibleIndices {
+    query,
+    sort,
+    indices: indices.clone(),
+  });
+  Some(indices)
+}
+
+fn find_visible_indices(app: &App, block: ActiveBlock, query: Option<&str>) -> Option<Vec<usize>> {
+  let rows = get_rows(app, block)?;
+  let mut indices: Vec<usize> = rows
+    .iter()
+    .enumerate()
+    .filter(|(_, row)| match query {
+      Some(query) => fuzzy_match(query, row),
+      None => true,
+    })
+    .map(|(index, _)| index)
+    .collect();
+  sort::sort_indices(app, block, &mut indices);
+  Some(indices)
+}
+
+/// The rows of `block` that are shown and the position of the selection among them
+pub fn get_visible_rows<T>(app: &App, block: ActiveBlock, rows: Vec<T>) -> (Vec<T>, usize) {
+  match get_visible_indices(app, block) {
+    Some(indices) => {
+      let mut rows = rows.into_iter().map(Some).collect::<Vec<Option<T>>>();
+      let visible = indices
+        .iter()
+        .filter_map(|&index| rows.get_mut(index).and_then(Option::take))
+        .collect();
+      (visible, get_visible_position(app, block))
+    }
+    None => (rows, get_selected_index(app, block)),
+  }
+}
+
+/// The position of the selection among the rows that are shown
+pub fn get_visible_position(app: &App, block: ActiveBlock) -> usize {
+  let selected_index = get_selected_index(app, block);
+  match get_visible_indices(app, block) {
+    Some(matches) => matches
+      .iter()
+      .position(|&index| index == selected_index)
+      .unwrap_or(0),
+    None => selected_index,
+  }
+}
+
+/// Maps a row that is shown to the index of its item
+pub fn get_filtered_index(app: &App, block: ActiveBlock, row: usize) -> Option<usize> {
+  match get_visible_indices(app, block) {
+    Some(matches) => matches.get(row).copied(),
+    None => Some(row),
+  }
+}
+
+/// The title of a filtered or sorted block shows the query and the sort
+pub fn get_title(app: &App, block: ActiveBlock, title: &str) -> String {
+  let title = sort::get_title(app, block, title);
+  match &app.filter {
+    Some(filter) if filter.block == block => format!(
+      "{} (filter: {}{})",
+      title,
+      filter.query.iter().collect::<String>(),
+      if filter.is_editing { "_" } else { "" }
+    ),
+    _ => title,
+  }
+}
+
+pub fn is_editing(app: &App) -> bool {
+  matches!(&app.filter, Some(filter) if filter.is_editing)
+}
+
+/// Moves the selection onto the first match, unless it matches already
+pub fn select_match(app: &mut App) {
+  if let Some(block) = app.filter.as_ref().map(|filter| filter.block) {
+    if let Some(matches) = get_visible_indices(app, block) {
+      if !matches.contains(&get_selected_index(app, block)) {
+        if let Some(&first) = matches.first() {
+          set_selected_index(app, block, first);
+        }
+      }
+    }
+  }
+}
+
+// Opening another view replaces the rows of the routes, so their filter no longer applies.
+// The playlists in the sidebar stay the same
+pub fn reset_route_filter(app: &mut App, route_id: &RouteId) {
+  if is_overlay_route(route_id) {
+    return;
+  }
+  if matches!(&app.filter, Some(filter) if filter.block != ActiveBlock::MyPlaylists) {
+    app.filter = None;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn matches_characters_of_each_word_in_order() {
+    assert!(fuzzy_match("", "Anything"));
+    assert!(fuzzy_match("bhrp", "Bohemian Rhapsody Queen"));
+    assert!(fuzzy_match("queen rhap", "Bohemian Rhapsody Queen"));
+    assert!(!fuzzy_match("phr", "Bohemian Rhapsody"));
+    assert!(!fuzzy_match("queen abba", "Bohemian Rhapsody Queen"));
+  }
+
+  #[test]
+  fn visible_rows_keep_the_selection_on_its_item() {
+    let mut app = App::default();
+    app.artists = ["Abba", "Queen", "Black Sabbath"]
+      .iter()
+      .map(|name| {
+        serde_json::from_value(serde_json::json!({
+          "external_urls": {}, "followers": {"href": null, "total": 0}, "genres": [],
+          "href": "", "id": name, "images": [], "name": name, "popularity": 0,
+          "type": "artist", "uri": ""
+        }))
+        .unwrap()
+      })
+      .collect();
+    app.artists_list_index = 2;
+    let rows = vec!["row 0", "row 1", "row 2"];
+
+    assert_eq!(
+      get_visible_rows(&app, ActiveBlock::Artists, rows.clone()),
+      (vec!["row 0", "row 1", "row 2"], 2)
+    );
+
+    app.filter = Some(ListFilter {
+      block: ActiveBlock::Artists,
+      query: "ab".chars().collect(),
+      is_editing: false,
+    });
+    assert_eq!(
+      get_visible_rows(&app, ActiveBlock::Artists, rows),
+      (vec!["row 0", "row 2"], 1)
+    );
+    assert_eq!(get_filtered_index(&app, ActiveBlock::Artists, 1), Some(2));
+    assert_eq!(get_filtered_index(&app, ActiveBlock::Artists, 2), None);
+  }
+}
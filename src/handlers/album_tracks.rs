@@ -7,28 +7,32 @@ use crate::{
 
 pub fn handler(key: Key, app: &mut App) {
   match key {
-    k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
-    k if common_key_events::down_event(k) => match app.album_table_context {
-      AlbumTableContext::Full => {
-        if let Some(selected_album) = &app.selected_album_full {
-          let next_index = common_key_events::on_down_press_handler(
-            &selected_album.album.tracks.items,
-            Some(app.saved_album_tracks_index),
-          );
-          app.saved_album_tracks_index = next_index;
-        };
-      }
-      AlbumTableContext::Simplified => {
-        if let Some(selected_album_simplified) = &mut app.selected_album_simplified {
-          let next_index = common_key_events::on_down_press_handler(
-            &selected_album_simplified.tracks.items,
-            Some(selected_album_simplified.selected_index),
-          );
-          selected_album_simplified.selected_index = next_index;
+    k if common_key_events::left_event(&k, &app.user_config.keys) => {
+      common_key_events::handle_left_event(app)
+    }
+    k if common_key_events::down_event(&k, &app.user_config.keys) => {
+      match app.album_table_context {
+        AlbumTableContext::Full => {
+          if let Some(selected_album) = &app.selected_album_full {
+            let next_index = common_key_events::on_down_press_handler(
+              &selected_album.album.tracks.items,
+              Some(app.saved_album_tracks_index),
+            );
+            app.saved_album_tracks_index = next_index;
+          };
+        }
+        AlbumTableContext::Simplified => {
+          if let Some(selected_album_simplified) = &mut app.selected_album_simplified {
+            let next_index = common_key_events::on_down_press_handler(
+              &selected_album_simplified.tracks.items,
+              Some(selected_album_simplified.selected_index),
+            );
+            selected_album_simplified.selected_index = next_index;
+          }
         }
       }
-    },
-    k if common_key_events::up_event(k) => match app.album_table_context {
+    }
+    k if common_key_events::up_event(&k, &app.user_config.keys) => match app.album_table_context {
       AlbumTableContext::Full => {
         if let Some(selected_album) = &app.selected_album_full {
           let next_index = common_key_events::on_up_press_handler(
@@ -48,11 +52,11 @@ pub fn handler(key: Key, app: &mut App) {
         }
       }
     },
-    k if common_key_events::high_event(k) => handle_high_event(app),
-    k if common_key_events::middle_event(k) => handle_middle_event(app),
-    k if common_key_events::low_event(k) => handle_low_event(app),
-    Key::Char('s') => handle_save_event(app),
-    Key::Char('w') => handle_save_album_event(app),
+    k if common_key_events::high_event(&k, &app.user_config.keys) => handle_high_event(app),
+    k if common_key_events::middle_event(&k, &app.user_config.keys) => handle_middle_event(app),
+    k if common_key_events::low_event(&k, &app.user_config.keys) => handle_low_event(app),
+    k if k == app.user_config.keys.save => handle_save_event(app),
+    k if k == app.user_config.keys.save_context => handle_save_album_event(app),
     Key::Enter => match app.album_table_context {
       AlbumTableContext::Full => {
         if let Some(selected_album) = app.selected_album_full.clone() {
@@ -74,7 +78,7 @@ pub fn handler(key: Key, app: &mut App) {
       }
     },
     //recommended playlist based on selected track
-    Key::Char('r') => {
+    k if k == app.user_config.keys.play_recommendations => {
       handle_recommended_tracks(app);
     }
     _ if key == app.user_config.keys.add_item_to_queue => match app.album_table_context {
@@ -102,10 +106,39 @@ pub fn handler(key: Key, app: &mut App) {
         };
       }
     },
+    _ if key == app.user_config.keys.add_to_playlist => handle_add_to_playlist_event(app),
     _ => {}
   };
 }
 
+fn handle_add_to_playlist_event(app: &mut App) {
+  let track_uri = match app.album_table_context {
+    AlbumTableContext::Full => app.selected_album_full.as_ref().and_then(|selected_album| {
+      selected_album
+        .album
+        .tracks
+        .items
+        .get(app.saved_album_tracks_index)
+        .map(|track| track.uri.clone())
+    }),
+    AlbumTableContext::Simplified => {
+      app
+        .selected_album_simplified
+        .as_ref()
+        .and_then(|selected_album| {
+          selected_album
+            .tracks
+            .items
+            .get(selected_album.selected_index)
+            .map(|track| track.uri.clone())
+        })
+    }
+  };
+  if let Some(uri) = track_uri {
+    app.open_add_to_playlist_dialog(vec![uri]);
+  }
+}
+
 fn handle_high_event(app: &mut App) {
   match app.album_table_context {
     AlbumTableContext::Full => {
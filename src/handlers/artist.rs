@@ -238,21 +238,21 @@ pub fn handler(key: Key, app: &mut App) {
       Key::Esc => {
         artist.artist_selected_block = ArtistBlock::Empty;
       }
-      k if common_key_events::down_event(k) => {
+      k if common_key_events::down_event(&k, &app.user_config.keys) => {
         if artist.artist_selected_block != ArtistBlock::Empty {
           handle_down_press_on_selected_block(app);
         } else {
           handle_down_press_on_hovered_block(app);
         }
       }
-      k if common_key_events::up_event(k) => {
+      k if common_key_events::up_event(&k, &app.user_config.keys) => {
         if artist.artist_selected_block != ArtistBlock::Empty {
           handle_up_press_on_selected_block(app);
         } else {
           handle_up_press_on_hovered_block(app);
         }
       }
-      k if common_key_events::left_event(k) => {
+      k if common_key_events::left_event(&k, &app.user_config.keys) => {
         artist.artist_selected_block = ArtistBlock::Empty;
         match artist.artist_hovered_block {
           ArtistBlock::TopTracks => common_key_events::handle_left_event(app),
@@ -265,21 +265,21 @@ pub fn handler(key: Key, app: &mut App) {
           ArtistBlock::Empty => {}
         }
       }
-      k if common_key_events::right_event(k) => {
+      k if common_key_events::right_event(&k, &app.user_config.keys) => {
         artist.artist_selected_block = ArtistBlock::Empty;
         handle_down_press_on_hovered_block(app);
       }
-      k if common_key_events::high_event(k) => {
+      k if common_key_events::high_event(&k, &app.user_config.keys) => {
         if artist.artist_selected_block != ArtistBlock::Empty {
           handle_high_press_on_selected_block(app);
         }
       }
-      k if common_key_events::middle_event(k) => {
+      k if common_key_events::middle_event(&k, &app.user_config.keys) => {
         if artist.artist_selected_block != ArtistBlock::Empty {
           handle_middle_press_on_selected_block(app);
         }
       }
-      k if common_key_events::low_event(k) => {
+      k if common_key_events::low_event(&k, &app.user_config.keys) => {
         if artist.artist_selected_block != ArtistBlock::Empty {
           handle_low_press_on_selected_block(app);
         }
@@ -291,17 +291,17 @@ pub fn handler(key: Key, app: &mut App) {
           handle_enter_event_on_hovered_block(app);
         }
       }
-      Key::Char('r') => {
+      k if k == app.user_config.keys.play_recommendations => {
         if artist.artist_selected_block != ArtistBlock::Empty {
           handle_recommend_event_on_selected_block(app);
         }
       }
-      Key::Char('w') => match artist.artist_selected_block {
+      k if k == app.user_config.keys.save_context => match artist.artist_selected_block {
         ArtistBlock::Albums => app.current_user_saved_album_add(ActiveBlock::ArtistBlock),
         ArtistBlock::RelatedArtists => app.user_follow_artists(ActiveBlock::ArtistBlock),
         _ => (),
       },
-      Key::Char('D') => match artist.artist_selected_block {
+      k if k == app.user_config.keys.delete => match artist.artist_selected_block {
         ArtistBlock::Albums => app.current_user_saved_album_delete(ActiveBlock::ArtistBlock),
         ArtistBlock::RelatedArtists => app.user_unfollow_artists(ActiveBlock::ArtistBlock),
         _ => (),
@@ -6,8 +6,8 @@ use crate::{
 
 pub fn handler(key: Key, app: &mut App) {
     match key {
-        k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
-        k if common_key_events::down_event(k) => {
+        k if common_key_events::left_event(&k, &app.user_config.keys) => common_key_events::handle_left_event(app),
+        k if common_key_events::down_event(&k, &app.user_config.keys) => {
             if let Some(artist_albums) = &mut app.artist_albums {
                 let next_index = common_key_events::on_down_press_handler(
                     &artist_albums.albums.items,
@@ -16,7 +16,7 @@ pub fn handler(key: Key, app: &mut App) {
                 artist_albums.selected_index = next_index;
             }
         }
-        k if common_key_events::up_event(k) => {
+        k if common_key_events::up_event(&k, &app.user_config.keys) => {
             if let Some(artist_albums) = &mut app.artist_albums {
                 let next_index = common_key_events::on_up_press_handler(
                     &artist_albums.albums.items,
@@ -7,34 +7,36 @@ use crate::{
 
 pub fn handler(key: Key, app: &mut App) {
   match key {
-    k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
-    k if common_key_events::down_event(k) => {
+    k if common_key_events::left_event(&k, &app.user_config.keys) => {
+      common_key_events::handle_left_event(app)
+    }
+    k if common_key_events::down_event(&k, &app.user_config.keys) => {
       if let Some(artists) = &mut app.library.saved_artists.get_results(None) {
         let next_index =
           common_key_events::on_down_press_handler(&artists.items, Some(app.artists_list_index));
         app.artists_list_index = next_index;
       }
     }
-    k if common_key_events::up_event(k) => {
+    k if common_key_events::up_event(&k, &app.user_config.keys) => {
       if let Some(artists) = &mut app.library.saved_artists.get_results(None) {
         let next_index =
           common_key_events::on_up_press_handler(&artists.items, Some(app.artists_list_index));
         app.artists_list_index = next_index;
       }
     }
-    k if common_key_events::high_event(k) => {
+    k if common_key_events::high_event(&k, &app.user_config.keys) => {
       if let Some(_artists) = &mut app.library.saved_artists.get_results(None) {
         let next_index = common_key_events::on_high_press_handler();
         app.artists_list_index = next_index;
       }
     }
-    k if common_key_events::middle_event(k) => {
+    k if common_key_events::middle_event(&k, &app.user_config.keys) => {
       if let Some(artists) = &mut app.library.saved_artists.get_results(None) {
         let next_index = common_key_events::on_middle_press_handler(&artists.items);
         app.artists_list_index = next_index;
       }
     }
-    k if common_key_events::low_event(k) => {
+    k if common_key_events::low_event(&k, &app.user_config.keys) => {
       if let Some(artists) = &mut app.library.saved_artists.get_results(None) {
         let next_index = common_key_events::on_low_press_handler(&artists.items);
         app.artists_list_index = next_index;
@@ -48,8 +50,8 @@ pub fn handler(key: Key, app: &mut App) {
         app.push_navigation_stack(RouteId::Artist, ActiveBlock::ArtistBlock);
       }
     }
-    Key::Char('D') => app.user_unfollow_artists(ActiveBlock::AlbumList),
-    Key::Char('e') => {
+    k if k == app.user_config.keys.delete => app.user_unfollow_artists(ActiveBlock::AlbumList),
+    k if k == app.user_config.keys.play_artist => {
       let artists = app.artists.to_owned();
       let artist = artists.get(app.artists_list_index);
       if let Some(artist) = artist {
@@ -60,7 +62,7 @@ pub fn handler(key: Key, app: &mut App) {
         ));
       }
     }
-    Key::Char('r') => {
+    k if k == app.user_config.keys.play_recommendations => {
       let artists = app.artists.to_owned();
       let artist = artists.get(app.artists_list_index);
       if let Some(artist) = artist {
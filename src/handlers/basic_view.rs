@@ -2,7 +2,7 @@ use crate::{app::App, event::Key, network::IoEvent};
 use rspotify::model::{context::CurrentlyPlaybackContext, PlayingItem};
 
 pub fn handler(key: Key, app: &mut App) {
-  if let Key::Char('s') = key {
+  if key == app.user_config.keys.save {
     if let Some(CurrentlyPlaybackContext {
       item: Some(item), ..
     }) = app.current_playback_context.to_owned()
@@ -0,0 +1,98 @@
+use crate::app::App;
+use crate::event::Key;
+
+// Repeating an action more often than this is most likely a typo
+const MAX_KEY_COUNT: u32 = 99;
+
+/// Collects counts and chords, returns the key to handle and how often once it is complete
+pub fn handler(key: Key, app: &mut App) -> Option<(Key, u32)> {
+  let is_pending = !app.pending_keys.is_empty() || app.key_count.is_some();
+  if key == Key::Esc && is_pending {
+    app.pending_keys.clear();
+    app.key_count = None;
+    return None;
+  }
+
+  if app.pending_keys.is_empty() {
+    if let Key::Char(c) = key {
+      if let Some(digit) = c.to_digit(10) {
+        // A count can't start with 0, and digits that are bound keep working
+        if (digit != 0 || app.key_count.is_some()) && !app.user_config.keys.is_bound(&key) {
+          let count = app.key_count.unwrap_or(0) * 10 + digit;
+          app.key_count = Some(count.min(MAX_KEY_COUNT));
+          return None;
+        }
+      }
+    }
+  }
+
+  app.pending_keys.push(key.clone());
+  let chords = app.user_config.keys.get_chords();
+  let completed = chords
+    .iter()
+    .find(|chord| chord.get_keys() == app.pending_keys.as_slice());
+  let key = match completed {
+    Some(chord) => chord.clone(),
+    None
+      if chords
+        .iter()
+        .any(|chord| chord.get_keys().starts_with(&app.pending_keys)) =>
+    {
+      return None;
+    }
+    // Keys that don't continue a chord are dropped along with it, like in vim
+    None if app.pending_keys.len() > 1 => {
+      app.pending_keys.clear();
+      app.key_count = None;
+      return None;
+    }
+    None => key,
+  };
+  app.pending_keys.clear();
+  Some((key, app.key_count.take().unwrap_or(1)))
+}
+
+/// The count and keys typed so far, shown until the chord is complete
+pub fn get_pending_text(app: &App) -> Option<String> {
+  if app.pending_keys.is_empty() && app.key_count.is_none() {
+    return None;
+  }
+  let mut text = app
+    .key_count
+    .map(|count| count.to_string())
+    .unwrap_or_default();
+  for key in &app.pending_keys {
+    text.push_str(&key.to_string());
+  }
+  Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn resolves_counts_and_chords() {
+    let mut app = App::default();
+    let chord = Key::chord(&[Key::Char('g'), Key::Char('a')]);
+    app.user_config.keys.jump_to_album = chord.clone();
+
+    assert_eq!(handler(Key::Char('j'), &mut app), Some((Key::Char('j'), 1)));
+
+    assert_eq!(handler(Key::Char('1'), &mut app), None);
+    assert_eq!(handler(Key::Char('2'), &mut app), None);
+    assert_eq!(handler(Key::Char('g'), &mut app), None);
+    assert_eq!(get_pending_text(&app), Some(String::from("12g")));
+    assert_eq!(handler(Key::Char('a'), &mut app), Some((chord, 12)));
+    assert_eq!(get_pending_text(&app), None);
+
+    // A key that doesn't continue the chord drops it
+    assert_eq!(handler(Key::Char('g'), &mut app), None);
+    assert_eq!(handler(Key::Char('x'), &mut app), None);
+    assert_eq!(handler(Key::Char('x'), &mut app), Some((Key::Char('x'), 1)));
+
+    assert_eq!(handler(Key::Char('5'), &mut app), None);
+    assert_eq!(handler(Key::Esc, &mut app), None);
+    assert_eq!(get_pending_text(&app), None);
+  }
+}
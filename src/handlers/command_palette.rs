@@ -0,0 +1,107 @@
+use super::handle_app;
+use crate::app::{ActiveBlock, App};
+use crate::command_palette::{self, Command, CommandPalette};
+use crate::event::Key;
+use crate::network::IoEvent;
+
+pub fn open(app: &mut App) {
+  let previous_block = app.get_current_route().active_block;
+  app.command_palette = Some(CommandPalette {
+    query: vec![],
+    selected_index: 0,
+    previous_block,
+  });
+  app.set_current_route_state(Some(ActiveBlock::CommandPalette), None);
+  // The devices to transfer playback to
+  app.dispatch(IoEvent::RefreshDevices);
+}
+
+fn close(app: &mut App) {
+  if let Some(palette) = app.command_palette.take() {
+    app.set_current_route_state(Some(palette.previous_block), None);
+  }
+}
+
+// Typed keys go to the query, so only keys that can't be typed move the selection
+pub fn handler(key: Key, app: &mut App) {
+  let entries = command_palette::get_entries(app);
+  let palette = match &mut app.command_palette {
+    Some(palette) => palette,
+    None => return,
+  };
+  match key {
+    Key::Esc => close(app),
+    Key::Enter => {
+      if let Some(entry) = entries.get(palette.selected_index) {
+        run(app, entry.command.clone());
+      }
+    }
+    Key::Down | Key::Ctrl('n') if !entries.is_empty() => {
+      palette.selected_index = (palette.selected_index + 1) % entries.len();
+    }
+    Key::Up | Key::Ctrl('p') if !entries.is_empty() => {
+      palette.selected_index = (palette.selected_index + entries.len() - 1) % entries.len();
+    }
+    Key::Backspace => {
+      palette.query.pop();
+      palette.selected_index = 0;
+    }
+    Key::Char(c) => {
+      palette.query.push(c);
+      palette.selected_index = 0;
+    }
+    _ => {}
+  }
+}
+
+fn run(app: &mut App, command: Command) {
+  if let Command::Complete(query) = command {
+    if let Some(palette) = &mut app.command_palette {
+      palette.query = query.chars().collect();
+      palette.selected_index = 0;
+    }
+    return;
+  }
+
+  close(app);
+  match command {
+    Command::Binding(key) => handle_app(key, app),
+    Command::SetVolume(volume) => app.dispatch(IoEvent::ChangeVolume(volume)),
+    Command::Seek(position_ms) => app.dispatch(IoEvent::Seek(position_ms)),
+    Command::TransferPlayback(device_id) => {
+      app.dispatch(IoEvent::TransferPlaybackToDevice(device_id))
+    }
+    Command::ExportLibrary(format) => app.dispatch(IoEvent::ExportLibrary(format)),
+    Command::SetSleepTimer(mode) => {
+      if let Err(e) = app.set_sleep_timer(mode) {
+        app.handle_error(e);
+      }
+    }
+    Command::CancelSleepTimer => app.cancel_sleep_timer(),
+    Command::Complete(_) => {}
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::app::RouteId;
+
+  #[test]
+  fn runs_the_action_in_the_previous_block() {
+    let mut app = App::default();
+    app.push_navigation_stack(RouteId::Home, ActiveBlock::Home);
+    open(&mut app);
+    assert_eq!(
+      app.get_current_route().active_block,
+      ActiveBlock::CommandPalette
+    );
+
+    for c in "lyrics".chars() {
+      handler(Key::Char(c), &mut app);
+    }
+    handler(Key::Enter, &mut app);
+    assert!(app.command_palette.is_none());
+    assert_eq!(app.get_current_route().id, RouteId::Lyrics);
+  }
+}
@@ -1,32 +1,33 @@
 use super::super::app::{ActiveBlock, App, RouteId};
 use crate::event::Key;
+use crate::user_config::KeyBindings;
 
-pub fn down_event(key: Key) -> bool {
-  matches!(key, Key::Down | Key::Char('j') | Key::Ctrl('n'))
+pub fn down_event(key: &Key, keys: &KeyBindings) -> bool {
+  *key == keys.move_down || matches!(key, Key::Down | Key::Ctrl('n'))
 }
 
-pub fn up_event(key: Key) -> bool {
-  matches!(key, Key::Up | Key::Char('k') | Key::Ctrl('p'))
+pub fn up_event(key: &Key, keys: &KeyBindings) -> bool {
+  *key == keys.move_up || matches!(key, Key::Up | Key::Ctrl('p'))
 }
 
-pub fn left_event(key: Key) -> bool {
-  matches!(key, Key::Left | Key::Char('h') | Key::Ctrl('b'))
+pub fn left_event(key: &Key, keys: &KeyBindings) -> bool {
+  *key == keys.move_left || matches!(key, Key::Left | Key::Ctrl('b'))
 }
 
-pub fn right_event(key: Key) -> bool {
-  matches!(key, Key::Right | Key::Char('l') | Key::Ctrl('f'))
+pub fn right_event(key: &Key, keys: &KeyBindings) -> bool {
+  *key == keys.move_right || matches!(key, Key::Right | Key::Ctrl('f'))
 }
 
-pub fn high_event(key: Key) -> bool {
-  matches!(key, Key::Char('H'))
+pub fn high_event(key: &Key, keys: &KeyBindings) -> bool {
+  *key == keys.move_to_top
 }
 
-pub fn middle_event(key: Key) -> bool {
-  matches!(key, Key::Char('M'))
+pub fn middle_event(key: &Key, keys: &KeyBindings) -> bool {
+  *key == keys.move_to_middle
 }
 
-pub fn low_event(key: Key) -> bool {
-  matches!(key, Key::Char('L'))
+pub fn low_event(key: &Key, keys: &KeyBindings) -> bool {
+  *key == keys.move_to_bottom
 }
 
 pub fn on_down_press_handler<T>(selection_data: &[T], selection_index: Option<usize>) -> usize {
@@ -79,64 +80,80 @@ pub fn on_low_press_handler<T>(selection_data: &[T]) -> usize {
 }
 
 pub fn handle_right_event(app: &mut App) {
-  match app.get_current_route().hovered_block {
-    ActiveBlock::MyPlaylists | ActiveBlock::Library => match app.get_current_route().id {
-      RouteId::AlbumTracks => {
-        app.set_current_route_state(
-          Some(ActiveBlock::AlbumTracks),
-          Some(ActiveBlock::AlbumTracks),
-        );
-      }
-      RouteId::TrackTable => {
-        app.set_current_route_state(Some(ActiveBlock::TrackTable), Some(ActiveBlock::TrackTable));
-      }
-      RouteId::Podcasts => {
-        app.set_current_route_state(Some(ActiveBlock::Podcasts), Some(ActiveBlock::Podcasts));
-      }
-      RouteId::Recommendations => {
-        app.set_current_route_state(Some(ActiveBlock::TrackTable), Some(ActiveBlock::TrackTable));
-      }
-      RouteId::AlbumList => {
-        app.set_current_route_state(Some(ActiveBlock::AlbumList), Some(ActiveBlock::AlbumList));
-      }
-      RouteId::PodcastEpisodes => {
-        app.set_current_route_state(
-          Some(ActiveBlock::EpisodeTable),
-          Some(ActiveBlock::EpisodeTable),
-        );
-      }
-      RouteId::MadeForYou => {
-        app.set_current_route_state(Some(ActiveBlock::MadeForYou), Some(ActiveBlock::MadeForYou));
-      }
-      RouteId::Artists => {
-        app.set_current_route_state(Some(ActiveBlock::Artists), Some(ActiveBlock::Artists));
-      }
-      RouteId::RecentlyPlayed => {
-        app.set_current_route_state(
-          Some(ActiveBlock::RecentlyPlayed),
-          Some(ActiveBlock::RecentlyPlayed),
-        );
-      }
-      RouteId::Search => {
-        app.set_current_route_state(
-          Some(ActiveBlock::SearchResultBlock),
-          Some(ActiveBlock::SearchResultBlock),
-        );
-      }
-      RouteId::Artist => app.set_current_route_state(
-        Some(ActiveBlock::ArtistBlock),
-        Some(ActiveBlock::ArtistBlock),
-      ),
-      RouteId::Home => {
-        app.set_current_route_state(Some(ActiveBlock::Home), Some(ActiveBlock::Home));
-      }
-      RouteId::SelectedDevice => {}
-      RouteId::Error => {}
-      RouteId::Analysis => {}
-      RouteId::BasicView => {}
-      RouteId::Dialog => {}
-    },
-    _ => {}
+  if matches!(
+    app.get_current_route().hovered_block,
+    ActiveBlock::MyPlaylists | ActiveBlock::Library
+  ) {
+    select_route_block(app);
+  }
+}
+
+// Selects the main block of the current route
+pub fn select_route_block(app: &mut App) {
+  match app.get_current_route().id {
+    RouteId::AlbumTracks => {
+      app.set_current_route_state(
+        Some(ActiveBlock::AlbumTracks),
+        Some(ActiveBlock::AlbumTracks),
+      );
+    }
+    RouteId::TrackTable => {
+      app.set_current_route_state(Some(ActiveBlock::TrackTable), Some(ActiveBlock::TrackTable));
+    }
+    RouteId::Podcasts => {
+      app.set_current_route_state(Some(ActiveBlock::Podcasts), Some(ActiveBlock::Podcasts));
+    }
+    RouteId::Recommendations => {
+      app.set_current_route_state(Some(ActiveBlock::TrackTable), Some(ActiveBlock::TrackTable));
+    }
+    RouteId::AlbumList => {
+      app.set_current_route_state(Some(ActiveBlock::AlbumList), Some(ActiveBlock::AlbumList));
+    }
+    RouteId::PodcastEpisodes => {
+      app.set_current_route_state(
+        Some(ActiveBlock::EpisodeTable),
+        Some(ActiveBlock::EpisodeTable),
+      );
+    }
+    RouteId::MadeForYou => {
+      app.set_current_route_state(Some(ActiveBlock::MadeForYou), Some(ActiveBlock::MadeForYou));
+    }
+    RouteId::Artists => {
+      app.set_current_route_state(Some(ActiveBlock::Artists), Some(ActiveBlock::Artists));
+    }
+    RouteId::RecentlyPlayed => {
+      app.set_current_route_state(
+        Some(ActiveBlock::RecentlyPlayed),
+        Some(ActiveBlock::RecentlyPlayed),
+      );
+    }
+    RouteId::Search => {
+      app.set_current_route_state(
+        Some(ActiveBlock::SearchResultBlock),
+        Some(ActiveBlock::SearchResultBlock),
+      );
+    }
+    RouteId::Artist => app.set_current_route_state(
+      Some(ActiveBlock::ArtistBlock),
+      Some(ActiveBlock::ArtistBlock),
+    ),
+    RouteId::Home => {
+      app.set_current_route_state(Some(ActiveBlock::Home), Some(ActiveBlock::Home));
+    }
+    RouteId::SelectedDevice => {}
+    RouteId::Error => {}
+    RouteId::Analysis => {}
+    RouteId::BasicView => {}
+    RouteId::Dialog => {}
+    RouteId::Queue => {
+      app.set_current_route_state(Some(ActiveBlock::Queue), Some(ActiveBlock::Queue));
+    }
+    RouteId::Lyrics => {
+      app.set_current_route_state(Some(ActiveBlock::Lyrics), Some(ActiveBlock::Lyrics));
+    }
+    RouteId::Stats => {
+      app.set_current_route_state(Some(ActiveBlock::Stats), Some(ActiveBlock::Stats));
+    }
   };
 }
 
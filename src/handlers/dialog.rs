@@ -1,7 +1,17 @@
 use super::super::app::{ActiveBlock, App, DialogContext};
+use super::common_key_events;
 use crate::event::Key;
 
 pub fn handler(key: Key, app: &mut App) {
+  if let ActiveBlock::Dialog(context) = app.get_current_route().active_block {
+    if context == DialogContext::AddToPlaylist {
+      return handle_playlist_picker(key, app);
+    }
+    if context.is_text_input() {
+      return handle_text_input(key, app, context);
+    }
+  }
+
   match key {
     Key::Enter => {
       if let Some(route) = app.pop_navigation_stack() {
@@ -10,12 +20,14 @@ pub fn handler(key: Key, app: &mut App) {
             match d {
               DialogContext::PlaylistWindow => handle_playlist_dialog(app),
               DialogContext::PlaylistSearch => handle_playlist_search_dialog(app),
+              DialogContext::RemoveFromPlaylist => app.remove_track_from_playlist(),
+              _ => {}
             }
           }
         }
       }
     }
-    Key::Char('q') => {
+    k if k == app.user_config.keys.back => {
       app.pop_navigation_stack();
     }
     Key::Right => app.confirm = !app.confirm,
@@ -31,3 +43,78 @@ fn handle_playlist_dialog(app: &mut App) {
 fn handle_playlist_search_dialog(app: &mut App) {
   app.user_unfollow_playlist_search_result()
 }
+
+fn handle_playlist_picker(key: Key, app: &mut App) {
+  let playlists = app.get_editable_playlists();
+  match key {
+    k if common_key_events::down_event(&k, &app.user_config.keys) => {
+      app.playlist_picker_index =
+        common_key_events::on_down_press_handler(&playlists, Some(app.playlist_picker_index));
+    }
+    k if common_key_events::up_event(&k, &app.user_config.keys) => {
+      app.playlist_picker_index =
+        common_key_events::on_up_press_handler(&playlists, Some(app.playlist_picker_index));
+    }
+    k if common_key_events::high_event(&k, &app.user_config.keys) => {
+      app.playlist_picker_index = common_key_events::on_high_press_handler();
+    }
+    k if common_key_events::middle_event(&k, &app.user_config.keys) => {
+      app.playlist_picker_index = common_key_events::on_middle_press_handler(&playlists);
+    }
+    k if common_key_events::low_event(&k, &app.user_config.keys) => {
+      app.playlist_picker_index = common_key_events::on_low_press_handler(&playlists);
+    }
+    Key::Enter => {
+      app.pop_navigation_stack();
+      app.add_tracks_to_picked_playlist();
+    }
+    k if k == app.user_config.keys.back => {
+      app.pop_navigation_stack();
+    }
+    _ => {}
+  }
+}
+
+// Text dialogs receive every key press, so they have to handle escaping themselves
+fn handle_text_input(key: Key, app: &mut App, context: DialogContext) {
+  match key {
+    Key::Enter => {
+      app.pop_navigation_stack();
+      app.submit_playlist_text_dialog(context);
+    }
+    Key::Esc => {
+      app.pop_navigation_stack();
+      app.dialog_input.clear();
+    }
+    Key::Backspace => {
+      app.dialog_input.pop();
+    }
+    Key::Char(c) => {
+      app.dialog_input.push(c);
+    }
+    _ => {}
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::app::RouteId;
+
+  #[test]
+  fn text_input_receives_characters() {
+    let mut app = App::default();
+    app.open_playlist_text_dialog(DialogContext::CreatePlaylist);
+
+    handler(Key::Char('q'), &mut app);
+    handler(Key::Char('a'), &mut app);
+    handler(Key::Backspace, &mut app);
+
+    assert_eq!(app.dialog_input, vec!['q']);
+    assert_eq!(app.get_current_route().id, RouteId::Dialog);
+
+    handler(Key::Esc, &mut app);
+    assert!(app.dialog_input.is_empty());
+    assert_eq!(app.get_current_route().id, RouteId::Home);
+  }
+}
@@ -8,34 +8,36 @@ use crate::network::IoEvent;
 
 pub fn handler(key: Key, app: &mut App) {
   match key {
-    k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
-    k if common_key_events::down_event(k) => {
+    k if common_key_events::left_event(&k, &app.user_config.keys) => {
+      common_key_events::handle_left_event(app)
+    }
+    k if common_key_events::down_event(&k, &app.user_config.keys) => {
       if let Some(episodes) = &mut app.library.show_episodes.get_results(None) {
         let next_index =
           common_key_events::on_down_press_handler(&episodes.items, Some(app.episode_list_index));
         app.episode_list_index = next_index;
       }
     }
-    k if common_key_events::up_event(k) => {
+    k if common_key_events::up_event(&k, &app.user_config.keys) => {
       if let Some(episodes) = &mut app.library.show_episodes.get_results(None) {
         let next_index =
           common_key_events::on_up_press_handler(&episodes.items, Some(app.episode_list_index));
         app.episode_list_index = next_index;
       }
     }
-    k if common_key_events::high_event(k) => {
+    k if common_key_events::high_event(&k, &app.user_config.keys) => {
       if let Some(_episodes) = app.library.show_episodes.get_results(None) {
         let next_index = common_key_events::on_high_press_handler();
         app.episode_list_index = next_index;
       }
     }
-    k if common_key_events::middle_event(k) => {
+    k if common_key_events::middle_event(&k, &app.user_config.keys) => {
       if let Some(episodes) = app.library.show_episodes.get_results(None) {
         let next_index = common_key_events::on_middle_press_handler(&episodes.items);
         app.episode_list_index = next_index;
       }
     }
-    k if common_key_events::low_event(k) => {
+    k if common_key_events::low_event(&k, &app.user_config.keys) => {
       if let Some(episodes) = app.library.show_episodes.get_results(None) {
         let next_index = common_key_events::on_low_press_handler(&episodes.items);
         app.episode_list_index = next_index;
@@ -48,11 +50,11 @@ pub fn handler(key: Key, app: &mut App) {
     k if k == app.user_config.keys.next_page => handle_next_event(app),
     // Scroll up
     k if k == app.user_config.keys.previous_page => handle_prev_event(app),
-    Key::Char('S') => toggle_sort_by_date(app),
-    Key::Char('s') => handle_follow_event(app),
-    Key::Char('D') => handle_unfollow_event(app),
-    Key::Ctrl('e') => jump_to_end(app),
-    Key::Ctrl('a') => jump_to_start(app),
+    k if k == app.user_config.keys.toggle_episode_order => toggle_sort_by_date(app),
+    k if k == app.user_config.keys.save => handle_follow_event(app),
+    k if k == app.user_config.keys.delete => handle_unfollow_event(app),
+    k if k == app.user_config.keys.jump_to_end => jump_to_end(app),
+    k if k == app.user_config.keys.jump_to_start => jump_to_start(app),
     _ => {}
   }
 }
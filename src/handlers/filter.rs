@@ -0,0 +1,104 @@
+use super::common_key_events;
+use crate::app::App;
+use crate::event::Key;
+use crate::filter::{self, ListFilter};
+
+pub fn start(app: &mut App) {
+  let block = app.get_current_route().active_block;
+  app.filter = Some(ListFilter {
+    block,
+    query: vec![],
+    is_editing: true,
+  });
+}
+
+// Keys go to the query while it is typed, so they don't trigger the global bindings
+pub fn handler(key: Key, app: &mut App) {
+  if let Some(list_filter) = &mut app.filter {
+    match key {
+      Key::Enter => {
+        list_filter.is_editing = false;
+        if list_filter.query.is_empty() {
+          app.filter = None;
+        }
+      }
+      Key::Esc => app.filter = None,
+      Key::Backspace => {
+        list_filter.query.pop();
+      }
+      Key::Char(c) => list_filter.query.push(c),
+      _ => {}
+    }
+  }
+  filter::select_match(app);
+}
+
+// Moves the selection between the rows that match, returns false for keys that are not
+// used for moving
+pub fn handle_navigation(key: &Key, app: &mut App) -> bool {
+  let block = app.get_current_route().active_block;
+  let matches = match filter::get_visible_indices(app, block) {
+    Some(matches) => matches,
+    None => return false,
+  };
+  let selected_index = filter::get_selected_index(app, block);
+  let position = matches.iter().position(|&index| index == selected_index);
+
+  let next_position = match key {
+    k if common_key_events::down_event(k, &app.user_config.keys) => {
+      common_key_events::on_down_press_handler(&matches, position)
+    }
+    k if common_key_events::up_event(k, &app.user_config.keys) => {
+      common_key_events::on_up_press_handler(&matches, position)
+    }
+    k if common_key_events::high_event(k, &app.user_config.keys) => {
+      common_key_events::on_high_press_handler()
+    }
+    k if common_key_events::middle_event(k, &app.user_config.keys) => {
+      common_key_events::on_middle_press_handler(&matches)
+    }
+    k if common_key_events::low_event(k, &app.user_config.keys) => {
+      common_key_events::on_low_press_handler(&matches)
+    }
+    _ => return false,
+  };
+
+  if let Some(&index) = matches.get(next_position) {
+    filter::set_selected_index(app, block, index);
+  }
+  true
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::app::{ActiveBlock, RouteId};
+
+  #[test]
+  fn typing_builds_the_query() {
+    let mut app = App::default();
+    app.push_navigation_stack(RouteId::Artists, ActiveBlock::Artists);
+    start(&mut app);
+
+    handler(Key::Char('a'), &mut app);
+    handler(Key::Char('q'), &mut app);
+    handler(Key::Backspace, &mut app);
+    handler(Key::Char('b'), &mut app);
+    assert!(filter::is_editing(&app));
+    assert_eq!(
+      filter::get_title(&app, ActiveBlock::Artists, "Artists"),
+      "Artists (filter: ab_)"
+    );
+
+    handler(Key::Enter, &mut app);
+    assert!(!filter::is_editing(&app));
+    assert_eq!(
+      filter::get_title(&app, ActiveBlock::Artists, "Artists"),
+      "Artists (filter: ab)"
+    );
+
+    // Opening another view drops the filter
+    app.push_navigation_stack(RouteId::TrackTable, ActiveBlock::TrackTable);
+    assert!(app.filter.is_none());
+  }
+}
@@ -9,16 +9,16 @@ enum Direction {
 
 pub fn handler(key: Key, app: &mut App) {
   match key {
-    k if common_key_events::down_event(k) => {
+    k if common_key_events::down_event(&k, &app.user_config.keys) => {
       move_page(Direction::Down, app);
     }
-    k if common_key_events::up_event(k) => {
+    k if common_key_events::up_event(&k, &app.user_config.keys) => {
       move_page(Direction::Up, app);
     }
-    Key::Ctrl('d') => {
+    k if k == app.user_config.keys.next_page => {
       move_page(Direction::Down, app);
     }
-    Key::Ctrl('u') => {
+    k if k == app.user_config.keys.previous_page => {
       move_page(Direction::Up, app);
     }
     _ => {}
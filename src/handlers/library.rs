@@ -7,28 +7,30 @@ use crate::network::IoEvent;
 
 pub fn handler(key: Key, app: &mut App) {
   match key {
-    k if common_key_events::right_event(k) => common_key_events::handle_right_event(app),
-    k if common_key_events::down_event(k) => {
+    k if common_key_events::right_event(&k, &app.user_config.keys) => {
+      common_key_events::handle_right_event(app)
+    }
+    k if common_key_events::down_event(&k, &app.user_config.keys) => {
       let next_index = common_key_events::on_down_press_handler(
         &LIBRARY_OPTIONS,
         Some(app.library.selected_index),
       );
       app.library.selected_index = next_index;
     }
-    k if common_key_events::up_event(k) => {
+    k if common_key_events::up_event(&k, &app.user_config.keys) => {
       let next_index =
         common_key_events::on_up_press_handler(&LIBRARY_OPTIONS, Some(app.library.selected_index));
       app.library.selected_index = next_index;
     }
-    k if common_key_events::high_event(k) => {
+    k if common_key_events::high_event(&k, &app.user_config.keys) => {
       let next_index = common_key_events::on_high_press_handler();
       app.library.selected_index = next_index;
     }
-    k if common_key_events::middle_event(k) => {
+    k if common_key_events::middle_event(&k, &app.user_config.keys) => {
       let next_index = common_key_events::on_middle_press_handler(&LIBRARY_OPTIONS);
       app.library.selected_index = next_index;
     }
-    k if common_key_events::low_event(k) => {
+    k if common_key_events::low_event(&k, &app.user_config.keys) => {
       let next_index = common_key_events::on_low_press_handler(&LIBRARY_OPTIONS);
       app.library.selected_index = next_index
     }
@@ -47,6 +49,10 @@ pub fn handler(key: Key, app: &mut App) {
       }
       // Liked Songs,
       2 => {
+        // Show the cached first page while it is being refreshed
+        if let Some(saved_tracks) = app.library.saved_tracks.get_results(Some(0)).cloned() {
+          app.set_saved_tracks_to_table(&saved_tracks);
+        }
         app.dispatch(IoEvent::GetCurrentSavedTracks(None));
         app.push_navigation_stack(RouteId::TrackTable, ActiveBlock::TrackTable);
       }
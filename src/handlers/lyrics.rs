@@ -0,0 +1,72 @@
+use super::{super::app::App, common_key_events};
+use crate::event::Key;
+use crate::network::IoEvent;
+
+pub fn handler(key: Key, app: &mut App) {
+  let lines = match &app.lyrics.lyrics {
+    Some(lyrics) => lyrics.lines.clone(),
+    None => vec![],
+  };
+  // Moving starts from the line that is being sung
+  let selected_index = app
+    .lyrics
+    .selected_index
+    .or_else(|| app.lyrics.get_current_line(app.song_progress_ms));
+
+  match key {
+    k if common_key_events::left_event(&k, &app.user_config.keys) => {
+      common_key_events::handle_left_event(app)
+    }
+    k if common_key_events::down_event(&k, &app.user_config.keys) => {
+      let next_index = common_key_events::on_down_press_handler(&lines, selected_index);
+      app.lyrics.selected_index = Some(next_index);
+    }
+    k if common_key_events::up_event(&k, &app.user_config.keys) => {
+      let next_index = common_key_events::on_up_press_handler(&lines, selected_index);
+      app.lyrics.selected_index = Some(next_index);
+    }
+    k if common_key_events::high_event(&k, &app.user_config.keys) => {
+      app.lyrics.selected_index = Some(common_key_events::on_high_press_handler());
+    }
+    k if common_key_events::middle_event(&k, &app.user_config.keys) => {
+      app.lyrics.selected_index = Some(common_key_events::on_middle_press_handler(&lines));
+    }
+    k if common_key_events::low_event(&k, &app.user_config.keys) => {
+      app.lyrics.selected_index = Some(common_key_events::on_low_press_handler(&lines));
+    }
+    Key::Enter => {
+      let time_ms = app
+        .lyrics
+        .selected_index
+        .and_then(|index| lines.get(index))
+        .and_then(|line| line.time_ms);
+      if let Some(time_ms) = time_ms {
+        app.dispatch(IoEvent::Seek(time_ms));
+      }
+      // Follow the song again
+      app.lyrics.selected_index = None;
+    }
+    _ => {}
+  };
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::lyrics::Lyrics;
+
+  #[test]
+  fn moving_starts_at_the_current_line() {
+    let mut app = App::default();
+    app.lyrics.lyrics = Some(Lyrics::parse(
+      "[00:01.00]One\n[00:02.00]Two\n[00:03.00]Three",
+    ));
+    app.song_progress_ms = 2500;
+
+    handler(Key::Down, &mut app);
+    assert_eq!(app.lyrics.selected_index, Some(2));
+
+    handler(Key::Enter, &mut app);
+    assert_eq!(app.lyrics.selected_index, None);
+  }
+}
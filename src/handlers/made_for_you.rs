@@ -7,34 +7,36 @@ use crate::network::IoEvent;
 
 pub fn handler(key: Key, app: &mut App) {
   match key {
-    k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
-    k if common_key_events::up_event(k) => {
+    k if common_key_events::left_event(&k, &app.user_config.keys) => {
+      common_key_events::handle_left_event(app)
+    }
+    k if common_key_events::up_event(&k, &app.user_config.keys) => {
       if let Some(playlists) = &mut app.library.made_for_you_playlists.get_results(None) {
         let next_index =
           common_key_events::on_up_press_handler(&playlists.items, Some(app.made_for_you_index));
         app.made_for_you_index = next_index;
       }
     }
-    k if common_key_events::down_event(k) => {
+    k if common_key_events::down_event(&k, &app.user_config.keys) => {
       if let Some(playlists) = &mut app.library.made_for_you_playlists.get_results(None) {
         let next_index =
           common_key_events::on_down_press_handler(&playlists.items, Some(app.made_for_you_index));
         app.made_for_you_index = next_index;
       }
     }
-    k if common_key_events::high_event(k) => {
+    k if common_key_events::high_event(&k, &app.user_config.keys) => {
       if let Some(_playlists) = &mut app.library.made_for_you_playlists.get_results(None) {
         let next_index = common_key_events::on_high_press_handler();
         app.made_for_you_index = next_index;
       }
     }
-    k if common_key_events::middle_event(k) => {
+    k if common_key_events::middle_event(&k, &app.user_config.keys) => {
       if let Some(playlists) = &mut app.library.made_for_you_playlists.get_results(None) {
         let next_index = common_key_events::on_middle_press_handler(&playlists.items);
         app.made_for_you_index = next_index;
       }
     }
-    k if common_key_events::low_event(k) => {
+    k if common_key_events::low_event(&k, &app.user_config.keys) => {
       if let Some(playlists) = &mut app.library.made_for_you_playlists.get_results(None) {
         let next_index = common_key_events::on_low_press_handler(&playlists.items);
         app.made_for_you_index = next_index;
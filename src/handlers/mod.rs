@@ -4,30 +4,44 @@ mod analysis;
 mod artist;
 mod artists;
 mod basic_view;
+mod chord;
+mod command_palette;
 mod common_key_events;
 mod dialog;
 mod empty;
 mod episode_table;
 mod error_screen;
+mod filter;
 mod help_menu;
 mod home;
 mod input;
 mod library;
+mod lyrics;
 mod made_for_you;
+mod mouse;
 mod playbar;
 mod playlist;
 mod podcasts;
+mod queue;
 mod recently_played;
 mod search_results;
 mod select_device;
+mod stats;
 mod track_table;
 
 use super::app::{ActiveBlock, App, ArtistBlock, RouteId, SearchResultBlock};
 use crate::event::Key;
 use crate::network::IoEvent;
+use crate::sort;
+use anyhow::anyhow;
 use rspotify::model::{context::CurrentlyPlaybackContext, PlayingItem};
 
+pub use chord::{get_pending_text, handler as chord_handler};
+pub use command_palette::handler as command_palette_handler;
+pub use dialog::handler as dialog_handler;
+pub use filter::handler as filter_handler;
 pub use input::handler as input_handler;
+pub use mouse::handler as mouse_handler;
 
 pub fn handle_app(key: Key, app: &mut App) {
   // First handle any global event and then move to block event
@@ -47,6 +61,13 @@ pub fn handle_app(key: Key, app: &mut App) {
     _ if key == app.user_config.keys.manage_devices => {
       app.dispatch(IoEvent::GetDevices);
     }
+    // The device list changes the volume of the selected device
+    _ if (key == app.user_config.keys.decrease_volume
+      || key == app.user_config.keys.increase_volume)
+      && app.get_current_route().active_block == ActiveBlock::SelectDevice =>
+    {
+      select_device::change_volume(app, key == app.user_config.keys.increase_volume);
+    }
     _ if key == app.user_config.keys.decrease_volume => {
       app.decrease_volume();
     }
@@ -94,12 +115,39 @@ pub fn handle_app(key: Key, app: &mut App) {
     _ if key == app.user_config.keys.basic_view => {
       app.push_navigation_stack(RouteId::BasicView, ActiveBlock::BasicView);
     }
+    _ if key == app.user_config.keys.show_queue => {
+      app.dispatch(IoEvent::GetQueue);
+      app.push_navigation_stack(RouteId::Queue, ActiveBlock::Queue);
+    }
+    _ if key == app.user_config.keys.show_lyrics => {
+      app.show_lyrics();
+    }
+    _ if key == app.user_config.keys.show_stats => {
+      app.show_stats();
+    }
+    _ if key == app.user_config.keys.command_palette => {
+      command_palette::open(app);
+    }
+    _ if key == app.user_config.keys.filter
+      && crate::filter::is_filterable(app.get_current_route().active_block) =>
+    {
+      filter::start(app);
+    }
+    _ if key == app.user_config.keys.sort
+      && sort::get_context(app, app.get_current_route().active_block).is_some() =>
+    {
+      cycle_sort(app);
+    }
     _ => handle_block_events(key, app),
   }
 }
 
 // Handle event for the current active block
 fn handle_block_events(key: Key, app: &mut App) {
+  // A filtered block only moves between the rows that are shown
+  if filter::handle_navigation(&key, app) {
+    return;
+  }
   let current_route = app.get_current_route();
   match current_route.active_block {
     ActiveBlock::Analysis => {
@@ -168,11 +216,37 @@ fn handle_block_events(key: Key, app: &mut App) {
     ActiveBlock::Dialog(_) => {
       dialog::handler(key, app);
     }
+    ActiveBlock::Queue => {
+      queue::handler(key, app);
+    }
+    ActiveBlock::Lyrics => {
+      lyrics::handler(key, app);
+    }
+    ActiveBlock::Stats => {
+      stats::handler(key, app);
+    }
+    ActiveBlock::CommandPalette => {
+      command_palette::handler(key, app);
+    }
+  }
+}
+
+// Moves the table on to its next sort and remembers it
+fn cycle_sort(app: &mut App) {
+  let block = app.get_current_route().active_block;
+  if let Some(context) = sort::get_context(app, block) {
+    let next_sort = sort::get_next_sort(app, block, sort::get_sort(app, block));
+    if let Err(e) = app.table_sorts.set(context, next_sort) {
+      app.handle_error(anyhow!("Could not save the sort of the table: {}", e));
+    }
   }
 }
 
 fn handle_escape(app: &mut App) {
   match app.get_current_route().active_block {
+    block if matches!(&app.filter, Some(filter) if filter.block == block) => {
+      app.filter = None;
+    }
     ActiveBlock::SearchResultBlock => {
       app.search_results.selected_block = SearchResultBlock::Empty;
     }
@@ -187,6 +261,10 @@ fn handle_escape(app: &mut App) {
     ActiveBlock::Dialog(_) => {
       app.pop_navigation_stack();
     }
+    // Stop browsing the lyrics and follow the song again
+    ActiveBlock::Lyrics if app.lyrics.selected_index.is_some() => {
+      app.lyrics.selected_index = None;
+    }
     // These are global views that have no active/inactive distinction so do nothing
     ActiveBlock::SelectDevice | ActiveBlock::Analysis => {}
     _ => {
@@ -0,0 +1,228 @@
+use super::{common_key_events, handle_block_events};
+use crate::app::{ActiveBlock, App, LIBRARY_OPTIONS};
+use crate::event::Key;
+use crate::filter;
+use crate::ui::{self, util};
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use rspotify::model::{context::CurrentlyPlaybackContext, PlayingItem};
+use std::time::{Duration, Instant};
+use tui::layout::Rect;
+
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(500);
+
+pub fn handler(mouse: MouseEvent, app: &mut App) {
+  let (column, row) = (mouse.column, mouse.row);
+  match app.get_current_route().active_block {
+    // Only the playbar is shown in the basic view
+    ActiveBlock::BasicView => {
+      if let Some(playbar) = ui::get_basic_view_playbar(app, app.size) {
+        if contains(playbar, column, row) {
+          handle_playbar_event(mouse, app, playbar);
+        }
+      }
+    }
+    // Full screen routes and dialogs are keyboard only
+    ActiveBlock::HelpMenu
+    | ActiveBlock::Error
+    | ActiveBlock::SelectDevice
+    | ActiveBlock::Analysis
+    | ActiveBlock::CommandPalette
+    | ActiveBlock::Dialog(_) => {}
+    _ => match mouse.kind {
+      MouseEventKind::Down(MouseButton::Left) => {
+        let is_double_click = is_double_click(app, column, row);
+        on_click(app, column, row, is_double_click);
+      }
+      MouseEventKind::ScrollDown => on_scroll(mouse, app, Key::Down),
+      MouseEventKind::ScrollUp => on_scroll(mouse, app, Key::Up),
+      _ => {}
+    },
+  }
+}
+
+fn contains(area: Rect, column: u16, row: u16) -> bool {
+  column >= area.x && column < area.x + area.width && row >= area.y && row < area.y + area.height
+}
+
+fn is_double_click(app: &mut App, column: u16, row: u16) -> bool {
+  let now = Instant::now();
+  let is_double_click = match app.last_click {
+    Some((instant, last_column, last_row)) => {
+      now.duration_since(instant) < DOUBLE_CLICK_INTERVAL
+        && last_column == column
+        && last_row == row
+    }
+    None => false,
+  };
+  // A double click should not start the next one
+  app.last_click = if is_double_click {
+    None
+  } else {
+    Some((now, column, row))
+  };
+  is_double_click
+}
+
+// Index of the clicked item in a list with borders, `None` if a border was hit
+fn get_clicked_list_index(area: Rect, row: u16, selected_index: usize) -> Option<usize> {
+  let top = area.y + 1;
+  if row < top || row >= area.y + area.height.saturating_sub(1) {
+    return None;
+  }
+  Some(util::get_list_offset(area, selected_index) + (row - top) as usize)
+}
+
+// Index of the clicked row in a table drawn by `draw_table`, below the borders and the header
+fn get_clicked_table_index(area: Rect, row: u16, selected_index: usize) -> Option<usize> {
+  let top = area.y + 2;
+  if row < top || row >= area.y + area.height.saturating_sub(1) {
+    return None;
+  }
+  Some(util::get_table_offset(area, selected_index) + (row - top) as usize)
+}
+
+fn on_click(app: &mut App, column: u16, row: u16, is_double_click: bool) {
+  let layout = ui::get_main_layout(app, app.size);
+  let input = ui::get_input_and_help_chunks(app, layout.search)[0];
+
+  if contains(input, column, row) {
+    app.set_current_route_state(Some(ActiveBlock::Input), Some(ActiveBlock::Input));
+  } else if contains(layout.library, column, row) {
+    app.set_current_route_state(Some(ActiveBlock::Library), Some(ActiveBlock::Library));
+    if let Some(index) = get_clicked_list_index(layout.library, row, app.library.selected_index) {
+      if index < LIBRARY_OPTIONS.len() {
+        app.library.selected_index = index;
+        if is_double_click {
+          handle_block_events(Key::Enter, app);
+        }
+      }
+    }
+  } else if contains(layout.playlists, column, row) {
+    app.set_current_route_state(
+      Some(ActiveBlock::MyPlaylists),
+      Some(ActiveBlock::MyPlaylists),
+    );
+    let selected_index = filter::get_visible_position(app, ActiveBlock::MyPlaylists);
+    let index = get_clicked_list_index(layout.playlists, row, selected_index)
+      .and_then(|row| filter::get_filtered_index(app, ActiveBlock::MyPlaylists, row));
+    if let (Some(playlists), Some(index)) = (&app.playlists, index) {
+      if index < playlists.items.len() {
+        app.selected_playlist_index = Some(index);
+        if is_double_click {
+          handle_block_events(Key::Enter, app);
+        }
+      }
+    }
+  } else if contains(layout.routes, column, row) {
+    common_key_events::select_route_block(app);
+    on_route_click(app, layout.routes, row, is_double_click);
+  } else if contains(layout.playbar, column, row) {
+    let gauge = ui::get_playbar_chunks(app, layout.playbar)[2];
+    if contains(gauge, column, row) {
+      seek_to_column(app, gauge, column);
+    } else {
+      app.set_current_route_state(Some(ActiveBlock::PlayBar), Some(ActiveBlock::PlayBar));
+    }
+  }
+}
+
+fn on_route_click(app: &mut App, area: Rect, row: u16, is_double_click: bool) {
+  match app.get_current_route().active_block {
+    ActiveBlock::TrackTable => {
+      let selected_index = filter::get_visible_position(app, ActiveBlock::TrackTable);
+      let index = get_clicked_table_index(area, row, selected_index)
+        .and_then(|row| filter::get_filtered_index(app, ActiveBlock::TrackTable, row));
+      if let Some(index) = index {
+        if index < app.track_table.tracks.len() {
+          app.track_table.selected_index = index;
+          if is_double_click {
+            handle_block_events(Key::Enter, app);
+          }
+        }
+      }
+    }
+    ActiveBlock::Queue => {
+      if let Some(index) = get_clicked_table_index(area, row, app.queue.selected_index) {
+        if index < app.queue.rows().len() {
+          app.queue.selected_index = index;
+          if is_double_click {
+            handle_block_events(Key::Enter, app);
+          }
+        }
+      }
+    }
+    _ => {}
+  }
+}
+
+fn on_scroll(mouse: MouseEvent, app: &mut App, key: Key) {
+  let (column, row) = (mouse.column, mouse.row);
+  let layout = ui::get_main_layout(app, app.size);
+
+  if contains(layout.playbar, column, row) {
+    handle_playbar_event(mouse, app, layout.playbar);
+    return;
+  }
+
+  if contains(layout.library, column, row) {
+    app.set_current_route_state(Some(ActiveBlock::Library), Some(ActiveBlock::Library));
+  } else if contains(layout.playlists, column, row) {
+    app.set_current_route_state(
+      Some(ActiveBlock::MyPlaylists),
+      Some(ActiveBlock::MyPlaylists),
+    );
+  } else if contains(layout.routes, column, row) {
+    common_key_events::select_route_block(app);
+  } else {
+    return;
+  }
+  // Scrolling moves the selection the same way the arrow keys do
+  handle_block_events(key, app);
+}
+
+fn handle_playbar_event(mouse: MouseEvent, app: &mut App, playbar: Rect) {
+  match mouse.kind {
+    MouseEventKind::Down(MouseButton::Left) => {
+      let gauge = ui::get_playbar_chunks(app, playbar)[2];
+      if contains(gauge, mouse.column, mouse.row) {
+        seek_to_column(app, gauge, mouse.column);
+      }
+    }
+    MouseEventKind::ScrollUp => app.increase_volume(),
+    MouseEventKind::ScrollDown => app.decrease_volume(),
+    _ => {}
+  }
+}
+
+fn seek_to_column(app: &mut App, gauge: Rect, column: u16) {
+  if let Some(CurrentlyPlaybackContext {
+    item: Some(item), ..
+  }) = &app.current_playback_context
+  {
+    let duration_ms = match item {
+      PlayingItem::Track(track) => track.duration_ms,
+      PlayingItem::Episode(episode) => episode.duration_ms,
+    };
+    let ratio = f64::from(column - gauge.x) / f64::from(gauge.width);
+    app.seek_to((f64::from(duration_ms) * ratio) as u32);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn clicked_index_accounts_for_borders_and_scrolling() {
+    let area = Rect::new(0, 10, 20, 10);
+
+    assert_eq!(get_clicked_list_index(area, 10, 0), None);
+    assert_eq!(get_clicked_list_index(area, 11, 0), Some(0));
+    assert_eq!(get_clicked_list_index(area, 19, 0), None);
+    // The eight visible rows end at the selection
+    assert_eq!(get_clicked_list_index(area, 11, 9), Some(2));
+
+    assert_eq!(get_clicked_table_index(area, 11, 0), None);
+    assert_eq!(get_clicked_table_index(area, 12, 0), Some(0));
+  }
+}
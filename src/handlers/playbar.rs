@@ -8,10 +8,10 @@ use rspotify::model::{context::CurrentlyPlaybackContext, PlayingItem};
 
 pub fn handler(key: Key, app: &mut App) {
   match key {
-    k if common_key_events::up_event(k) => {
+    k if common_key_events::up_event(&k, &app.user_config.keys) => {
       app.set_current_route_state(Some(ActiveBlock::Empty), Some(ActiveBlock::MyPlaylists));
     }
-    Key::Char('s') => {
+    k if k == app.user_config.keys.save => {
       if let Some(CurrentlyPlaybackContext {
         item: Some(item), ..
       }) = app.current_playback_context.to_owned()
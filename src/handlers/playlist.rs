@@ -8,8 +8,10 @@ use crate::network::IoEvent;
 
 pub fn handler(key: Key, app: &mut App) {
   match key {
-    k if common_key_events::right_event(k) => common_key_events::handle_right_event(app),
-    k if common_key_events::down_event(k) => {
+    k if common_key_events::right_event(&k, &app.user_config.keys) => {
+      common_key_events::handle_right_event(app)
+    }
+    k if common_key_events::down_event(&k, &app.user_config.keys) => {
       match &app.playlists {
         Some(p) => {
           if let Some(selected_playlist_index) = app.selected_playlist_index {
@@ -21,7 +23,7 @@ pub fn handler(key: Key, app: &mut App) {
         None => {}
       };
     }
-    k if common_key_events::up_event(k) => {
+    k if common_key_events::up_event(&k, &app.user_config.keys) => {
       match &app.playlists {
         Some(p) => {
           let next_index =
@@ -31,7 +33,7 @@ pub fn handler(key: Key, app: &mut App) {
         None => {}
       };
     }
-    k if common_key_events::high_event(k) => {
+    k if common_key_events::high_event(&k, &app.user_config.keys) => {
       match &app.playlists {
         Some(_p) => {
           let next_index = common_key_events::on_high_press_handler();
@@ -40,7 +42,7 @@ pub fn handler(key: Key, app: &mut App) {
         None => {}
       };
     }
-    k if common_key_events::middle_event(k) => {
+    k if common_key_events::middle_event(&k, &app.user_config.keys) => {
       match &app.playlists {
         Some(p) => {
           let next_index = common_key_events::on_middle_press_handler(&p.items);
@@ -49,7 +51,7 @@ pub fn handler(key: Key, app: &mut App) {
         None => {}
       };
     }
-    k if common_key_events::low_event(k) => {
+    k if common_key_events::low_event(&k, &app.user_config.keys) => {
       match &app.playlists {
         Some(p) => {
           let next_index = common_key_events::on_low_press_handler(&p.items);
@@ -71,7 +73,7 @@ pub fn handler(key: Key, app: &mut App) {
         }
       };
     }
-    Key::Char('D') => {
+    k if k == app.user_config.keys.delete => {
       if let (Some(playlists), Some(selected_index)) = (&app.playlists, app.selected_playlist_index)
       {
         let selected_playlist = &playlists.items[selected_index].name;
@@ -84,6 +86,15 @@ pub fn handler(key: Key, app: &mut App) {
         );
       }
     }
+    k if k == app.user_config.keys.create_playlist => {
+      app.open_playlist_text_dialog(DialogContext::CreatePlaylist)
+    }
+    k if k == app.user_config.keys.rename_playlist => {
+      app.open_playlist_text_dialog(DialogContext::RenamePlaylist)
+    }
+    k if k == app.user_config.keys.describe_playlist => {
+      app.open_playlist_text_dialog(DialogContext::DescribePlaylist)
+    }
     _ => {}
   }
 }
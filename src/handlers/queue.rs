@@ -0,0 +1,112 @@
+use super::{super::app::App, common_key_events};
+use crate::event::Key;
+use crate::network::IoEvent;
+use rspotify::model::PlayingItem;
+
+pub fn handler(key: Key, app: &mut App) {
+  match key {
+    k if common_key_events::left_event(&k, &app.user_config.keys) => {
+      common_key_events::handle_left_event(app)
+    }
+    k if common_key_events::down_event(&k, &app.user_config.keys) => {
+      let next_index =
+        common_key_events::on_down_press_handler(&app.queue.rows(), Some(app.queue.selected_index));
+      app.queue.selected_index = next_index;
+    }
+    k if common_key_events::up_event(&k, &app.user_config.keys) => {
+      let next_index =
+        common_key_events::on_up_press_handler(&app.queue.rows(), Some(app.queue.selected_index));
+      app.queue.selected_index = next_index;
+    }
+    k if common_key_events::high_event(&k, &app.user_config.keys) => {
+      app.queue.selected_index = common_key_events::on_high_press_handler();
+    }
+    k if common_key_events::middle_event(&k, &app.user_config.keys) => {
+      let next_index = common_key_events::on_middle_press_handler(&app.queue.rows());
+      app.queue.selected_index = next_index;
+    }
+    k if common_key_events::low_event(&k, &app.user_config.keys) => {
+      let next_index = common_key_events::on_low_press_handler(&app.queue.rows());
+      app.queue.selected_index = next_index;
+    }
+    Key::Enter => {
+      if let Some(position) = app.queue.get_position_for_row(app.queue.selected_index) {
+        app.queue.selected_index = 0;
+        app.dispatch(IoEvent::JumpToQueueItem(position));
+      }
+    }
+    k if k == app.user_config.keys.save => {
+      if let Some(PlayingItem::Track(track)) = app.queue.rows().get(app.queue.selected_index) {
+        if let Some(id) = track.id.clone() {
+          app.dispatch(IoEvent::ToggleSaveTrack(id));
+        }
+      }
+    }
+    k if k == app.user_config.keys.delete => {
+      let selected_index = app.queue.selected_index;
+      app.queue.remove_row(selected_index);
+    }
+    k if k == app.user_config.keys.clear_queue => {
+      app.queue.clear_locally_added();
+    }
+    _ if key == app.user_config.keys.add_to_playlist => {
+      if let Some(PlayingItem::Track(track)) = app.queue.rows().get(app.queue.selected_index) {
+        let uri = track.uri.clone();
+        app.open_add_to_playlist_dialog(vec![uri]);
+      }
+    }
+    _ => {}
+  };
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use rspotify::model::show::FullEpisode;
+
+  fn episode(uri: &str) -> PlayingItem {
+    let mut episode: FullEpisode = serde_json::from_str(
+      r#"{
+        "audio_preview_url": null, "description": "", "duration_ms": 1000, "explicit": false,
+        "external_urls": {}, "href": "", "id": "id", "images": [], "is_externally_hosted": false,
+        "is_playable": true, "language": "en", "languages": [], "name": "episode", "release_date": "2020-01-01",
+        "release_date_precision": "day", "show": {
+          "available_markets": [], "copyrights": [], "description": "", "explicit": false,
+          "external_urls": {}, "href": "", "id": "show", "images": [],
+          "is_externally_hosted": null, "languages": [], "media_type": "audio", "name": "show",
+          "publisher": "publisher", "type": "show", "uri": "spotify:show:show"
+        },
+        "type": "episode", "uri": ""
+      }"#,
+    )
+    .unwrap();
+    episode.uri = uri.to_string();
+    PlayingItem::Episode(episode)
+  }
+
+  #[test]
+  fn removed_rows_are_hidden_and_skipped() {
+    let mut app = App::default();
+    app.queue.set_items(
+      Some(episode("current")),
+      vec![episode("a"), episode("b"), episode("a")],
+    );
+    app.queue.locally_added.push("b".to_string());
+
+    // Remove the first "a"
+    app.queue.selected_index = 1;
+    handler(Key::Char('D'), &mut app);
+    assert_eq!(app.queue.rows().len(), 3);
+    assert_eq!(app.queue.get_position_for_row(1), Some(1));
+    assert_eq!(app.queue.get_position_for_row(2), Some(2));
+
+    handler(Key::Char('x'), &mut app);
+    assert_eq!(app.queue.rows().len(), 2);
+    assert_eq!(app.queue.get_position_for_row(1), Some(2));
+
+    // Once a removed entry starts playing it gets skipped
+    assert!(app.queue.take_skipped_head(&episode("a")));
+    assert!(app.queue.take_skipped_head(&episode("b")));
+    assert!(!app.queue.take_skipped_head(&episode("a")));
+  }
+}
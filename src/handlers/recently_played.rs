@@ -3,8 +3,10 @@ use crate::{app::RecommendationsContext, event::Key, network::IoEvent};
 
 pub fn handler(key: Key, app: &mut App) {
   match key {
-    k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
-    k if common_key_events::down_event(k) => {
+    k if common_key_events::left_event(&k, &app.user_config.keys) => {
+      common_key_events::handle_left_event(app)
+    }
+    k if common_key_events::down_event(&k, &app.user_config.keys) => {
       if let Some(recently_played_result) = &app.recently_played.result {
         let next_index = common_key_events::on_down_press_handler(
           &recently_played_result.items,
@@ -13,7 +15,7 @@ pub fn handler(key: Key, app: &mut App) {
         app.recently_played.index = next_index;
       }
     }
-    k if common_key_events::up_event(k) => {
+    k if common_key_events::up_event(&k, &app.user_config.keys) => {
       if let Some(recently_played_result) = &app.recently_played.result {
         let next_index = common_key_events::on_up_press_handler(
           &recently_played_result.items,
@@ -22,25 +24,25 @@ pub fn handler(key: Key, app: &mut App) {
         app.recently_played.index = next_index;
       }
     }
-    k if common_key_events::high_event(k) => {
+    k if common_key_events::high_event(&k, &app.user_config.keys) => {
       if let Some(_recently_played_result) = &app.recently_played.result {
         let next_index = common_key_events::on_high_press_handler();
         app.recently_played.index = next_index;
       }
     }
-    k if common_key_events::middle_event(k) => {
+    k if common_key_events::middle_event(&k, &app.user_config.keys) => {
       if let Some(recently_played_result) = &app.recently_played.result {
         let next_index = common_key_events::on_middle_press_handler(&recently_played_result.items);
         app.recently_played.index = next_index;
       }
     }
-    k if common_key_events::low_event(k) => {
+    k if common_key_events::low_event(&k, &app.user_config.keys) => {
       if let Some(recently_played_result) = &app.recently_played.result {
         let next_index = common_key_events::on_low_press_handler(&recently_played_result.items);
         app.recently_played.index = next_index;
       }
     }
-    Key::Char('s') => {
+    k if k == app.user_config.keys.save => {
       if let Some(recently_played_result) = &app.recently_played.result.clone() {
         if let Some(selected_track) = recently_played_result.items.get(app.recently_played.index) {
           if let Some(track_id) = &selected_track.track.id {
@@ -64,7 +66,7 @@ pub fn handler(key: Key, app: &mut App) {
         ));
       };
     }
-    Key::Char('r') => {
+    k if k == app.user_config.keys.play_recommendations => {
       if let Some(recently_played_result) = &app.recently_played.result.clone() {
         let selected_track_history_item =
           recently_played_result.items.get(app.recently_played.index);
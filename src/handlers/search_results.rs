@@ -283,6 +283,20 @@ fn handle_add_item_to_queue(app: &mut App) {
   };
 }
 
+fn handle_add_to_playlist(app: &mut App) {
+  if let SearchResultBlock::SongSearch = app.search_results.selected_block {
+    if let (Some(index), Some(tracks)) = (
+      app.search_results.selected_tracks_index,
+      &app.search_results.tracks,
+    ) {
+      if let Some(track) = tracks.items.get(index) {
+        let uri = track.uri.clone();
+        app.open_add_to_playlist_dialog(vec![uri]);
+      }
+    }
+  }
+}
+
 fn handle_enter_event_on_selected_block(app: &mut App) {
   match &app.search_results.selected_block {
     SearchResultBlock::AlbumSearch => {
@@ -422,21 +436,21 @@ pub fn handler(key: Key, app: &mut App) {
     Key::Esc => {
       app.search_results.selected_block = SearchResultBlock::Empty;
     }
-    k if common_key_events::down_event(k) => {
+    k if common_key_events::down_event(&k, &app.user_config.keys) => {
       if app.search_results.selected_block != SearchResultBlock::Empty {
         handle_down_press_on_selected_block(app);
       } else {
         handle_down_press_on_hovered_block(app);
       }
     }
-    k if common_key_events::up_event(k) => {
+    k if common_key_events::up_event(&k, &app.user_config.keys) => {
       if app.search_results.selected_block != SearchResultBlock::Empty {
         handle_up_press_on_selected_block(app);
       } else {
         handle_up_press_on_hovered_block(app);
       }
     }
-    k if common_key_events::left_event(k) => {
+    k if common_key_events::left_event(&k, &app.user_config.keys) => {
       app.search_results.selected_block = SearchResultBlock::Empty;
       match app.search_results.hovered_block {
         SearchResultBlock::AlbumSearch => {
@@ -457,7 +471,7 @@ pub fn handler(key: Key, app: &mut App) {
         SearchResultBlock::Empty => {}
       }
     }
-    k if common_key_events::right_event(k) => {
+    k if common_key_events::right_event(&k, &app.user_config.keys) => {
       app.search_results.selected_block = SearchResultBlock::Empty;
       match app.search_results.hovered_block {
         SearchResultBlock::AlbumSearch => {
@@ -476,17 +490,17 @@ pub fn handler(key: Key, app: &mut App) {
         SearchResultBlock::Empty => {}
       }
     }
-    k if common_key_events::high_event(k) => {
+    k if common_key_events::high_event(&k, &app.user_config.keys) => {
       if app.search_results.selected_block != SearchResultBlock::Empty {
         handle_high_press_on_selected_block(app);
       }
     }
-    k if common_key_events::middle_event(k) => {
+    k if common_key_events::middle_event(&k, &app.user_config.keys) => {
       if app.search_results.selected_block != SearchResultBlock::Empty {
         handle_middle_press_on_selected_block(app);
       }
     }
-    k if common_key_events::low_event(k) => {
+    k if common_key_events::low_event(&k, &app.user_config.keys) => {
       if app.search_results.selected_block != SearchResultBlock::Empty {
         handle_low_press_on_selected_block(app)
       }
@@ -500,7 +514,7 @@ pub fn handler(key: Key, app: &mut App) {
       }
       _ => handle_enter_event_on_selected_block(app),
     },
-    Key::Char('w') => match app.search_results.selected_block {
+    k if k == app.user_config.keys.save_context => match app.search_results.selected_block {
       SearchResultBlock::AlbumSearch => {
         app.current_user_saved_album_add(ActiveBlock::SearchResultBlock)
       }
@@ -512,7 +526,7 @@ pub fn handler(key: Key, app: &mut App) {
       SearchResultBlock::ShowSearch => app.user_follow_show(ActiveBlock::SearchResultBlock),
       SearchResultBlock::Empty => {}
     },
-    Key::Char('D') => match app.search_results.selected_block {
+    k if k == app.user_config.keys.delete => match app.search_results.selected_block {
       SearchResultBlock::AlbumSearch => {
         app.current_user_saved_album_delete(ActiveBlock::SearchResultBlock)
       }
@@ -536,8 +550,9 @@ pub fn handler(key: Key, app: &mut App) {
       SearchResultBlock::ShowSearch => app.user_unfollow_show(ActiveBlock::SearchResultBlock),
       SearchResultBlock::Empty => {}
     },
-    Key::Char('r') => handle_recommended_tracks(app),
+    k if k == app.user_config.keys.play_recommendations => handle_recommended_tracks(app),
     _ if key == app.user_config.keys.add_item_to_queue => handle_add_item_to_queue(app),
+    _ if key == app.user_config.keys.add_to_playlist => handle_add_to_playlist(app),
     // Add `s` to "see more" on each option
     _ => {}
   }
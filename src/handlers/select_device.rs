@@ -4,13 +4,15 @@ use super::{
 };
 use crate::event::Key;
 use crate::network::IoEvent;
+use anyhow::anyhow;
+use std::cmp::min;
 
 pub fn handler(key: Key, app: &mut App) {
   match key {
     Key::Esc => {
       app.set_current_route_state(Some(ActiveBlock::Library), None);
     }
-    k if common_key_events::down_event(k) => {
+    k if common_key_events::down_event(&k, &app.user_config.keys) => {
       match &app.devices {
         Some(p) => {
           if let Some(selected_device_index) = app.selected_device_index {
@@ -22,7 +24,7 @@ pub fn handler(key: Key, app: &mut App) {
         None => {}
       };
     }
-    k if common_key_events::up_event(k) => {
+    k if common_key_events::up_event(&k, &app.user_config.keys) => {
       match &app.devices {
         Some(p) => {
           if let Some(selected_device_index) = app.selected_device_index {
@@ -34,7 +36,7 @@ pub fn handler(key: Key, app: &mut App) {
         None => {}
       };
     }
-    k if common_key_events::high_event(k) => {
+    k if common_key_events::high_event(&k, &app.user_config.keys) => {
       match &app.devices {
         Some(_p) => {
           if let Some(_selected_device_index) = app.selected_device_index {
@@ -45,7 +47,7 @@ pub fn handler(key: Key, app: &mut App) {
         None => {}
       };
     }
-    k if common_key_events::middle_event(k) => {
+    k if common_key_events::middle_event(&k, &app.user_config.keys) => {
       match &app.devices {
         Some(p) => {
           if let Some(_selected_device_index) = app.selected_device_index {
@@ -56,7 +58,7 @@ pub fn handler(key: Key, app: &mut App) {
         None => {}
       };
     }
-    k if common_key_events::low_event(k) => {
+    k if common_key_events::low_event(&k, &app.user_config.keys) => {
       match &app.devices {
         Some(p) => {
           if let Some(_selected_device_index) = app.selected_device_index {
@@ -69,11 +71,38 @@ pub fn handler(key: Key, app: &mut App) {
     }
     Key::Enter => {
       if let (Some(devices), Some(index)) = (app.devices.clone(), app.selected_device_index) {
-        if let Some(device) = &devices.devices.get(index) {
-          app.dispatch(IoEvent::TransferPlaybackToDevice(device.id.clone()));
+        match devices.devices.get(index) {
+          Some(device) if device.is_controllable() => {
+            app.dispatch(IoEvent::TransferPlaybackToDevice(device.id.clone()));
+          }
+          Some(device) => {
+            app.handle_error(anyhow!("{} cannot be controlled remotely", device.name));
+          }
+          None => {}
         }
       };
     }
     _ => {}
   }
 }
+
+pub fn change_volume(app: &mut App, increase: bool) {
+  let device = match (&app.devices, app.selected_device_index) {
+    (Some(devices), Some(index)) => devices.devices.get(index),
+    _ => None,
+  };
+  if let Some(device) = device.filter(|device| device.is_controllable()) {
+    if let Some(current_volume) = device.volume_percent {
+      let increment = u32::from(app.user_config.behavior.volume_increment);
+      let next_volume = if increase {
+        min(current_volume + increment, 100)
+      } else {
+        current_volume.saturating_sub(increment)
+      };
+      if next_volume != current_volume {
+        let event = IoEvent::ChangeDeviceVolume(device.id.clone(), next_volume as u8);
+        app.dispatch(event);
+      }
+    }
+  }
+}
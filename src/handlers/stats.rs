@@ -0,0 +1,14 @@
+use super::{super::app::App, common_key_events};
+use crate::event::Key;
+
+pub fn handler(key: Key, app: &mut App) {
+  match key {
+    k if common_key_events::left_event(&k, &app.user_config.keys) => {
+      common_key_events::handle_left_event(app)
+    }
+    k if k == app.user_config.keys.cycle_stats_period => {
+      app.cycle_stats_period();
+    }
+    _ => {}
+  };
+}
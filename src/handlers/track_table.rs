@@ -1,5 +1,7 @@
 use super::{
-  super::app::{App, RecommendationsContext, TrackTable, TrackTableContext},
+  super::app::{
+    ActiveBlock, App, DialogContext, RecommendationsContext, RouteId, TrackTable, TrackTableContext,
+  },
   common_key_events,
 };
 use crate::event::Key;
@@ -9,30 +11,32 @@ use serde_json::from_value;
 
 pub fn handler(key: Key, app: &mut App) {
   match key {
-    k if common_key_events::left_event(k) => common_key_events::handle_left_event(app),
-    k if common_key_events::down_event(k) => {
+    k if common_key_events::left_event(&k, &app.user_config.keys) => {
+      common_key_events::handle_left_event(app)
+    }
+    k if common_key_events::down_event(&k, &app.user_config.keys) => {
       let next_index = common_key_events::on_down_press_handler(
         &app.track_table.tracks,
         Some(app.track_table.selected_index),
       );
       app.track_table.selected_index = next_index;
     }
-    k if common_key_events::up_event(k) => {
+    k if common_key_events::up_event(&k, &app.user_config.keys) => {
       let next_index = common_key_events::on_up_press_handler(
         &app.track_table.tracks,
         Some(app.track_table.selected_index),
       );
       app.track_table.selected_index = next_index;
     }
-    k if common_key_events::high_event(k) => {
+    k if common_key_events::high_event(&k, &app.user_config.keys) => {
       let next_index = common_key_events::on_high_press_handler();
       app.track_table.selected_index = next_index;
     }
-    k if common_key_events::middle_event(k) => {
+    k if common_key_events::middle_event(&k, &app.user_config.keys) => {
       let next_index = common_key_events::on_middle_press_handler(&app.track_table.tracks);
       app.track_table.selected_index = next_index;
     }
-    k if common_key_events::low_event(k) => {
+    k if common_key_events::low_event(&k, &app.user_config.keys) => {
       let next_index = common_key_events::on_low_press_handler(&app.track_table.tracks);
       app.track_table.selected_index = next_index;
     }
@@ -43,6 +47,8 @@ pub fn handler(key: Key, app: &mut App) {
     k if k == app.user_config.keys.next_page => {
       match &app.track_table.context {
         Some(context) => match context {
+          // There are no pages when the whole playlist is loaded
+          TrackTableContext::MyPlaylists if app.has_full_playlist() => {}
           TrackTableContext::MyPlaylists => {
             if let (Some(playlists), Some(selected_playlist_index)) =
               (&app.playlists, &app.selected_playlist_index)
@@ -96,6 +102,7 @@ pub fn handler(key: Key, app: &mut App) {
     k if k == app.user_config.keys.previous_page => {
       match &app.track_table.context {
         Some(context) => match context {
+          TrackTableContext::MyPlaylists if app.has_full_playlist() => {}
           TrackTableContext::MyPlaylists => {
             if let (Some(playlists), Some(selected_playlist_index)) =
               (&app.playlists, &app.selected_playlist_index)
@@ -141,19 +148,45 @@ pub fn handler(key: Key, app: &mut App) {
         None => {}
       };
     }
-    Key::Char('s') => handle_save_track_event(app),
-    Key::Char('S') => play_random_song(app),
+    k if k == app.user_config.keys.save => handle_save_track_event(app),
+    k if k == app.user_config.keys.play_random_song => play_random_song(app),
     k if k == app.user_config.keys.jump_to_end => jump_to_end(app),
     k if k == app.user_config.keys.jump_to_start => jump_to_start(app),
     //recommended song radio
-    Key::Char('r') => {
+    k if k == app.user_config.keys.play_recommendations => {
       handle_recommended_tracks(app);
     }
     _ if key == app.user_config.keys.add_item_to_queue => on_queue(app),
+    _ if key == app.user_config.keys.add_to_playlist => on_add_to_playlist(app),
+    _ if key == app.user_config.keys.move_track_up => app.move_playlist_track(true),
+    _ if key == app.user_config.keys.move_track_down => app.move_playlist_track(false),
+    k if k == app.user_config.keys.delete => on_remove_from_playlist(app),
     _ => {}
   }
 }
 
+fn on_add_to_playlist(app: &mut App) {
+  if let Some(track) = app.track_table.tracks.get(app.track_table.selected_index) {
+    let uri = track.uri.clone();
+    app.open_add_to_playlist_dialog(vec![uri]);
+  }
+}
+
+fn on_remove_from_playlist(app: &mut App) {
+  if app.track_table.context != Some(TrackTableContext::MyPlaylists) {
+    return;
+  }
+  if let Some(track) = app.track_table.tracks.get(app.track_table.selected_index) {
+    app.dialog = Some(track.name.clone());
+    app.confirm = false;
+
+    app.push_navigation_stack(
+      RouteId::Dialog,
+      ActiveBlock::Dialog(DialogContext::RemoveFromPlaylist),
+    );
+  }
+}
+
 fn play_random_song(app: &mut App) {
   if let Some(context) = &app.track_table.context {
     match context {
@@ -175,11 +208,12 @@ fn play_random_song(app: &mut App) {
 
         if let Some(val) = track_json {
           let num_tracks: usize = from_value(val.clone()).unwrap();
-          app.dispatch(IoEvent::StartPlayback(
-            context_uri,
-            None,
-            Some(thread_rng().gen_range(0..num_tracks)),
-          ));
+          let offset = thread_rng().gen_range(0..num_tracks);
+          // Show where playback starts when the track is in the table
+          if app.has_full_playlist() && offset < app.track_table.tracks.len() {
+            app.track_table.selected_index = offset;
+          }
+          app.dispatch(IoEvent::StartPlayback(context_uri, None, Some(offset)));
         }
       }
       TrackTableContext::RecommendedTracks => {}
@@ -278,6 +312,9 @@ fn handle_recommended_tracks(app: &mut App) {
 fn jump_to_end(app: &mut App) {
   match &app.track_table.context {
     Some(context) => match context {
+      TrackTableContext::MyPlaylists if app.has_full_playlist() => {
+        app.track_table.selected_index = app.track_table.tracks.len().saturating_sub(1);
+      }
       TrackTableContext::MyPlaylists => {
         if let (Some(playlists), Some(selected_playlist_index)) =
           (&app.playlists, &app.selected_playlist_index)
@@ -313,6 +350,7 @@ fn on_enter(app: &mut App) {
     context,
     selected_index,
     tracks,
+    ..
   } = &app.track_table;
   match &context {
     Some(context) => match context {
@@ -419,6 +457,7 @@ fn on_queue(app: &mut App) {
     context,
     selected_index,
     tracks,
+    ..
   } = &app.track_table;
   match &context {
     Some(context) => match context {
@@ -468,6 +507,9 @@ fn on_queue(app: &mut App) {
 fn jump_to_start(app: &mut App) {
   match &app.track_table.context {
     Some(context) => match context {
+      TrackTableContext::MyPlaylists if app.has_full_playlist() => {
+        app.track_table.selected_index = 0;
+      }
       TrackTableContext::MyPlaylists => {
         if let (Some(playlists), Some(selected_playlist_index)) =
           (&app.playlists, &app.selected_playlist_index)
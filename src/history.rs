@@ -0,0 +1,469 @@
+use anyhow::Result;
+use chrono::{Duration, Local, NaiveDate, TimeZone};
+use rspotify::model::track::FullTrack;
+use serde::{Deserialize, Serialize};
+use std::{
+  collections::{BTreeSet, HashMap},
+  fs::{self, OpenOptions},
+  io::Write,
+  path::Path,
+};
+
+// Skipping a track before this much of it was heard doesn't count as playing it
+const MIN_PLAY_MS: u32 = 30 * 1000;
+// Progress counted between two polls beyond the time that passed was seeked over
+const PROGRESS_MARGIN_MS: u32 = 1000;
+// How close to the end a track has to be for going back to the start to be a replay
+const END_MARGIN_MS: u32 = 10 * 1000;
+
+/// A track that was listened to, a line of the history file
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Play {
+  pub uri: String,
+  pub name: String,
+  pub artists: Vec<String>,
+  pub album: String,
+  pub album_uri: Option<String>,
+  pub duration_ms: u32,
+  // When the track started playing, in seconds since the unix epoch
+  pub started_at: i64,
+  // How much of it was heard, seeking over parts doesn't count them
+  pub listened_ms: u32,
+}
+
+impl Play {
+  fn new(track: &FullTrack, started_at: i64) -> Self {
+    Play {
+      uri: track.uri.clone(),
+      name: track.name.clone(),
+      artists: track.artists.iter().map(|a| a.name.clone()).collect(),
+      album: track.album.name.clone(),
+      album_uri: track.album.uri.clone(),
+      duration_ms: track.duration_ms,
+      started_at,
+      listened_ms: 0,
+    }
+  }
+}
+
+/// Turns the playback seen on every poll into plays, which end when another track plays
+#[derive(Clone, Default)]
+pub struct PlayTracker {
+  play: Option<Play>,
+  progress_ms: u32,
+  is_playing: bool,
+  // In milliseconds since the unix epoch
+  updated_at: i64,
+}
+
+impl PlayTracker {
+  /// Returns the play that ended, if it was long enough to count
+  pub fn update(
+    &mut self,
+    track: Option<&FullTrack>,
+    is_playing: bool,
+    progress_ms: u32,
+    now: i64,
+  ) -> Option<Play> {
+    // Long gaps, like a suspended laptop, are cut to what fits in the counts
+    let elapsed_ms = (now - self.updated_at).clamp(0, i64::from(u32::MAX)) as u32;
+    let track = track.filter(|track| !track.is_local);
+
+    let ended = match (&mut self.play, track) {
+      (Some(play), Some(track)) if play.uri == track.uri => {
+        let heard_ms = elapsed_ms.saturating_add(PROGRESS_MARGIN_MS);
+        let was_at_end = self
+          .progress_ms
+          .saturating_add(elapsed_ms)
+          .saturating_add(END_MARGIN_MS)
+          >= play.duration_ms;
+        if progress_ms >= self.progress_ms {
+          if self.is_playing || is_playing {
+            play.listened_ms = play
+              .listened_ms
+              .saturating_add((progress_ms - self.progress_ms).min(heard_ms));
+          }
+          false
+        } else {
+          // Going back from the end is playing the track again, otherwise it is a seek
+          self.is_playing && was_at_end && progress_ms <= heard_ms
+        }
+      }
+      (None, None) => false,
+      _ => true,
+    };
+
+    let mut ended_play = None;
+    if ended {
+      if let Some(mut play) = self.play.take() {
+        // The rest of the track was heard if it played on to the next one
+        if self.is_playing {
+          let remaining_ms = play.duration_ms.saturating_sub(self.progress_ms);
+          play.listened_ms = play
+            .listened_ms
+            .saturating_add(remaining_ms.min(elapsed_ms));
+        }
+        ended_play = Some(play).filter(|play| play.listened_ms >= MIN_PLAY_MS);
+      }
+      self.play = track.map(|track| Play::new(track, (now - i64::from(progress_ms)) / 1000));
+    }
+    self.progress_ms = progress_ms;
+    self.is_playing = is_playing;
+    self.updated_at = now;
+    ended_play
+  }
+
+  /// Ends the play in progress, e.g. when the app exits, returning it if it counts
+  pub fn finish(&mut self, now: i64) -> Option<Play> {
+    self.update(None, false, 0, now)
+  }
+}
+
+/// Adds the play to the end of the history file
+pub fn append(path: &Path, play: &Play) -> Result<()> {
+  let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+  writeln!(file, "{}", serde_json::to_string(play)?)?;
+  Ok(())
+}
+
+/// Reads the history file, skipping lines that can't be read, e.g. one cut off by a crash
+pub fn load(path: &Path) -> Result<Vec<Play>> {
+  if !path.exists() {
+    return Ok(vec![]);
+  }
+  Ok(
+    fs::read_to_string(path)?
+      .lines()
+      .filter_map(|line| serde_json::from_str(line).ok())
+      .collect(),
+  )
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Period {
+  Week,
+  #[default]
+  Month,
+  Year,
+  AllTime,
+}
+
+impl Period {
+  pub fn parse(period: &str) -> Option<Self> {
+    match period {
+      "week" => Some(Period::Week),
+      "month" => Some(Period::Month),
+      "year" => Some(Period::Year),
+      "all" => Some(Period::AllTime),
+      _ => None,
+    }
+  }
+
+  pub fn next(self) -> Self {
+    match self {
+      Period::Week => Period::Month,
+      Period::Month => Period::Year,
+      Period::Year => Period::AllTime,
+      Period::AllTime => Period::Week,
+    }
+  }
+
+  pub fn label(self) -> &'static str {
+    match self {
+      Period::Week => "Last 7 days",
+      Period::Month => "Last 30 days",
+      Period::Year => "Last 365 days",
+      Period::AllTime => "All time",
+    }
+  }
+
+  fn days(self) -> Option<i64> {
+    match self {
+      Period::Week => Some(7),
+      Period::Month => Some(30),
+      Period::Year => Some(365),
+      Period::AllTime => None,
+    }
+  }
+}
+
+/// A track, artist or album with how often it was played
+#[derive(Clone, Debug, PartialEq)]
+pub struct Count {
+  pub name: String,
+  // The artists of a track or album, empty for an artist
+  pub artists: String,
+  pub uri: Option<String>,
+  pub plays: usize,
+  pub listened_ms: u64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stats {
+  pub period: Period,
+  pub plays: usize,
+  pub listened_ms: u64,
+  // Days in a row with plays, whatever the period
+  pub current_streak: u32,
+  pub longest_streak: u32,
+  pub top_tracks: Vec<Count>,
+  pub top_artists: Vec<Count>,
+  pub top_albums: Vec<Count>,
+}
+
+// Adds the play to the count of the key, which is made on its first play
+fn add_play(
+  counts: &mut HashMap<String, Count>,
+  key: &str,
+  play: &Play,
+  new_count: impl FnOnce() -> Count,
+) {
+  let count = counts.entry(key.to_string()).or_insert_with(new_count);
+  count.plays += 1;
+  count.listened_ms += u64::from(play.listened_ms);
+}
+
+// Most played first, ties go to the longest listened and then the name
+fn get_top(counts: HashMap<String, Count>) -> Vec<Count> {
+  let mut counts = counts.into_values().collect::<Vec<_>>();
+  counts.sort_by(|a, b| {
+    b.plays
+      .cmp(&a.plays)
+      .then(b.listened_ms.cmp(&a.listened_ms))
+      .then(a.name.cmp(&b.name))
+  });
+  counts
+}
+
+// The local day of the time in seconds since the unix epoch
+fn get_date(timestamp: i64) -> NaiveDate {
+  Local.timestamp_opt(timestamp, 0).earliest().map_or_else(
+    || NaiveDate::from_ymd_opt(1970, 1, 1).unwrap(),
+    |time| time.date_naive(),
+  )
+}
+
+// The current and the longest run of days in a row with plays
+fn get_streaks(plays: &[Play], today: NaiveDate) -> (u32, u32) {
+  let days = plays
+    .iter()
+    .map(|play| get_date(play.started_at))
+    .collect::<BTreeSet<_>>();
+  let mut longest = 0;
+  let mut streak = 0;
+  let mut previous_day: Option<NaiveDate> = None;
+  for day in &days {
+    streak = match previous_day {
+      Some(previous_day) if *day - previous_day == Duration::days(1) => streak + 1,
+      _ => 1,
+    };
+    longest = longest.max(streak);
+    previous_day = Some(*day);
+  }
+  // The streak goes on until a whole day passes without plays
+  let is_current = previous_day.is_some_and(|day| today - day <= Duration::days(1));
+  (if is_current { streak } else { 0 }, longest)
+}
+
+impl Stats {
+  /// Counts the plays of the period up to `now`, in seconds since the unix epoch
+  pub fn new(plays: &[Play], period: Period, now: i64) -> Self {
+    let since = period
+      .days()
+      .map_or(i64::MIN, |days| now - days * 24 * 60 * 60);
+    let mut tracks = HashMap::new();
+    let mut artists = HashMap::new();
+    let mut albums = HashMap::new();
+    let mut listened_ms = 0;
+    let mut count = 0;
+
+    for play in plays.iter().filter(|play| play.started_at >= since) {
+      count += 1;
+      listened_ms += u64::from(play.listened_ms);
+      let joined_artists = play.artists.join(", ");
+      add_play(&mut tracks, &play.uri, play, || Count {
+        name: play.name.clone(),
+        artists: joined_artists.clone(),
+        uri: Some(play.uri.clone()),
+        plays: 0,
+        listened_ms: 0,
+      });
+      for artist in &play.artists {
+        add_play(&mut artists, artist, play, || Count {
+          name: artist.clone(),
+          artists: String::new(),
+          uri: None,
+          plays: 0,
+          listened_ms: 0,
+        });
+      }
+      let album_key = play
+        .album_uri
+        .clone()
+        .unwrap_or_else(|| format!("{} - {}", play.album, joined_artists));
+      add_play(&mut albums, &album_key, play, || Count {
+        name: play.album.clone(),
+        artists: joined_artists.clone(),
+        uri: play.album_uri.clone(),
+        plays: 0,
+        listened_ms: 0,
+      });
+    }
+
+    let (current_streak, longest_streak) = get_streaks(plays, get_date(now));
+    Stats {
+      period,
+      plays: count,
+      listened_ms,
+      current_streak,
+      longest_streak,
+      top_tracks: get_top(tracks),
+      top_artists: get_top(artists),
+      top_albums: get_top(albums),
+    }
+  }
+
+  /// E.g. "Last 7 days: 42 plays, 2h 5m listened, 3 day streak (longest 9)"
+  pub fn summary(&self) -> String {
+    format!(
+      "{}: {} plays, {} listened, {} day streak (longest {})",
+      self.period.label(),
+      self.plays,
+      format_listening_time(self.listened_ms),
+      self.current_streak,
+      self.longest_streak
+    )
+  }
+}
+
+/// E.g. "2h 5m", or "5m" under an hour
+pub fn format_listening_time(ms: u64) -> String {
+  let minutes = ms / 1000 / 60;
+  if minutes < 60 {
+    format!("{}m", minutes)
+  } else {
+    format!("{}h {}m", minutes / 60, minutes % 60)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn get_track(uri: &str, duration_ms: u32) -> FullTrack {
+    serde_json::from_value(json!({
+      "album": {"artists": [], "available_markets": [], "external_urls": {}, "href": null,
+        "id": null, "images": [], "name": "Album", "type": "album", "uri": "spotify:album:1"},
+      "artists": [{"external_urls": {}, "href": null, "id": null, "name": "Artist",
+        "type": "artist", "uri": null}],
+      "available_markets": [], "disc_number": 1, "duration_ms": duration_ms,
+      "explicit": false, "external_ids": {}, "external_urls": {}, "href": null, "id": "1",
+      "is_local": false, "name": uri, "popularity": 0, "preview_url": null,
+      "track_number": 1, "type": "track", "uri": uri
+    }))
+    .unwrap()
+  }
+
+  #[test]
+  fn tracks_plays_from_polls() {
+    let first = get_track("spotify:track:1", 60_000);
+    let second = get_track("spotify:track:2", 60_000);
+    let mut tracker = PlayTracker::default();
+    assert_eq!(tracker.update(Some(&first), true, 0, 1_000_000), None);
+    assert_eq!(tracker.update(Some(&first), true, 5_000, 1_005_000), None);
+    // Seeking forward doesn't count what was skipped
+    assert_eq!(tracker.update(Some(&first), true, 40_000, 1_010_000), None);
+    assert_eq!(tracker.update(Some(&first), true, 55_000, 1_025_000), None);
+
+    // The end of the track was heard before the next one started
+    let play = tracker
+      .update(Some(&second), true, 2_000, 1_030_000)
+      .unwrap();
+    assert_eq!(play.uri, "spotify:track:1");
+    assert_eq!(play.started_at, 1000);
+    assert_eq!(play.listened_ms, 5_000 + 6_000 + 15_000 + 5_000);
+
+    // Skipped too early to count
+    assert_eq!(tracker.update(Some(&first), true, 0, 1_035_000), None);
+
+    // Repeating the track is another play
+    assert_eq!(tracker.update(Some(&first), true, 58_000, 1_093_000), None);
+    let play = tracker
+      .update(Some(&first), true, 3_000, 1_098_000)
+      .unwrap();
+    assert_eq!(play.listened_ms, 60_000);
+
+    // Pausing and stopping ends the play with what was heard
+    assert_eq!(tracker.update(Some(&first), false, 3_000, 1_100_000), None);
+    assert_eq!(tracker.update(None, false, 0, 1_200_000), None);
+
+    // Exiting during a play records what was heard of it
+    assert_eq!(tracker.update(Some(&second), true, 0, 1_300_000), None);
+    assert_eq!(tracker.update(Some(&second), true, 40_000, 1_340_000), None);
+    assert_eq!(tracker.finish(1_340_500).unwrap().listened_ms, 40_500);
+  }
+
+  #[test]
+  fn survives_long_gaps_between_polls() {
+    let track = get_track("spotify:track:1", u32::MAX);
+    let mut tracker = PlayTracker::default();
+    tracker.update(Some(&track), true, u32::MAX - 1, 0);
+    assert_eq!(tracker.update(Some(&track), true, 0, i64::MAX), None);
+  }
+
+  #[test]
+  fn counts_top_items_and_streaks() {
+    let day = 24 * 60 * 60;
+    let now = Local
+      .with_ymd_and_hms(2021, 6, 10, 12, 0, 0)
+      .unwrap()
+      .timestamp();
+    let play = |uri: &str, artist: &str, started_at: i64| Play {
+      uri: uri.to_string(),
+      name: uri.to_string(),
+      artists: vec![artist.to_string()],
+      album: "Album".to_string(),
+      album_uri: None,
+      duration_ms: 200_000,
+      started_at,
+      listened_ms: 100_000,
+    };
+    let plays = vec![
+      play("a", "X", now - 40 * day),
+      play("b", "Y", now - 3 * day),
+      play("a", "X", now - 2 * day),
+      play("a", "X", now - day),
+      play("b", "Y", now - day),
+      play("c", "Y", now),
+    ];
+
+    let stats = Stats::new(&plays, Period::Week, now);
+    assert_eq!(stats.plays, 5);
+    assert_eq!(stats.listened_ms, 500_000);
+    assert_eq!(stats.top_tracks[0].name, "a");
+    assert_eq!(stats.top_tracks[0].plays, 2);
+    assert_eq!(stats.top_artists[0].name, "Y");
+    assert_eq!(stats.top_artists[0].plays, 3);
+    assert_eq!(stats.top_albums.len(), 2);
+    assert_eq!((stats.current_streak, stats.longest_streak), (4, 4));
+    assert_eq!(Stats::new(&plays, Period::AllTime, now).plays, 6);
+
+    // A day without plays doesn't end the streak yet, two do
+    assert_eq!(
+      Stats::new(&plays, Period::Week, now + day).current_streak,
+      4
+    );
+    assert_eq!(
+      Stats::new(&plays, Period::Week, now + 2 * day).current_streak,
+      0
+    );
+  }
+
+  #[test]
+  fn formats_listening_time() {
+    assert_eq!(format_listening_time(59_000), "0m");
+    assert_eq!(format_listening_time(5 * 60 * 1000), "5m");
+    assert_eq!(format_listening_time(125 * 60 * 1000), "2h 5m");
+  }
+}
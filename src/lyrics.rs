@@ -0,0 +1,201 @@
+use anyhow::Result;
+use std::{fs, future::Future, path::PathBuf, pin::Pin};
+
+/// A line of lyrics, synced lines have the time at which they are sung
+#[derive(Clone, Debug, PartialEq)]
+pub struct LyricsLine {
+  pub time_ms: Option<u32>,
+  pub text: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lyrics {
+  pub lines: Vec<LyricsLine>,
+}
+
+impl Lyrics {
+  /// Parses LRC lyrics, text without any timestamps is taken as plain unsynced lyrics
+  pub fn parse(content: &str) -> Lyrics {
+    let mut offset_ms = 0i64;
+    let mut synced_lines = vec![];
+    for line in content.lines() {
+      let (times, text) = parse_timestamps(line.trim());
+      if times.is_empty() {
+        if let Some(offset) = parse_tag(line.trim(), "offset") {
+          offset_ms = offset.trim().parse().unwrap_or(0);
+        }
+      }
+      // A line may be repeated at several times
+      for time_ms in times {
+        synced_lines.push((time_ms, text.trim().to_string()));
+      }
+    }
+
+    if synced_lines.is_empty() {
+      return Lyrics {
+        lines: content
+          .lines()
+          .map(|line| LyricsLine {
+            time_ms: None,
+            text: line.trim_end().to_string(),
+          })
+          .collect(),
+      };
+    }
+
+    synced_lines.sort_by_key(|(time_ms, _)| *time_ms);
+    Lyrics {
+      lines: synced_lines
+        .into_iter()
+        .map(|(time_ms, text)| LyricsLine {
+          // A positive offset makes the lyrics appear sooner
+          time_ms: Some((i64::from(time_ms) - offset_ms).max(0) as u32),
+          text,
+        })
+        .collect(),
+    }
+  }
+
+  pub fn is_synced(&self) -> bool {
+    self.lines.iter().any(|line| line.time_ms.is_some())
+  }
+
+  /// The line that is being sung at `progress_ms`
+  pub fn get_current_line(&self, progress_ms: u128) -> Option<usize> {
+    self
+      .lines
+      .iter()
+      .rposition(|line| matches!(line.time_ms, Some(time_ms) if u128::from(time_ms) <= progress_ms))
+  }
+}
+
+// Splits the leading `[mm:ss.xx]` timestamps off a line
+fn parse_timestamps(mut line: &str) -> (Vec<u32>, &str) {
+  let mut times = vec![];
+  while let Some(rest) = line.strip_prefix('[') {
+    let end = match rest.find(']') {
+      Some(end) => end,
+      None => break,
+    };
+    match parse_time(&rest[..end]) {
+      Some(time_ms) => times.push(time_ms),
+      None => break,
+    }
+    line = &rest[end + 1..];
+  }
+  (times, line)
+}
+
+fn parse_time(time: &str) -> Option<u32> {
+  let (minutes, seconds) = time.split_once(':')?;
+  let minutes: u32 = minutes.parse().ok()?;
+  let seconds: f64 = seconds.parse().ok()?;
+  Some(minutes * 60_000 + (seconds * 1000.0).round() as u32)
+}
+
+// The value of an `[id:value]` tag
+fn parse_tag<'a>(line: &'a str, id: &str) -> Option<&'a str> {
+  let tag = line.strip_prefix('[')?.strip_suffix(']')?;
+  let (tag_id, value) = tag.split_once(':')?;
+  if tag_id.trim().eq_ignore_ascii_case(id) {
+    Some(value)
+  } else {
+    None
+  }
+}
+
+/// What the lyrics are looked up by
+#[derive(Clone, Debug)]
+pub struct LyricsQuery {
+  pub title: String,
+  pub artists: Vec<String>,
+}
+
+pub type LyricsFuture<'a> = Pin<Box<dyn Future<Output = Result<Option<Lyrics>>> + Send + 'a>>;
+
+/// A source of lyrics, providers are asked in turn until one of them has lyrics for the track
+pub trait LyricsProvider: Send + Sync {
+  fn get_lyrics<'a>(&'a self, query: &'a LyricsQuery) -> LyricsFuture<'a>;
+}
+
+/// Reads `<artist> - <title>.lrc` or `<title>.lrc` (or `.txt` for plain lyrics) from a directory
+pub struct LocalLrcProvider {
+  pub dir: PathBuf,
+}
+
+impl LocalLrcProvider {
+  fn get_candidates(&self, query: &LyricsQuery) -> Vec<PathBuf> {
+    // Path separators can't be part of a file name
+    let clean = |name: &str| name.replace('/', "_");
+    let title = clean(&query.title);
+    let mut names = vec![];
+    if let Some(artist) = query.artists.first() {
+      names.push(format!("{} - {}", clean(artist), title));
+    }
+    names.push(title);
+
+    names
+      .iter()
+      .flat_map(|name| {
+        vec![
+          self.dir.join(format!("{}.lrc", name)),
+          self.dir.join(format!("{}.txt", name)),
+        ]
+      })
+      .collect()
+  }
+}
+
+impl LyricsProvider for LocalLrcProvider {
+  fn get_lyrics<'a>(&'a self, query: &'a LyricsQuery) -> LyricsFuture<'a> {
+    Box::pin(async move {
+      for path in self.get_candidates(query) {
+        if path.is_file() {
+          return Ok(Some(Lyrics::parse(&fs::read_to_string(path)?)));
+        }
+      }
+      Ok(None)
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parses_synced_lyrics() {
+    let lyrics = Lyrics::parse(
+      "[ar:Artist]\n[offset:500]\n[00:12.00]First line\n[00:05.50][00:20.00]Chorus\n",
+    );
+    assert!(lyrics.is_synced());
+    assert_eq!(
+      lyrics.lines,
+      vec![
+        LyricsLine {
+          time_ms: Some(5000),
+          text: "Chorus".to_string()
+        },
+        LyricsLine {
+          time_ms: Some(11500),
+          text: "First line".to_string()
+        },
+        LyricsLine {
+          time_ms: Some(19500),
+          text: "Chorus".to_string()
+        },
+      ]
+    );
+    assert_eq!(lyrics.get_current_line(4000), None);
+    assert_eq!(lyrics.get_current_line(12000), Some(1));
+    assert_eq!(lyrics.get_current_line(60000), Some(2));
+  }
+
+  #[test]
+  fn text_without_timestamps_is_unsynced() {
+    let lyrics = Lyrics::parse("First line\n\nSecond line");
+    assert!(!lyrics.is_synced());
+    assert_eq!(lyrics.lines.len(), 3);
+    assert_eq!(lyrics.get_current_line(1000), None);
+  }
+}
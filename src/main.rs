@@ -1,11 +1,27 @@
+mod album_art;
 mod app;
 mod banner;
+mod cache;
 mod cli;
+mod command_palette;
 mod config;
+mod config_watcher;
+mod device;
 mod event;
+mod export;
+mod filter;
 mod handlers;
+mod history;
+mod lyrics;
+#[cfg(feature = "mpris")]
+mod mpris;
 mod network;
+mod notifications;
 mod redirect_uri;
+mod retry;
+mod scrobbler;
+mod sleep_timer;
+mod sort;
 mod ui;
 mod user_config;
 
@@ -14,9 +30,10 @@ use crate::event::Key;
 use anyhow::{anyhow, Result};
 use app::{ActiveBlock, App};
 use backtrace::Backtrace;
-use banner::BANNER;
-use clap::{App as ClapApp, Arg, Shell};
+use cache::Cache;
+use clap::Shell;
 use config::ClientConfig;
+use config_watcher::ConfigWatcher;
 use crossterm::{
   cursor::MoveTo,
   event::{DisableMouseCapture, EnableMouseCapture},
@@ -33,18 +50,19 @@ use rspotify::{
   oauth2::{SpotifyOAuth, TokenInfo},
   util::{process_token, request_token},
 };
+use sort::TableSorts;
 use std::{
   cmp::{max, min},
   io::{self, stdout},
   panic::{self, PanicInfo},
-  path::PathBuf,
+  path::{Path, PathBuf},
   sync::Arc,
   time::SystemTime,
 };
 use tokio::sync::Mutex;
 use tui::{
   backend::{Backend, CrosstermBackend},
-  Terminal,
+  Frame, Terminal,
 };
 use user_config::{UserConfig, UserConfigPaths};
 
@@ -126,47 +144,7 @@ async fn main() -> Result<()> {
     panic_hook(info);
   }));
 
-  let mut clap_app = ClapApp::new(env!("CARGO_PKG_NAME"))
-    .version(env!("CARGO_PKG_VERSION"))
-    .author(env!("CARGO_PKG_AUTHORS"))
-    .about(env!("CARGO_PKG_DESCRIPTION"))
-    .usage("Press `?` while running the app to see keybindings")
-    .before_help(BANNER)
-    .after_help(
-      "Your spotify Client ID and Client Secret are stored in $HOME/.config/spotify-tui/client.yml",
-    )
-    .arg(
-      Arg::with_name("tick-rate")
-        .short("t")
-        .long("tick-rate")
-        .help("Set the tick rate (milliseconds): the lower the number the higher the FPS.")
-        .long_help(
-          "Specify the tick rate in milliseconds: the lower the number the \
-higher the FPS. It can be nicer to have a lower value when you want to use the audio analysis view \
-of the app. Beware that this comes at a CPU cost!",
-        )
-        .takes_value(true),
-    )
-    .arg(
-      Arg::with_name("config")
-        .short("c")
-        .long("config")
-        .help("Specify configuration file path.")
-        .takes_value(true),
-    )
-    .arg(
-      Arg::with_name("completions")
-        .long("completions")
-        .help("Generates completions for your preferred shell")
-        .takes_value(true)
-        .possible_values(&["bash", "zsh", "fish", "power-shell", "elvish"])
-        .value_name("SHELL"),
-    )
-    // Control spotify from the command line
-    .subcommand(cli::playback_subcommand())
-    .subcommand(cli::play_subcommand())
-    .subcommand(cli::list_subcommand())
-    .subcommand(cli::search_subcommand());
+  let mut clap_app = cli::clap_app();
 
   let matches = clap_app.clone().get_matches();
 
@@ -184,6 +162,17 @@ of the app. Beware that this comes at a CPU cost!",
     return Ok(());
   }
 
+  // A running instance already has a session, so let it handle the playback commands
+  #[cfg(unix)]
+  {
+    if let Some("playback") | Some("play") = matches.subcommand_name() {
+      if let Some(output) = cli::forward_to_running_instance(std::env::args().collect()).await {
+        println!("{}", output?);
+        return Ok(());
+      }
+    }
+  }
+
   let mut user_config = UserConfig::new();
   if let Some(config_file_path) = matches.value_of("config") {
     let config_file_path = PathBuf::from(config_file_path);
@@ -192,10 +181,10 @@ of the app. Beware that this comes at a CPU cost!",
   }
   user_config.load_config()?;
 
-  if let Some(tick_rate) = matches
+  let tick_rate = matches
     .value_of("tick-rate")
-    .and_then(|tick_rate| tick_rate.parse().ok())
-  {
+    .and_then(|tick_rate| tick_rate.parse().ok());
+  if let Some(tick_rate) = tick_rate {
     if tick_rate >= 1000 {
       panic!("Tick rate must be below 1000");
     } else {
@@ -242,13 +231,38 @@ of the app. Beware that this comes at a CPU cost!",
         );
       // Launch the UI (async)
       } else {
+        #[cfg(feature = "mpris")]
+        mpris::start(Arc::clone(&app));
+
+        #[cfg(unix)]
+        {
+          let socket_app = Arc::clone(&app);
+          let user_config = user_config.clone();
+          std::thread::spawn(move || {
+            cli::start_socket_server(socket_app, user_config);
+          });
+        }
+
+        // Show the library from the last run until the network thread has refreshed it
+        let cache = Cache::load(config_paths.library_cache_path);
+        cache.apply(&mut *app.lock().await);
+        app.lock().await.table_sorts = TableSorts::load(config_paths.table_sort_path);
+
         let cloned_app = Arc::clone(&app);
         std::thread::spawn(move || {
           let mut network = Network::new(oauth, spotify, client_config, &app);
+          network.cache = cache;
+          network.degraded_mode = true;
           start_tokio(sync_io_rx, &mut network);
         });
         // The UI must run in the "main" thread
-        start_ui(user_config, &cloned_app).await?;
+        start_ui(
+          user_config,
+          tick_rate,
+          &cloned_app,
+          &config_paths.history_path,
+        )
+        .await?;
       }
     }
     None => println!("\nSpotify auth failed"),
@@ -264,7 +278,54 @@ async fn start_tokio<'a>(io_rx: std::sync::mpsc::Receiver<IoEvent>, network: &mu
   }
 }
 
-async fn start_ui(user_config: UserConfig, app: &Arc<Mutex<App>>) -> Result<()> {
+// Applies the changed config file, keeping the current config while the file is invalid
+fn reload_user_config(app: &mut App, events: &event::Events, tick_rate: Option<u64>) {
+  match app.user_config.reload() {
+    Ok(mut user_config) => {
+      // The tick rate given on the command line still wins over the config file
+      if let Some(tick_rate) = tick_rate {
+        user_config.behavior.tick_rate_milliseconds = tick_rate;
+      }
+      events.set_tick_rate(user_config.behavior.tick_rate_milliseconds);
+      app.help_docs_size = ui::help::get_help_docs(&user_config.keys).len() as u32;
+      app.user_config = user_config;
+    }
+    Err(e) => app.handle_error(anyhow!("Failed to reload the config: {}", e)),
+  }
+}
+
+fn draw<B>(f: &mut Frame<B>, app: &App, active_block: ActiveBlock)
+where
+  B: Backend,
+{
+  match active_block {
+    ActiveBlock::HelpMenu => {
+      ui::draw_help_menu(f, app);
+    }
+    ActiveBlock::Error => {
+      ui::draw_error_screen(f, app);
+    }
+    ActiveBlock::SelectDevice => {
+      ui::draw_device_list(f, app);
+    }
+    ActiveBlock::Analysis => {
+      ui::audio_analysis::draw(f, app);
+    }
+    ActiveBlock::BasicView => {
+      ui::draw_basic_view(f, app);
+    }
+    _ => {
+      ui::draw_main_layout(f, app);
+    }
+  }
+}
+
+async fn start_ui(
+  user_config: UserConfig,
+  tick_rate: Option<u64>,
+  app: &Arc<Mutex<App>>,
+  history_path: &Path,
+) -> Result<()> {
   // Terminal initialization
   let mut stdout = stdout();
   execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
@@ -280,6 +341,7 @@ async fn start_ui(user_config: UserConfig, app: &Arc<Mutex<App>>) -> Result<()>
   terminal.hide_cursor()?;
 
   let events = event::Events::new(user_config.behavior.tick_rate_milliseconds);
+  let mut config_watcher = ConfigWatcher::new(&user_config);
 
   // play music on, if not send them to the device selection view
 
@@ -318,28 +380,17 @@ async fn start_ui(user_config: UserConfig, app: &Arc<Mutex<App>>) -> Result<()>
       }
     };
 
-    let current_route = app.get_current_route();
-    terminal.draw(|mut f| match current_route.active_block {
-      ActiveBlock::HelpMenu => {
-        ui::draw_help_menu(&mut f, &app);
-      }
-      ActiveBlock::Error => {
-        ui::draw_error_screen(&mut f, &app);
-      }
-      ActiveBlock::SelectDevice => {
-        ui::draw_device_list(&mut f, &app);
-      }
-      ActiveBlock::Analysis => {
-        ui::audio_analysis::draw(&mut f, &app);
+    album_art::request_images(&mut app);
+    let active_block = app.get_current_route().active_block;
+    // Drawn again when the screen was cleared to remove album art
+    loop {
+      terminal.draw(|f| draw(f, &app, active_block))?;
+      if !album_art::write_graphics(&mut terminal, &mut app)? {
+        break;
       }
-      ActiveBlock::BasicView => {
-        ui::draw_basic_view(&mut f, &app);
-      }
-      _ => {
-        ui::draw_main_layout(&mut f, &app);
-      }
-    })?;
+    }
 
+    let current_route = app.get_current_route();
     if current_route.active_block == ActiveBlock::Input {
       terminal.show_cursor()?;
     } else {
@@ -368,6 +419,8 @@ async fn start_ui(user_config: UserConfig, app: &Arc<Mutex<App>>) -> Result<()>
         if key == Key::Ctrl('c') {
           break;
         }
+        // Messages like the one of a finished export stay until the next key press
+        app.status_message = None;
 
         let current_active_block = app.get_current_route().active_block;
 
@@ -375,25 +428,43 @@ async fn start_ui(user_config: UserConfig, app: &Arc<Mutex<App>>) -> Result<()>
         // case for the input handler
         if current_active_block == ActiveBlock::Input {
           handlers::input_handler(key, &mut app);
-        } else if key == app.user_config.keys.back {
-          if app.get_current_route().active_block != ActiveBlock::Input {
-            // Go back through navigation stack when not in search input mode and exit the app if there are no more places to back to
-
-            let pop_result = match app.pop_navigation_stack() {
-              Some(ref x) if x.id == RouteId::Search => app.pop_navigation_stack(),
-              Some(x) => Some(x),
-              None => None,
-            };
-            if pop_result.is_none() {
-              break; // Exit application
+        } else if matches!(current_active_block, ActiveBlock::Dialog(context) if context.is_text_input())
+        {
+          handlers::dialog_handler(key, &mut app);
+        } else if current_active_block == ActiveBlock::CommandPalette {
+          handlers::command_palette_handler(key, &mut app);
+        } else if filter::is_editing(&app) {
+          handlers::filter_handler(key, &mut app);
+        } else if let Some((key, count)) = handlers::chord_handler(key, &mut app) {
+          if key == app.user_config.keys.back {
+            if app.get_current_route().active_block != ActiveBlock::Input {
+              // Go back through navigation stack when not in search input mode and exit the app if there are no more places to back to
+
+              let pop_result = match app.pop_navigation_stack() {
+                Some(ref x) if x.id == RouteId::Search => app.pop_navigation_stack(),
+                Some(x) => Some(x),
+                None => None,
+              };
+              if pop_result.is_none() {
+                break; // Exit application
+              }
+            }
+          } else {
+            // A count repeats the action, e.g. `5j` moves down five rows
+            for _ in 0..count {
+              handlers::handle_app(key.clone(), &mut app);
             }
           }
-        } else {
-          handlers::handle_app(key, &mut app);
         }
       }
+      event::Event::Mouse(mouse) => {
+        handlers::mouse_handler(mouse, &mut app);
+      }
       event::Event::Tick => {
         app.update_on_tick();
+        if config_watcher.has_changed(&app.user_config) {
+          reload_user_config(&mut app, &events, tick_rate);
+        }
       }
     }
 
@@ -403,6 +474,9 @@ async fn start_ui(user_config: UserConfig, app: &Arc<Mutex<App>>) -> Result<()>
       app.dispatch(IoEvent::GetPlaylists);
       app.dispatch(IoEvent::GetUser);
       app.dispatch(IoEvent::GetCurrentPlayback);
+      if !app.user_config.devices.preferred.is_empty() {
+        app.dispatch(IoEvent::AutoSelectDevice(false));
+      }
       app.help_docs_size = ui::help::get_help_docs(&app.user_config.keys).len() as u32;
 
       is_first_render = false;
@@ -412,5 +486,10 @@ async fn start_ui(user_config: UserConfig, app: &Arc<Mutex<App>>) -> Result<()>
   terminal.show_cursor()?;
   close_application()?;
 
+  // The network thread is not waited for, so the last play is written here
+  if let Some(play) = app.lock().await.finish_play() {
+    history::append(history_path, &play)?;
+  }
+
   Ok(())
 }
@@ -0,0 +1,507 @@
+use crate::app::App;
+use crate::network::IoEvent;
+use anyhow::anyhow;
+use rspotify::{
+  model::{context::CurrentlyPlaybackContext, PlayingItem},
+  senum::RepeatState,
+};
+use std::{collections::HashMap, convert::TryFrom, sync::Arc, thread, time::Duration};
+use tokio::sync::Mutex;
+use zbus::{
+  block_on, blocking::ConnectionBuilder, dbus_interface, fdo, zvariant::ObjectPath,
+  zvariant::OwnedValue, zvariant::Value,
+};
+
+const BUS_NAME: &str = "org.mpris.MediaPlayer2.spt";
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+const NO_TRACK: &str = "/org/mpris/MediaPlayer2/TrackList/NoTrack";
+
+// How often the playback context is checked for changes to signal
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Serves the MPRIS interfaces on the session bus until the app exits
+pub fn start(app: Arc<Mutex<App>>) {
+  thread::spawn(move || {
+    if let Err(e) = serve(&app) {
+      block_on(app.lock()).handle_error(anyhow!("Could not start the MPRIS server: {}", e));
+    }
+  });
+}
+
+fn serve(app: &Arc<Mutex<App>>) -> zbus::Result<()> {
+  let connection = ConnectionBuilder::session()?
+    .serve_at(OBJECT_PATH, MediaPlayer2)?
+    .serve_at(
+      OBJECT_PATH,
+      Player {
+        app: Arc::clone(app),
+      },
+    )?
+    .build()?;
+
+  // Another instance owns the name already, so fall back to a unique one
+  if connection.request_name(BUS_NAME).is_err() {
+    connection.request_name(format!("{}.instance{}", BUS_NAME, std::process::id()))?;
+  }
+
+  let player = connection
+    .object_server()
+    .interface::<_, Player>(OBJECT_PATH)?;
+  let mut last_state = None;
+  loop {
+    let state = PlayerState::new(&block_on(app.lock()).current_playback_context);
+    if let Some(last_state) = &last_state {
+      if *last_state != state {
+        notify_changes(last_state, &state, &player)?;
+      }
+    }
+    last_state = Some(state);
+    thread::sleep(POLL_INTERVAL);
+  }
+}
+
+fn notify_changes(
+  old: &PlayerState,
+  new: &PlayerState,
+  player: &zbus::blocking::InterfaceRef<Player>,
+) -> zbus::Result<()> {
+  let ctxt = player.signal_context();
+  let player = player.get();
+  if old.playback_status != new.playback_status {
+    block_on(player.playback_status_changed(ctxt))?;
+  }
+  if old.loop_status != new.loop_status {
+    block_on(player.loop_status_changed(ctxt))?;
+  }
+  if old.shuffle != new.shuffle {
+    block_on(player.shuffle_changed(ctxt))?;
+  }
+  if old.volume_percent != new.volume_percent {
+    block_on(player.volume_changed(ctxt))?;
+  }
+  if old.item_uri != new.item_uri {
+    block_on(player.metadata_changed(ctxt))?;
+    block_on(player.can_seek_changed(ctxt))?;
+  }
+  Ok(())
+}
+
+// The parts of the playback context that clients are told about when they change
+#[derive(PartialEq)]
+struct PlayerState {
+  playback_status: &'static str,
+  loop_status: &'static str,
+  shuffle: bool,
+  volume_percent: u32,
+  item_uri: Option<String>,
+}
+
+impl PlayerState {
+  fn new(context: &Option<CurrentlyPlaybackContext>) -> Self {
+    PlayerState {
+      playback_status: get_playback_status(context),
+      loop_status: context
+        .as_ref()
+        .map(|context| get_loop_status(context.repeat_state))
+        .unwrap_or("None"),
+      shuffle: context
+        .as_ref()
+        .map(|context| context.shuffle_state)
+        .unwrap_or(false),
+      volume_percent: context
+        .as_ref()
+        .map(|context| context.device.volume_percent)
+        .unwrap_or(0),
+      item_uri: context
+        .as_ref()
+        .and_then(|context| context.item.as_ref())
+        .map(|item| get_item_uri(item).to_string()),
+    }
+  }
+}
+
+fn get_item_uri(item: &PlayingItem) -> &str {
+  match item {
+    PlayingItem::Track(track) => &track.uri,
+    PlayingItem::Episode(episode) => &episode.uri,
+  }
+}
+
+fn get_duration_ms(item: &PlayingItem) -> u32 {
+  match item {
+    PlayingItem::Track(track) => track.duration_ms,
+    PlayingItem::Episode(episode) => episode.duration_ms,
+  }
+}
+
+fn get_playback_status(context: &Option<CurrentlyPlaybackContext>) -> &'static str {
+  match context {
+    Some(CurrentlyPlaybackContext {
+      is_playing: true, ..
+    }) => "Playing",
+    Some(CurrentlyPlaybackContext { item: Some(_), .. }) => "Paused",
+    _ => "Stopped",
+  }
+}
+
+fn get_loop_status(repeat_state: RepeatState) -> &'static str {
+  match repeat_state {
+    RepeatState::Off => "None",
+    RepeatState::Track => "Track",
+    RepeatState::Context => "Playlist",
+  }
+}
+
+// `IoEvent::Repeat` moves on to the state after the one it is given, so this returns the state
+// before the requested loop status
+fn get_repeat_state_before(loop_status: &str) -> Option<RepeatState> {
+  match loop_status {
+    "None" => Some(RepeatState::Track),
+    "Playlist" => Some(RepeatState::Off),
+    "Track" => Some(RepeatState::Context),
+    _ => None,
+  }
+}
+
+fn get_track_id(item: Option<&PlayingItem>) -> ObjectPath<'static> {
+  let id = match item {
+    Some(PlayingItem::Track(track)) => track.id.clone(),
+    Some(PlayingItem::Episode(episode)) => Some(episode.id.clone()),
+    None => None,
+  };
+  id.and_then(|id| ObjectPath::try_from(format!("{}/Track/{}", OBJECT_PATH, id)).ok())
+    .unwrap_or_else(|| ObjectPath::from_static_str_unchecked(NO_TRACK))
+}
+
+fn get_metadata(item: Option<&PlayingItem>) -> HashMap<String, OwnedValue> {
+  let mut metadata: HashMap<String, Value> = HashMap::new();
+  metadata.insert("mpris:trackid".to_string(), get_track_id(item).into());
+
+  match item {
+    Some(PlayingItem::Track(track)) => {
+      let artists = track
+        .artists
+        .iter()
+        .map(|artist| artist.name.clone())
+        .collect::<Vec<String>>();
+      metadata.insert("xesam:title".to_string(), track.name.clone().into());
+      metadata.insert("xesam:album".to_string(), track.album.name.clone().into());
+      metadata.insert("xesam:artist".to_string(), artists.into());
+      metadata.insert(
+        "xesam:trackNumber".to_string(),
+        (track.track_number as i32).into(),
+      );
+      metadata.insert("xesam:discNumber".to_string(), track.disc_number.into());
+      if let Some(image) = track.album.images.first() {
+        metadata.insert("mpris:artUrl".to_string(), image.url.clone().into());
+      }
+      if let Some(url) = track.external_urls.get("spotify") {
+        metadata.insert("xesam:url".to_string(), url.clone().into());
+      }
+    }
+    Some(PlayingItem::Episode(episode)) => {
+      metadata.insert("xesam:title".to_string(), episode.name.clone().into());
+      metadata.insert("xesam:album".to_string(), episode.show.name.clone().into());
+      metadata.insert(
+        "xesam:artist".to_string(),
+        vec![episode.show.publisher.clone()].into(),
+      );
+      if let Some(image) = episode.images.first() {
+        metadata.insert("mpris:artUrl".to_string(), image.url.clone().into());
+      }
+      if let Some(url) = episode.external_urls.get("spotify") {
+        metadata.insert("xesam:url".to_string(), url.clone().into());
+      }
+    }
+    None => {}
+  }
+
+  if let Some(item) = item {
+    metadata.insert(
+      "mpris:length".to_string(),
+      (i64::from(get_duration_ms(item)) * 1000).into(),
+    );
+  }
+
+  metadata
+    .into_iter()
+    .map(|(key, value)| (key, value.into()))
+    .collect()
+}
+
+struct MediaPlayer2;
+
+#[dbus_interface(name = "org.mpris.MediaPlayer2")]
+impl MediaPlayer2 {
+  fn raise(&self) {}
+
+  fn quit(&self) {}
+
+  #[dbus_interface(property)]
+  fn can_quit(&self) -> bool {
+    false
+  }
+
+  #[dbus_interface(property)]
+  fn can_raise(&self) -> bool {
+    false
+  }
+
+  #[dbus_interface(property)]
+  fn has_track_list(&self) -> bool {
+    false
+  }
+
+  #[dbus_interface(property)]
+  fn identity(&self) -> &str {
+    "spotify-tui"
+  }
+
+  #[dbus_interface(property)]
+  fn supported_uri_schemes(&self) -> Vec<String> {
+    vec!["spotify".to_string()]
+  }
+
+  #[dbus_interface(property)]
+  fn supported_mime_types(&self) -> Vec<String> {
+    vec![]
+  }
+}
+
+struct Player {
+  app: Arc<Mutex<App>>,
+}
+
+#[dbus_interface(name = "org.mpris.MediaPlayer2.Player")]
+impl Player {
+  async fn next(&self) {
+    self.app.lock().await.dispatch(IoEvent::NextTrack);
+  }
+
+  async fn previous(&self) {
+    self.app.lock().await.previous_track();
+  }
+
+  async fn pause(&self) {
+    let mut app = self.app.lock().await;
+    if get_playback_status(&app.current_playback_context) == "Playing" {
+      app.dispatch(IoEvent::PausePlayback);
+    }
+  }
+
+  async fn play_pause(&self) {
+    self.app.lock().await.toggle_playback();
+  }
+
+  async fn stop(&self) {
+    self.pause().await;
+  }
+
+  async fn play(&self) {
+    let mut app = self.app.lock().await;
+    if get_playback_status(&app.current_playback_context) != "Playing" {
+      app.dispatch(IoEvent::StartPlayback(None, None, None));
+    }
+  }
+
+  async fn seek(&self, offset: i64) {
+    let mut app = self.app.lock().await;
+    let duration_ms = match app
+      .current_playback_context
+      .as_ref()
+      .and_then(|context| context.item.as_ref())
+    {
+      Some(item) => get_duration_ms(item),
+      None => return,
+    };
+    let position_ms = app.song_progress_ms as i64 + offset / 1000;
+    if position_ms >= i64::from(duration_ms) {
+      app.dispatch(IoEvent::NextTrack);
+    } else {
+      app.dispatch(IoEvent::Seek(position_ms.max(0) as u32));
+    }
+  }
+
+  async fn set_position(&self, track_id: ObjectPath<'_>, position: i64) {
+    let mut app = self.app.lock().await;
+    let duration_ms = match app
+      .current_playback_context
+      .as_ref()
+      .and_then(|context| context.item.as_ref())
+    {
+      // Requests for a track that is no longer playing are ignored
+      Some(item) if get_track_id(Some(item)) == track_id => get_duration_ms(item),
+      _ => return,
+    };
+    let position_ms = position / 1000;
+    if position_ms >= 0 && position_ms <= i64::from(duration_ms) {
+      app.dispatch(IoEvent::Seek(position_ms as u32));
+    }
+  }
+
+  async fn open_uri(&self, uri: &str) -> fdo::Result<()> {
+    let mut app = self.app.lock().await;
+    match uri.split(':').nth(1) {
+      Some("track") | Some("episode") => {
+        app.dispatch(IoEvent::StartPlayback(
+          None,
+          Some(vec![uri.to_string()]),
+          None,
+        ));
+      }
+      Some("album") | Some("artist") | Some("playlist") | Some("show") => {
+        app.dispatch(IoEvent::StartPlayback(Some(uri.to_string()), None, None));
+      }
+      _ => return Err(fdo::Error::InvalidArgs(format!("Unsupported uri {}", uri))),
+    }
+    Ok(())
+  }
+
+  #[dbus_interface(property)]
+  async fn playback_status(&self) -> &str {
+    get_playback_status(&self.app.lock().await.current_playback_context)
+  }
+
+  #[dbus_interface(property)]
+  async fn loop_status(&self) -> &str {
+    PlayerState::new(&self.app.lock().await.current_playback_context).loop_status
+  }
+
+  #[dbus_interface(property)]
+  async fn set_loop_status(&mut self, loop_status: &str) {
+    if let Some(repeat_state) = get_repeat_state_before(loop_status) {
+      let mut app = self.app.lock().await;
+      if PlayerState::new(&app.current_playback_context).loop_status != loop_status {
+        app.dispatch(IoEvent::Repeat(repeat_state));
+      }
+    }
+  }
+
+  #[dbus_interface(property)]
+  fn rate(&self) -> f64 {
+    1.0
+  }
+
+  #[dbus_interface(property)]
+  fn set_rate(&mut self, _rate: f64) {}
+
+  #[dbus_interface(property)]
+  async fn shuffle(&self) -> bool {
+    PlayerState::new(&self.app.lock().await.current_playback_context).shuffle
+  }
+
+  #[dbus_interface(property)]
+  async fn set_shuffle(&mut self, shuffle: bool) {
+    let mut app = self.app.lock().await;
+    // `IoEvent::Shuffle` is given the current state and toggles it
+    if PlayerState::new(&app.current_playback_context).shuffle != shuffle {
+      app.dispatch(IoEvent::Shuffle(!shuffle));
+    }
+  }
+
+  #[dbus_interface(property)]
+  async fn metadata(&self) -> HashMap<String, OwnedValue> {
+    let app = self.app.lock().await;
+    get_metadata(
+      app
+        .current_playback_context
+        .as_ref()
+        .and_then(|context| context.item.as_ref()),
+    )
+  }
+
+  #[dbus_interface(property)]
+  async fn volume(&self) -> f64 {
+    let volume_percent =
+      PlayerState::new(&self.app.lock().await.current_playback_context).volume_percent;
+    f64::from(volume_percent) / 100.0
+  }
+
+  #[dbus_interface(property)]
+  async fn set_volume(&mut self, volume: f64) {
+    let volume_percent = (volume.clamp(0.0, 1.0) * 100.0).round() as u8;
+    self
+      .app
+      .lock()
+      .await
+      .dispatch(IoEvent::ChangeVolume(volume_percent));
+  }
+
+  #[dbus_interface(property)]
+  async fn position(&self) -> i64 {
+    self.app.lock().await.song_progress_ms as i64 * 1000
+  }
+
+  #[dbus_interface(property)]
+  fn minimum_rate(&self) -> f64 {
+    1.0
+  }
+
+  #[dbus_interface(property)]
+  fn maximum_rate(&self) -> f64 {
+    1.0
+  }
+
+  #[dbus_interface(property)]
+  fn can_go_next(&self) -> bool {
+    true
+  }
+
+  #[dbus_interface(property)]
+  fn can_go_previous(&self) -> bool {
+    true
+  }
+
+  #[dbus_interface(property)]
+  fn can_play(&self) -> bool {
+    true
+  }
+
+  #[dbus_interface(property)]
+  fn can_pause(&self) -> bool {
+    true
+  }
+
+  #[dbus_interface(property)]
+  async fn can_seek(&self) -> bool {
+    PlayerState::new(&self.app.lock().await.current_playback_context)
+      .item_uri
+      .is_some()
+  }
+
+  #[dbus_interface(property)]
+  fn can_control(&self) -> bool {
+    true
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn loop_status_round_trips_through_repeat() {
+    for repeat_state in &[RepeatState::Off, RepeatState::Track, RepeatState::Context] {
+      let loop_status = get_loop_status(*repeat_state);
+      // The network layer moves on from the returned state to the next one
+      let next = match get_repeat_state_before(loop_status).unwrap() {
+        RepeatState::Off => RepeatState::Context,
+        RepeatState::Context => RepeatState::Track,
+        RepeatState::Track => RepeatState::Off,
+      };
+      assert_eq!(get_loop_status(next), loop_status);
+    }
+    assert!(get_repeat_state_before("Shuffle").is_none());
+  }
+
+  #[test]
+  fn metadata_without_item_has_no_track() {
+    let metadata = get_metadata(None);
+    assert_eq!(metadata.len(), 1);
+    assert_eq!(
+      metadata.get("mpris:trackid"),
+      Some(&ObjectPath::from_static_str_unchecked(NO_TRACK).into())
+    );
+    assert_eq!(get_playback_status(&None), "Stopped");
+  }
+}
@@ -1,42 +1,76 @@
+use crate::album_art;
 use crate::app::{
-  ActiveBlock, AlbumTableContext, App, Artist, ArtistBlock, EpisodeTableContext, RouteId,
-  ScrollableResultPages, SelectedAlbum, SelectedFullAlbum, SelectedFullShow, SelectedShow,
+  ActiveBlock, AlbumTableContext, App, Artist, ArtistBlock, EpisodeTableContext, NetworkStatus,
+  RouteId, ScrollableResultPages, SelectedAlbum, SelectedFullAlbum, SelectedFullShow, SelectedShow,
   TrackTableContext,
 };
+use crate::cache::{Cache, Cached};
 use crate::config::ClientConfig;
+use crate::device::DevicePayload;
+use crate::export::{self, ExportFormat, Library};
+use crate::history::{self, Period, Play, Stats};
+use crate::lyrics::{LocalLrcProvider, LyricsProvider, LyricsQuery};
+use crate::notifications::{self, Notification, Notifier};
+use crate::retry::{self, TransientError};
+use crate::scrobbler::{self, Listen, Scrobbler};
+use crate::sleep_timer::SleepMode;
 use anyhow::anyhow;
+use chrono::Local;
+use futures::stream::{self, StreamExt};
+use image::RgbImage;
+use rand::{thread_rng, Rng};
+use reqwest::{
+  header::{ETAG, IF_NONE_MATCH},
+  Method, RequestBuilder, StatusCode,
+};
 use rspotify::{
-  client::Spotify,
+  client::{ApiError, Spotify},
   model::{
-    album::SimplifiedAlbum,
-    artist::FullArtist,
+    album::{SavedAlbum, SimplifiedAlbum},
+    artist::{CursorPageFullArtists, FullArtist},
+    cud_result::CUDResult,
     offset::for_position,
     page::Page,
     playlist::{PlaylistTrack, SimplifiedPlaylist},
     recommend::Recommendations,
     search::SearchResult,
-    show::SimplifiedShow,
-    track::FullTrack,
+    show::{Show, SimplifiedShow},
+    track::{FullTrack, SavedTrack},
     PlayingItem,
   },
   oauth2::{SpotifyClientCredentials, SpotifyOAuth, TokenInfo},
   senum::{AdditionalType, Country, RepeatState, SearchType},
   util::get_token,
 };
-use serde_json::{map::Map, Value};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, map::Map, Value};
 use std::{
+  collections::VecDeque,
+  fs,
   sync::Arc,
   time::{Duration, Instant, SystemTime},
 };
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Notify};
 use tokio::try_join;
 
-#[derive(Debug)]
+const SPOTIFY_API_URL: &str = "https://api.spotify.com/v1/";
+// Limits of the Web API
+const MAX_IDS_PER_REQUEST: usize = 50;
+pub const MAX_PLAYLIST_TRACKS_LIMIT: u32 = 100;
+const MAX_SAVED_TRACKS_CONTAINS_IDS: usize = 50;
+// Pages of a playlist that are fetched at the same time when loading all of it
+const PLAYLIST_PAGES_CONCURRENCY: usize = 4;
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+
+#[derive(Clone, Debug)]
 pub enum IoEvent {
   GetCurrentPlayback,
   RefreshAuthentication,
   GetPlaylists,
   GetDevices,
+  RefreshDevices,
+  // Moves playback to the preferred device, resuming it if true
+  AutoSelectDevice(bool),
   GetSearchResults(String, Option<Country>),
   SetTracksToTable(Vec<FullTrack>),
   GetMadeForYouPlaylistTracks(String, u32),
@@ -51,6 +85,7 @@ pub enum IoEvent {
   Repeat(RepeatState),
   PausePlayback,
   ChangeVolume(u8),
+  ChangeDeviceVolume(String, u8),
   GetArtist(String, String, Option<Country>),
   GetAlbumTracks(Box<SimplifiedAlbum>),
   GetRecommendationsForSeed(
@@ -67,10 +102,19 @@ pub enum IoEvent {
   UserFollowArtists(Vec<String>),
   UserFollowPlaylist(String, String, Option<bool>),
   UserUnfollowPlaylist(String, String),
+  CreatePlaylist(String, String),
+  ChangePlaylistDetail(String, String, Option<String>, Option<String>),
+  AddTracksToPlaylist(String, String, Vec<String>),
+  RemoveTracksFromPlaylist(String, String, Vec<String>),
+  // Removes the track at a position of the playlist, as long as it is still at the snapshot
+  RemovePlaylistTrack(String, String, usize, String),
+  ReorderPlaylistTracks(String, String, usize, usize),
   MadeForYouSearchAndAdd(String, Option<Country>),
   GetAudioAnalysis(String),
   GetUser,
   ToggleSaveTrack(String),
+  // Likes the track if true, otherwise unlikes it
+  SetTrackSaved(String, bool),
   GetRecommendationsForTrackId(String, Option<Country>),
   GetRecentlyPlayed,
   GetFollowedArtists(Option<String>),
@@ -88,6 +132,150 @@ pub enum IoEvent {
   GetShow(String),
   GetCurrentShowEpisodes(String, Option<u32>),
   AddItemToQueue(String),
+  GetQueue,
+  JumpToQueueItem(usize),
+  GetAlbumArt(String),
+  GetLyrics(String, LyricsQuery),
+  ExportLibrary(ExportFormat),
+  GetStats(Period),
+  // Appends a play the UI saw end to the listening history
+  RecordPlay(Play),
+  UpdateNowPlaying(Listen),
+  Scrobble(Listen),
+  // Plays the uri, or queues it if true, from a random track of a playlist if true
+  PlayUri(String, bool, bool),
+  // Like `PlayUri`, with the first result of the search
+  PlaySearchResult(String, SearchType, bool, bool),
+  // Wakes whoever waits for the events sent before this one to be handled
+  Notify(Arc<Notify>),
+}
+
+impl IoEvent {
+  // Actions of the user that are sent again once requests work again, if they failed meanwhile
+  // and `can_replay_after` allows it
+  fn is_user_action(&self) -> bool {
+    matches!(
+      self,
+      IoEvent::NextTrack
+        | IoEvent::PreviousTrack
+        | IoEvent::AddItemToQueue(_)
+        | IoEvent::ToggleSaveTrack(_)
+        | IoEvent::SetTrackSaved(_, _)
+        | IoEvent::Shuffle(_)
+        | IoEvent::Repeat(_)
+        | IoEvent::ChangeVolume(_)
+        | IoEvent::RemoveTracksFromPlaylist(_, _, _)
+        | IoEvent::CurrentUserSavedAlbumDelete(_)
+        | IoEvent::CurrentUserSavedAlbumAdd(_)
+        | IoEvent::UserFollowArtists(_)
+        | IoEvent::UserUnfollowArtists(_)
+        | IoEvent::CurrentUserSavedShowDelete(_)
+        | IoEvent::CurrentUserSavedShowAdd(_)
+        | IoEvent::UserFollowPlaylist(_, _, _)
+        | IoEvent::UserUnfollowPlaylist(_, _)
+    )
+  }
+
+  // Whether running the event again after it failed midway does no harm, because it only
+  // reads or sets something to the same value again. Skipping, queueing, toggling, adding or
+  // moving by position would happen twice when a request went through before the failure
+  fn can_replay(&self) -> bool {
+    matches!(
+      self,
+      IoEvent::GetCurrentPlayback
+        | IoEvent::RefreshAuthentication
+        | IoEvent::GetPlaylists
+        | IoEvent::GetDevices
+        | IoEvent::RefreshDevices
+        | IoEvent::GetSearchResults(_, _)
+        | IoEvent::GetMadeForYouPlaylistTracks(_, _)
+        | IoEvent::GetPlaylistTracks(_, _)
+        | IoEvent::GetCurrentSavedTracks(_)
+        | IoEvent::Seek(_)
+        | IoEvent::Shuffle(_)
+        | IoEvent::Repeat(_)
+        | IoEvent::PausePlayback
+        | IoEvent::ChangeVolume(_)
+        | IoEvent::ChangeDeviceVolume(_, _)
+        | IoEvent::GetArtist(_, _, _)
+        | IoEvent::GetAlbumTracks(_)
+        | IoEvent::GetRecommendationsForSeed(_, _, _, _)
+        | IoEvent::GetCurrentUserSavedAlbums(_)
+        | IoEvent::CurrentUserSavedAlbumsContains(_)
+        | IoEvent::CurrentUserSavedAlbumDelete(_)
+        | IoEvent::CurrentUserSavedAlbumAdd(_)
+        | IoEvent::UserUnfollowArtists(_)
+        | IoEvent::UserFollowArtists(_)
+        | IoEvent::UserFollowPlaylist(_, _, _)
+        | IoEvent::UserUnfollowPlaylist(_, _)
+        | IoEvent::ChangePlaylistDetail(_, _, _, _)
+        | IoEvent::RemoveTracksFromPlaylist(_, _, _)
+        | IoEvent::MadeForYouSearchAndAdd(_, _)
+        | IoEvent::GetAudioAnalysis(_)
+        | IoEvent::GetUser
+        | IoEvent::GetRecommendationsForTrackId(_, _)
+        | IoEvent::GetRecentlyPlayed
+        | IoEvent::GetFollowedArtists(_)
+        | IoEvent::UserArtistFollowCheck(_)
+        | IoEvent::GetAlbum(_)
+        | IoEvent::TransferPlaybackToDevice(_)
+        | IoEvent::GetAlbumForTrack(_)
+        | IoEvent::CurrentUserSavedTracksContains(_)
+        | IoEvent::GetCurrentUserSavedShows(_)
+        | IoEvent::CurrentUserSavedShowsContains(_)
+        | IoEvent::CurrentUserSavedShowDelete(_)
+        | IoEvent::CurrentUserSavedShowAdd(_)
+        | IoEvent::SetTrackSaved(_, _)
+        | IoEvent::GetShowEpisodes(_)
+        | IoEvent::GetShow(_)
+        | IoEvent::GetCurrentShowEpisodes(_, _)
+        | IoEvent::GetQueue
+        | IoEvent::GetLyrics(_, _)
+        | IoEvent::GetStats(_)
+    )
+  }
+
+  // Whether the event can be run again after it failed with the error. Skips and queue
+  // additions would happen twice if they reached the api, so they are only sent again when the
+  // connection could not even be made
+  fn can_replay_after(&self, error: &TransientError) -> bool {
+    self.can_replay()
+      || error.is_unsent
+        && matches!(
+          self,
+          IoEvent::NextTrack | IoEvent::PreviousTrack | IoEvent::AddItemToQueue(_)
+        )
+  }
+
+  // Whether the event can change anything that is kept in the library cache
+  fn updates_cache(&self) -> bool {
+    matches!(
+      self,
+      IoEvent::GetPlaylists
+        | IoEvent::GetUser
+        | IoEvent::GetPlaylistTracks(_, _)
+        | IoEvent::GetCurrentSavedTracks(_)
+        | IoEvent::GetCurrentUserSavedAlbums(_)
+        | IoEvent::GetFollowedArtists(_)
+        | IoEvent::GetCurrentUserSavedShows(_)
+        | IoEvent::AddTracksToPlaylist(_, _, _)
+        | IoEvent::RemoveTracksFromPlaylist(_, _, _)
+        | IoEvent::RemovePlaylistTrack(_, _, _, _)
+        | IoEvent::ReorderPlaylistTracks(_, _, _, _)
+        | IoEvent::ToggleSaveTrack(_)
+        | IoEvent::SetTrackSaved(_, _)
+        | IoEvent::CurrentUserSavedTracksContains(_)
+        | IoEvent::CurrentUserSavedAlbumsContains(_)
+        | IoEvent::CurrentUserSavedAlbumDelete(_)
+        | IoEvent::CurrentUserSavedAlbumAdd(_)
+        | IoEvent::UserFollowArtists(_)
+        | IoEvent::UserUnfollowArtists(_)
+        | IoEvent::UserArtistFollowCheck(_)
+        | IoEvent::CurrentUserSavedShowsContains(_)
+        | IoEvent::CurrentUserSavedShowDelete(_)
+        | IoEvent::CurrentUserSavedShowAdd(_)
+    )
+  }
 }
 
 pub fn get_spotify(token_info: TokenInfo) -> (Spotify, SystemTime) {
@@ -113,13 +301,62 @@ pub fn get_spotify(token_info: TokenInfo) -> (Spotify, SystemTime) {
   (spotify, token_expiry)
 }
 
+#[derive(Deserialize)]
+struct QueueResponse {
+  currently_playing: Option<PlayingItem>,
+  queue: Vec<PlayingItem>,
+}
+
+// Scrobbles that failed for a while stay queued, other failures are shown once. The scrobbles
+// a service rejected are not sent again, so they are only reported once too
+async fn handle_scrobble_result(
+  scrobbler: &mut Scrobbler,
+  app: &Mutex<App>,
+  result: anyhow::Result<()>,
+) {
+  match result {
+    Ok(()) => scrobbler.shown_error = None,
+    Err(e) if scrobbler::is_transient(&e) => {}
+    Err(e) => {
+      let message = format!("Scrobbling failed: {}", e);
+      if scrobbler.shown_error.as_ref() != Some(&message) {
+        scrobbler.shown_error = Some(message.clone());
+        app.lock().await.handle_error(anyhow!(message));
+      }
+    }
+  }
+}
+
+fn get_tracks(items: &[PlaylistTrack]) -> Vec<FullTrack> {
+  items.iter().filter_map(|item| item.track.clone()).collect()
+}
+
 #[derive(Clone)]
 pub struct Network<'a> {
   oauth: SpotifyOAuth,
   pub spotify: Spotify,
+  http_client: reqwest::Client,
   large_search_limit: u32,
   small_search_limit: u32,
   pub client_config: ClientConfig,
+  pub cache: Cache,
+  // Asked for lyrics after the local lyrics directory
+  pub lyrics_providers: Vec<Arc<dyn LyricsProvider>>,
+  // The device of the last playback context, to notice when it goes away
+  active_device_id: Option<String>,
+  // Started when the first notification is sent
+  notifier: Option<Notifier>,
+  playing_uri: Option<String>,
+  // Set by `handle_error` for a failure that is worth retrying
+  transient_error: Option<TransientError>,
+  queued_actions: VecDeque<IoEvent>,
+  is_offline: bool,
+  // Loaded with the scrobbles that were not sent yet when the first one is made. It is shared
+  // with the tasks that send the scrobbles, so a slow service doesn't hold up the requests
+  scrobbler: Option<Arc<Mutex<Scrobbler>>>,
+  // Shows transient failures in the status of the app and queues the actions of the user
+  // instead of showing the error screen
+  pub degraded_mode: bool,
   pub app: &'a Arc<Mutex<App>>,
 }
 
@@ -133,15 +370,104 @@ impl<'a> Network<'a> {
     Network {
       oauth,
       spotify,
+      http_client: reqwest::Client::new(),
       large_search_limit: 20,
       small_search_limit: 4,
       client_config,
+      cache: Cache::default(),
+      lyrics_providers: vec![],
+      active_device_id: None,
+      notifier: None,
+      playing_uri: None,
+      transient_error: None,
+      queued_actions: VecDeque::new(),
+      is_offline: false,
+      scrobbler: None,
+      degraded_mode: false,
       app,
     }
   }
 
-  #[allow(clippy::cognitive_complexity)]
   pub async fn handle_network_event(&mut self, io_event: IoEvent) {
+    match self.handle_with_retries(io_event).await {
+      Ok(()) => {
+        // The connection is back, so the actions that failed meanwhile are sent in order
+        while let Some(action) = self.queued_actions.pop_front() {
+          if let Err((action, error)) = self.handle_with_retries(action).await {
+            if action.can_replay_after(&error) {
+              self.queued_actions.push_front(action);
+            }
+            self.report_transient_error(error).await;
+            return;
+          }
+        }
+        // Also clears the status shown while the event was retried
+        self.is_offline = false;
+        let mut app = self.app.lock().await;
+        app.network_status = NetworkStatus::Online;
+      }
+      Err((io_event, error)) => {
+        if self.degraded_mode && io_event.is_user_action() && io_event.can_replay_after(&error) {
+          self.queued_actions.push_back(io_event);
+        }
+        self.report_transient_error(error).await;
+      }
+    }
+  }
+
+  // Tries the event again with backoff while it fails with a transient error, returning it
+  // with the last error if it did not succeed
+  async fn handle_with_retries(
+    &mut self,
+    mut io_event: IoEvent,
+  ) -> Result<(), (IoEvent, TransientError)> {
+    // Polling while offline fails fast, the user actions still get their retries
+    let max_retries = if self.is_offline && !io_event.is_user_action() {
+      0
+    } else {
+      retry::MAX_RETRIES
+    };
+    let mut attempt = 0;
+    loop {
+      self.run_network_event(io_event.clone()).await;
+      let error = match self.transient_error.take() {
+        Some(error) => error,
+        None => return Ok(()),
+      };
+      // Toggling again could undo the like if it went through, so the state it was meant to
+      // reach is sent instead. The liked ids only change once the toggle succeeds
+      if let IoEvent::ToggleSaveTrack(track_id) = &io_event {
+        let is_saved = self.app.lock().await.liked_song_ids_set.contains(track_id);
+        io_event = IoEvent::SetTrackSaved(track_id.clone(), !is_saved);
+      }
+      if attempt >= max_retries || !io_event.can_replay_after(&error) {
+        return Err((io_event, error));
+      }
+      if self.degraded_mode {
+        let mut app = self.app.lock().await;
+        app.network_status = NetworkStatus::Retrying(error.message.clone());
+      }
+      tokio::time::delay_for(retry::get_delay(attempt, error.retry_after)).await;
+      attempt += 1;
+    }
+  }
+
+  async fn report_transient_error(&mut self, error: TransientError) {
+    let mut app = self.app.lock().await;
+    if self.degraded_mode {
+      self.is_offline = true;
+      app.network_status = NetworkStatus::Offline {
+        error: error.message,
+        queued_actions: self.queued_actions.len(),
+      };
+    } else {
+      app.handle_error(anyhow!(error.message));
+    }
+  }
+
+  #[allow(clippy::cognitive_complexity)]
+  async fn run_network_event(&mut self, io_event: IoEvent) {
+    let updates_cache = io_event.updates_cache();
     match io_event {
       IoEvent::RefreshAuthentication => {
         self.refresh_authentication().await;
@@ -155,6 +481,12 @@ impl<'a> Network<'a> {
       IoEvent::GetDevices => {
         self.get_devices().await;
       }
+      IoEvent::RefreshDevices => {
+        self.refresh_devices().await;
+      }
+      IoEvent::AutoSelectDevice(resume) => {
+        self.auto_select_device(resume).await;
+      }
       IoEvent::GetCurrentPlayback => {
         self.get_current_playback().await;
       }
@@ -200,6 +532,9 @@ impl<'a> Network<'a> {
       IoEvent::ChangeVolume(volume) => {
         self.change_volume(volume).await;
       }
+      IoEvent::ChangeDeviceVolume(device_id, volume) => {
+        self.change_device_volume(device_id, volume).await;
+      }
       IoEvent::GetArtist(artist_id, input_artist_name, country) => {
         self.get_artist(artist_id, input_artist_name, country).await;
       }
@@ -237,6 +572,34 @@ impl<'a> Network<'a> {
       IoEvent::UserUnfollowPlaylist(user_id, playlist_id) => {
         self.user_unfollow_playlist(user_id, playlist_id).await;
       }
+      IoEvent::CreatePlaylist(user_id, name) => {
+        self.create_playlist(user_id, name).await;
+      }
+      IoEvent::ChangePlaylistDetail(user_id, playlist_id, name, description) => {
+        self
+          .change_playlist_detail(user_id, playlist_id, name, description)
+          .await;
+      }
+      IoEvent::AddTracksToPlaylist(user_id, playlist_id, track_uris) => {
+        self
+          .add_tracks_to_playlist(user_id, playlist_id, track_uris)
+          .await;
+      }
+      IoEvent::RemoveTracksFromPlaylist(user_id, playlist_id, track_uris) => {
+        self
+          .remove_tracks_from_playlist(user_id, playlist_id, track_uris)
+          .await;
+      }
+      IoEvent::RemovePlaylistTrack(playlist_id, track_uri, position, snapshot_id) => {
+        self
+          .remove_playlist_track(playlist_id, track_uri, position, snapshot_id)
+          .await;
+      }
+      IoEvent::ReorderPlaylistTracks(user_id, playlist_id, range_start, insert_before) => {
+        self
+          .reorder_playlist_tracks(user_id, playlist_id, range_start, insert_before)
+          .await;
+      }
       IoEvent::MadeForYouSearchAndAdd(search_term, country) => {
         self.made_for_you_search_and_add(search_term, country).await;
       }
@@ -246,6 +609,9 @@ impl<'a> Network<'a> {
       IoEvent::ToggleSaveTrack(track_id) => {
         self.toggle_save_track(track_id).await;
       }
+      IoEvent::SetTrackSaved(track_id, is_saved) => {
+        self.set_track_saved(track_id, is_saved).await;
+      }
       IoEvent::GetRecommendationsForTrackId(track_id, country) => {
         self
           .get_recommendations_for_track_id(track_id, country)
@@ -302,31 +668,146 @@ impl<'a> Network<'a> {
       IoEvent::AddItemToQueue(item) => {
         self.add_item_to_queue(item).await;
       }
+      IoEvent::GetQueue => {
+        self.get_queue().await;
+      }
+      IoEvent::JumpToQueueItem(position) => {
+        self.jump_to_queue_item(position).await;
+      }
+      IoEvent::GetAlbumArt(url) => {
+        self.get_album_art(url).await;
+      }
+      IoEvent::GetLyrics(uri, query) => {
+        self.get_lyrics(uri, query).await;
+      }
+      IoEvent::ExportLibrary(format) => {
+        self.export_library(format).await;
+      }
+      IoEvent::GetStats(period) => {
+        self.get_stats(period).await;
+      }
+      IoEvent::RecordPlay(play) => {
+        self.record_play(play).await;
+      }
+      IoEvent::UpdateNowPlaying(listen) => {
+        self.update_now_playing(listen).await;
+      }
+      IoEvent::Scrobble(listen) => {
+        self.scrobble(listen).await;
+      }
+      IoEvent::PlayUri(uri, queue, random) => {
+        self.play_uri(uri, queue, random).await;
+      }
+      IoEvent::PlaySearchResult(search, search_type, queue, random) => {
+        self
+          .play_search_result(search, search_type, queue, random)
+          .await;
+      }
+      IoEvent::Notify(notify) => {
+        notify.notify();
+      }
     };
 
     let mut app = self.app.lock().await;
     app.is_loading = false;
+    if updates_cache {
+      if let Err(e) = self.cache.save(&app) {
+        app.handle_error(anyhow!("Could not write the library cache: {}", e));
+      }
+    }
   }
 
   async fn handle_error(&mut self, e: anyhow::Error) {
+    // Retried by `handle_network_event`, which reports it if it keeps failing
+    if let Some(error) = retry::get_transient_error(&e) {
+      self.transient_error = Some(error);
+      return;
+    }
     let mut app = self.app.lock().await;
     app.handle_error(e);
   }
 
+  async fn api_request(&self, method: Method, path: &str) -> anyhow::Result<RequestBuilder> {
+    let access_token = match &self.spotify.client_credentials_manager {
+      Some(credentials) => credentials.get_access_token().await,
+      None => return Err(anyhow!("no access token available")),
+    };
+    Ok(
+      self
+        .http_client
+        .request(method, &format!("{}{}", SPOTIFY_API_URL, path))
+        .bearer_auth(access_token),
+    )
+  }
+
+  // rspotify does not cover every endpoint of the Web API, this calls the missing ones with
+  // the access token of the current session. Returns `None` if there was no content
+  async fn api_get<T: DeserializeOwned>(&self, path: &str) -> anyhow::Result<Option<T>> {
+    let response = self.api_request(Method::GET, path).await?.send().await?;
+
+    let status = response.status();
+    if status == StatusCode::NO_CONTENT {
+      Ok(None)
+    } else if status.is_success() {
+      Ok(Some(serde_json::from_str(&response.text().await?)?))
+    } else {
+      Err(retry::get_response_error(&response))
+    }
+  }
+
+  // Like `api_get`, but a cached response for the same path is only fetched again if its
+  // `ETag` no longer matches. Returns `None` if the cached response is still current
+  async fn api_get_cached<T: DeserializeOwned>(
+    &self,
+    path: String,
+    cached: Option<&Cached<T>>,
+  ) -> anyhow::Result<Option<Cached<T>>> {
+    let mut request = self.api_request(Method::GET, &path).await?;
+    let cached = cached.filter(|cached| cached.path == path);
+    if let Some(Cached {
+      etag: Some(etag), ..
+    }) = &cached
+    {
+      request = request.header(IF_NONE_MATCH, etag.as_str());
+    }
+    let response = request.send().await?;
+
+    let status = response.status();
+    match cached {
+      Some(_) if status == StatusCode::NOT_MODIFIED => Ok(None),
+      _ if status.is_success() => {
+        let etag = response
+          .headers()
+          .get(ETAG)
+          .and_then(|etag| etag.to_str().ok())
+          .map(String::from);
+        Ok(Some(Cached {
+          path,
+          etag,
+          value: serde_json::from_str(&response.text().await?)?,
+        }))
+      }
+      _ => Err(retry::get_response_error(&response)),
+    }
+  }
+
   async fn get_user(&mut self) {
-    match self.spotify.current_user().await {
+    match self
+      .refresh_cached("me".to_string(), |cache| &mut cache.user)
+      .await
+    {
       Ok(user) => {
         let mut app = self.app.lock().await;
         app.user = Some(user);
       }
       Err(e) => {
-        self.handle_error(anyhow!(e)).await;
+        self.handle_error(e).await;
       }
     }
   }
 
   async fn get_devices(&mut self) {
-    if let Ok(result) = self.spotify.device().await {
+    if let Ok(Some(result)) = self.api_get::<DevicePayload>("me/player/devices").await {
       let mut app = self.app.lock().await;
       app.push_navigation_stack(RouteId::SelectedDevice, ActiveBlock::SelectDevice);
       if !result.devices.is_empty() {
@@ -337,6 +818,48 @@ impl<'a> Network<'a> {
     }
   }
 
+  // Updates the devices without opening the device list
+  async fn refresh_devices(&mut self) {
+    if let Ok(Some(result)) = self.api_get::<DevicePayload>("me/player/devices").await {
+      let mut app = self.app.lock().await;
+      if let Some(index) = app.selected_device_index {
+        app.selected_device_index = Some(index.min(result.devices.len().saturating_sub(1)));
+      }
+      app.devices = Some(result);
+    }
+  }
+
+  async fn auto_select_device(&mut self, resume: bool) {
+    let devices = match self.api_get::<DevicePayload>("me/player/devices").await {
+      Ok(devices) => devices.unwrap_or_default(),
+      Err(e) => {
+        self.handle_error(e).await;
+        return;
+      }
+    };
+    let mut app = self.app.lock().await;
+    let device = app
+      .user_config
+      .devices
+      .find_preferred(&devices.devices)
+      .filter(|device| !device.is_active)
+      .cloned();
+    app.devices = Some(devices);
+    drop(app);
+
+    if let Some(device) = device {
+      if let Err(e) = self.spotify.transfer_playback(&device.id, resume).await {
+        self.handle_error(anyhow!(e)).await;
+        return;
+      }
+      if let Err(e) = self.client_config.set_device_id(device.id) {
+        self.handle_error(e).await;
+        return;
+      }
+      self.get_current_playback().await;
+    }
+  }
+
   async fn get_current_playback(&mut self) {
     let context = self
       .spotify
@@ -348,10 +871,59 @@ impl<'a> Network<'a> {
 
     match context {
       Ok(Some(c)) => {
+        self.active_device_id = Some(c.device.id.clone());
         let mut app = self.app.lock().await;
         app.current_playback_context = Some(c.clone());
         app.instant_since_last_current_playback_poll = Instant::now();
 
+        let uri = notifications::get_item_uri(&c).map(String::from);
+        if uri.is_some() && uri != self.playing_uri && app.user_config.behavior.enable_notifications
+        {
+          let is_liked = match &c.item {
+            Some(PlayingItem::Track(FullTrack { id: Some(id), .. })) => {
+              app.liked_song_ids_set.contains(id)
+            }
+            _ => false,
+          };
+          let notification = Notification::new(&c, is_liked, &app.user_config);
+          if self.notifier.is_none() {
+            match Notifier::start(Arc::clone(self.app)) {
+              Ok(notifier) => self.notifier = Some(notifier),
+              Err(e) => {
+                // Report it once, until notifications are enabled again
+                app.user_config.behavior.enable_notifications = false;
+                app.handle_error(e);
+              }
+            }
+          }
+          if let Some(notifier) = &self.notifier {
+            notifier.notify(notification);
+          }
+        }
+        self.playing_uri = uri;
+
+        if let Some(item) = &c.item {
+          if app.queue.take_skipped_head(item) {
+            app.dispatch(IoEvent::NextTrack);
+          }
+        }
+        // Keep the queue consistent with the playbar while it is shown or has removed entries,
+        // and while the sleep timer looks in it for the end of the album
+        let is_waiting_for_album_end = matches!(
+          &app.sleep_timer,
+          Some(timer) if timer.mode == SleepMode::EndOfAlbum
+        );
+        if app.get_current_route().id == RouteId::Queue
+          || !app.queue.skipped.is_empty()
+          || is_waiting_for_album_end
+        {
+          app.dispatch(IoEvent::GetQueue);
+        }
+        // Follow the playing item with the lyrics
+        if app.get_current_route().id == RouteId::Lyrics {
+          app.update_lyrics();
+        }
+
         if let Some(item) = c.item {
           match item {
             PlayingItem::Track(track) => {
@@ -366,6 +938,10 @@ impl<'a> Network<'a> {
       Ok(None) => {
         let mut app = self.app.lock().await;
         app.instant_since_last_current_playback_poll = Instant::now();
+        // The device that was playing went away
+        if self.active_device_id.take().is_some() && !app.user_config.devices.preferred.is_empty() {
+          app.dispatch(IoEvent::AutoSelectDevice(true));
+        }
       }
       Err(e) => {
         self.handle_error(anyhow!(e)).await;
@@ -377,6 +953,18 @@ impl<'a> Network<'a> {
     app.is_fetching_current_playback = false;
   }
 
+  // Adds a track that stopped playing to the listening history
+  async fn record_play(&mut self, play: Play) {
+    let result = self
+      .client_config
+      .get_or_build_paths()
+      .and_then(|paths| history::append(&paths.history_path, &play));
+    if let Err(e) = result {
+      let mut app = self.app.lock().await;
+      app.handle_error(anyhow!("Could not record the play: {}", e));
+    }
+  }
+
   async fn current_user_saved_tracks_contains(&mut self, ids: Vec<String>) {
     match self.spotify.current_user_saved_tracks_contains(&ids).await {
       Ok(is_saved_vec) => {
@@ -401,50 +989,212 @@ impl<'a> Network<'a> {
   }
 
   async fn get_playlist_tracks(&mut self, playlist_id: String, playlist_offset: u32) {
-    if let Ok(playlist_tracks) = self
-      .spotify
-      .user_playlist_tracks(
-        "spotify",
-        &playlist_id,
-        None,
-        Some(self.large_search_limit),
-        Some(playlist_offset),
-        None,
-      )
+    if self
+      .refresh_playlist_tracks(playlist_id, playlist_offset)
       .await
     {
-      self.set_playlist_tracks_to_table(&playlist_tracks).await;
-
       let mut app = self.app.lock().await;
-      app.playlist_tracks = Some(playlist_tracks);
       app.push_navigation_stack(RouteId::TrackTable, ActiveBlock::TrackTable);
     };
   }
 
+  // Loads a page of playlist tracks into the track table without touching the navigation
+  // stack, returns whether the page could be fetched. With `load_full_playlists` the first
+  // page comes with all the others
+  async fn refresh_playlist_tracks(&mut self, playlist_id: String, playlist_offset: u32) -> bool {
+    let load_full_playlist = playlist_offset == 0 && {
+      let app = self.app.lock().await;
+      app.user_config.behavior.load_full_playlists
+    };
+    // Only the first page of the user's own playlists is cached, as only those come with a
+    // snapshot id to tell if the tracks changed
+    let snapshot_id = if playlist_offset == 0 {
+      self.get_playlist_snapshot_id(&playlist_id).await
+    } else {
+      None
+    };
+    let large_search_limit = self.large_search_limit;
+    let cached_tracks = snapshot_id
+      .as_ref()
+      .and_then(|snapshot_id| self.cache.get_playlist_tracks(&playlist_id, snapshot_id))
+      .filter(|tracks| {
+        if load_full_playlist {
+          tracks.items.len() as u32 >= tracks.total
+        } else {
+          tracks.limit == large_search_limit
+        }
+      })
+      .cloned();
+
+    let playlist_tracks = match cached_tracks {
+      Some(playlist_tracks) => playlist_tracks,
+      None => {
+        let fetched = if load_full_playlist {
+          self.get_full_playlist_tracks(&playlist_id).await
+        } else {
+          self
+            .get_playlist_tracks_page(&playlist_id, playlist_offset, self.large_search_limit)
+            .await
+        };
+        match fetched {
+          Ok(playlist_tracks) => {
+            if let Some(snapshot_id) = snapshot_id {
+              self
+                .cache
+                .insert_playlist_tracks(playlist_id, snapshot_id, playlist_tracks.clone());
+            }
+            playlist_tracks
+          }
+          Err(_) => return false,
+        }
+      }
+    };
+
+    self.set_playlist_tracks_to_table(&playlist_tracks).await;
+
+    let mut app = self.app.lock().await;
+    app.playlist_tracks = Some(playlist_tracks);
+    // The dates the tracks were added come with the playlist
+    app.track_table.reset_visible_indices();
+    true
+  }
+
+  // Fetches a page of playlist tracks, waiting as long as the api asks when it is rate limited
+  pub async fn get_playlist_tracks_page(
+    &self,
+    playlist_id: &str,
+    offset: u32,
+    limit: u32,
+  ) -> anyhow::Result<Page<PlaylistTrack>> {
+    let mut retries = 0;
+    loop {
+      let result = self
+        .spotify
+        .user_playlist_tracks(
+          "spotify",
+          playlist_id,
+          None,
+          Some(limit),
+          Some(offset),
+          None,
+        )
+        .await;
+      match result {
+        Ok(page) => return Ok(page),
+        Err(e) => match e.downcast_ref::<ApiError>() {
+          Some(ApiError::RateLimited(retry_after)) if retries < MAX_RATE_LIMIT_RETRIES => {
+            retries += 1;
+            let seconds = retry_after.unwrap_or(1) as u64;
+            tokio::time::delay_for(Duration::from_secs(seconds)).await;
+          }
+          _ => return Err(anyhow!(e)),
+        },
+      }
+    }
+  }
+
+  // Fetches all the tracks of a playlist, a few pages at a time, showing them as they arrive
+  async fn get_full_playlist_tracks(
+    &mut self,
+    playlist_id: &str,
+  ) -> anyhow::Result<Page<PlaylistTrack>> {
+    let mut playlist_tracks = self
+      .get_playlist_tracks_page(playlist_id, 0, MAX_PLAYLIST_TRACKS_LIMIT)
+      .await?;
+    let total = playlist_tracks.total;
+    if playlist_tracks.items.len() as u32 >= total {
+      return Ok(playlist_tracks);
+    }
+
+    // The liked state of the tracks is checked once all of them are there
+    {
+      let mut app = self.app.lock().await;
+      app.track_table.tracks = get_tracks(&playlist_tracks.items);
+      app.track_table.reset_visible_indices();
+      app.playlist_tracks_progress = Some((playlist_tracks.items.len() as u32, total));
+    }
+
+    let offsets =
+      (playlist_tracks.items.len() as u32..total).step_by(MAX_PLAYLIST_TRACKS_LIMIT as usize);
+    let mut pages = stream::iter(offsets)
+      .map(|offset| self.get_playlist_tracks_page(playlist_id, offset, MAX_PLAYLIST_TRACKS_LIMIT))
+      .buffered(PLAYLIST_PAGES_CONCURRENCY);
+    let mut result = Ok(());
+    while let Some(page) = pages.next().await {
+      match page {
+        Ok(page) => {
+          let mut app = self.app.lock().await;
+          app.track_table.tracks.extend(get_tracks(&page.items));
+          app.track_table.reset_visible_indices();
+          playlist_tracks.items.extend(page.items);
+          app.playlist_tracks_progress = Some((playlist_tracks.items.len() as u32, total));
+        }
+        Err(e) => {
+          result = Err(e);
+          break;
+        }
+      }
+    }
+    drop(pages);
+
+    self.app.lock().await.playlist_tracks_progress = None;
+    result?;
+    // The pages have been merged into one, so there is nothing to page through
+    playlist_tracks.limit = total;
+    playlist_tracks.next = None;
+    Ok(playlist_tracks)
+  }
+
+  async fn get_playlist_snapshot_id(&self, playlist_id: &str) -> Option<String> {
+    let app = self.app.lock().await;
+    app
+      .playlists
+      .as_ref()?
+      .items
+      .iter()
+      .find(|playlist| playlist.id == playlist_id)
+      .map(|playlist| playlist.snapshot_id.clone())
+  }
+
+  // Revalidates one of the cached responses, see `api_get_cached`
+  async fn refresh_cached<T: DeserializeOwned + Clone>(
+    &mut self,
+    path: String,
+    entry: fn(&mut Cache) -> &mut Option<Cached<T>>,
+  ) -> anyhow::Result<T> {
+    let cached = entry(&mut self.cache).clone();
+    match (self.api_get_cached(path, cached.as_ref()).await?, cached) {
+      (Some(response), _) => {
+        let value = response.value.clone();
+        *entry(&mut self.cache) = Some(response);
+        self.cache.mark_changed();
+        Ok(value)
+      }
+      // Not modified, so there is nothing new to write
+      (None, Some(cached)) => Ok(cached.value),
+      (None, None) => Err(anyhow!("not modified, but nothing was cached")),
+    }
+  }
+
   async fn set_playlist_tracks_to_table(&mut self, playlist_track_page: &Page<PlaylistTrack>) {
     self
-      .set_tracks_to_table(
-        playlist_track_page
-          .items
-          .clone()
-          .into_iter()
-          .filter_map(|item| item.track)
-          .collect::<Vec<FullTrack>>(),
-      )
+      .set_tracks_to_table(get_tracks(&playlist_track_page.items))
       .await;
   }
 
   async fn set_tracks_to_table(&mut self, tracks: Vec<FullTrack>) {
     let mut app = self.app.lock().await;
     app.track_table.tracks = tracks.clone();
+    app.track_table.reset_visible_indices();
 
-    // Send this event round (don't block here)
-    app.dispatch(IoEvent::CurrentUserSavedTracksContains(
-      tracks
-        .into_iter()
-        .filter_map(|item| item.id)
-        .collect::<Vec<String>>(),
-    ));
+    let ids = tracks
+      .into_iter()
+      .filter_map(|item| item.id)
+      .collect::<Vec<String>>();
+    // Send this event round (don't block here). The api checks a limited number of ids at once
+    for ids in ids.chunks(MAX_SAVED_TRACKS_CONTAINS_IDS) {
+      app.dispatch(IoEvent::CurrentUserSavedTracksContains(ids.to_vec()));
+    }
   }
 
   async fn set_artists_to_table(&mut self, artists: Vec<FullArtist>) {
@@ -481,21 +1231,37 @@ impl<'a> Network<'a> {
     }
   }
 
+  async fn get_saved_shows_page(&mut self, offset: Option<u32>) -> anyhow::Result<Page<Show>> {
+    match offset {
+      // Only the first page is cached
+      None => {
+        let path = format!("me/shows?limit={}", self.large_search_limit);
+        self
+          .refresh_cached(path, |cache| &mut cache.saved_shows)
+          .await
+      }
+      Some(_) => self
+        .spotify
+        .get_saved_show(self.large_search_limit, offset)
+        .await
+        .map_err(|e| anyhow!(e)),
+    }
+  }
+
   async fn get_current_user_saved_shows(&mut self, offset: Option<u32>) {
-    match self
-      .spotify
-      .get_saved_show(self.large_search_limit, offset)
-      .await
-    {
+    match self.get_saved_shows_page(offset).await {
       Ok(saved_shows) => {
+        let mut app = self.app.lock().await;
+        if offset.is_none() {
+          app.library.saved_shows = ScrollableResultPages::new();
+        }
         // not to show a blank page
         if !saved_shows.items.is_empty() {
-          let mut app = self.app.lock().await;
           app.library.saved_shows.add_pages(saved_shows);
         }
       }
       Err(e) => {
-        self.handle_error(anyhow!(e)).await;
+        self.handle_error(e).await;
       }
     }
   }
@@ -681,20 +1447,40 @@ impl<'a> Network<'a> {
     };
   }
 
+  async fn get_saved_tracks_page(
+    &mut self,
+    offset: Option<u32>,
+  ) -> anyhow::Result<Page<SavedTrack>> {
+    match offset {
+      // Only the first page is cached
+      None => {
+        let path = format!("me/tracks?limit={}", self.large_search_limit);
+        self
+          .refresh_cached(path, |cache| &mut cache.saved_tracks)
+          .await
+      }
+      Some(_) => self
+        .spotify
+        .current_user_saved_tracks(self.large_search_limit, offset)
+        .await
+        .map_err(|e| anyhow!(e)),
+    }
+  }
+
   async fn get_current_user_saved_tracks(&mut self, offset: Option<u32>) {
-    match self
-      .spotify
-      .current_user_saved_tracks(self.large_search_limit, offset)
-      .await
-    {
+    match self.get_saved_tracks_page(offset).await {
       Ok(saved_tracks) => {
         let mut app = self.app.lock().await;
+        if offset.is_none() {
+          app.library.saved_tracks = ScrollableResultPages::new();
+        }
         app.track_table.tracks = saved_tracks
           .items
           .clone()
           .into_iter()
           .map(|item| item.track)
           .collect::<Vec<FullTrack>>();
+        app.track_table.reset_visible_indices();
 
         saved_tracks.items.iter().for_each(|item| {
           if let Some(track_id) = &item.track.id {
@@ -706,7 +1492,7 @@ impl<'a> Network<'a> {
         app.track_table.context = Some(TrackTableContext::SavedTracks);
       }
       Err(e) => {
-        self.handle_error(anyhow!(e)).await;
+        self.handle_error(e).await;
       }
     }
   }
@@ -787,6 +1573,7 @@ impl<'a> Network<'a> {
     {
       Ok(()) => {
         self.get_current_playback().await;
+        self.mark_action_sent();
       }
       Err(e) => {
         self.handle_error(anyhow!(e)).await;
@@ -802,6 +1589,7 @@ impl<'a> Network<'a> {
     {
       Ok(()) => {
         self.get_current_playback().await;
+        self.mark_action_sent();
       }
       Err(e) => {
         self.handle_error(anyhow!(e)).await;
@@ -885,19 +1673,44 @@ impl<'a> Network<'a> {
     };
   }
 
-  async fn get_artist(
-    &mut self,
-    artist_id: String,
-    input_artist_name: String,
-    country: Option<Country>,
-  ) {
-    let albums = self.spotify.artist_albums(
-      &artist_id,
-      None,
-      country,
-      Some(self.large_search_limit),
-      Some(0),
-    );
+  async fn change_device_volume(&mut self, device_id: String, volume_percent: u8) {
+    match self
+      .spotify
+      .volume(volume_percent, Some(device_id.clone()))
+      .await
+    {
+      Ok(()) => {
+        let mut app = self.app.lock().await;
+        if let Some(devices) = &mut app.devices {
+          for device in devices.devices.iter_mut().filter(|d| d.id == device_id) {
+            device.volume_percent = Some(volume_percent.into());
+          }
+        }
+        if let Some(current_playback_context) = &mut app.current_playback_context {
+          if current_playback_context.device.id == device_id {
+            current_playback_context.device.volume_percent = volume_percent.into();
+          }
+        };
+      }
+      Err(e) => {
+        self.handle_error(anyhow!(e)).await;
+      }
+    };
+  }
+
+  async fn get_artist(
+    &mut self,
+    artist_id: String,
+    input_artist_name: String,
+    country: Option<Country>,
+  ) {
+    let albums = self.spotify.artist_albums(
+      &artist_id,
+      None,
+      country,
+      Some(self.large_search_limit),
+      Some(0),
+    );
     let artist_name = if input_artist_name.is_empty() {
       self
         .spotify
@@ -1095,19 +1908,67 @@ impl<'a> Network<'a> {
     };
   }
 
+  // The action reached the api, so a failure of what followed it must not send it again
+  fn mark_action_sent(&mut self) {
+    if let Some(error) = &mut self.transient_error {
+      error.is_unsent = false;
+    }
+  }
+
+  async fn set_track_saved(&mut self, track_id: String, is_saved: bool) {
+    let ids = [track_id.clone()];
+    let result = if is_saved {
+      self.spotify.current_user_saved_tracks_add(&ids).await
+    } else {
+      self.spotify.current_user_saved_tracks_delete(&ids).await
+    };
+    match result {
+      Ok(()) => {
+        let mut app = self.app.lock().await;
+        if is_saved {
+          app.liked_song_ids_set.insert(track_id);
+        } else {
+          app.liked_song_ids_set.remove(&track_id);
+        }
+      }
+      Err(e) => {
+        self.handle_error(anyhow!(e)).await;
+      }
+    }
+  }
+
+  async fn get_followed_artists_page(
+    &mut self,
+    after: Option<String>,
+  ) -> anyhow::Result<CursorPageFullArtists> {
+    if after.is_none() {
+      // Only the first page is cached
+      let path = format!("me/following?type=artist&limit={}", self.large_search_limit);
+      self
+        .refresh_cached(path, |cache| &mut cache.saved_artists)
+        .await
+    } else {
+      self
+        .spotify
+        .current_user_followed_artists(self.large_search_limit, after)
+        .await
+        .map_err(|e| anyhow!(e))
+    }
+  }
+
   async fn get_followed_artists(&mut self, after: Option<String>) {
-    match self
-      .spotify
-      .current_user_followed_artists(self.large_search_limit, after)
-      .await
-    {
+    let is_first_page = after.is_none();
+    match self.get_followed_artists_page(after).await {
       Ok(saved_artists) => {
         let mut app = self.app.lock().await;
+        if is_first_page {
+          app.library.saved_artists = ScrollableResultPages::new();
+        }
         app.artists = saved_artists.artists.items.to_owned();
         app.library.saved_artists.add_pages(saved_artists.artists);
       }
       Err(e) => {
-        self.handle_error(anyhow!(e)).await;
+        self.handle_error(e).await;
       }
     };
   }
@@ -1125,21 +1986,40 @@ impl<'a> Network<'a> {
     }
   }
 
+  async fn get_saved_albums_page(
+    &mut self,
+    offset: Option<u32>,
+  ) -> anyhow::Result<Page<SavedAlbum>> {
+    match offset {
+      // Only the first page is cached
+      None => {
+        let path = format!("me/albums?limit={}", self.large_search_limit);
+        self
+          .refresh_cached(path, |cache| &mut cache.saved_albums)
+          .await
+      }
+      Some(_) => self
+        .spotify
+        .current_user_saved_albums(self.large_search_limit, offset)
+        .await
+        .map_err(|e| anyhow!(e)),
+    }
+  }
+
   async fn get_current_user_saved_albums(&mut self, offset: Option<u32>) {
-    match self
-      .spotify
-      .current_user_saved_albums(self.large_search_limit, offset)
-      .await
-    {
+    match self.get_saved_albums_page(offset).await {
       Ok(saved_albums) => {
+        let mut app = self.app.lock().await;
+        if offset.is_none() {
+          app.library.saved_albums = ScrollableResultPages::new();
+        }
         // not to show a blank page
         if !saved_albums.items.is_empty() {
-          let mut app = self.app.lock().await;
           app.library.saved_albums.add_pages(saved_albums);
         }
       }
       Err(e) => {
-        self.handle_error(anyhow!(e)).await;
+        self.handle_error(e).await;
       }
     };
   }
@@ -1287,6 +2167,228 @@ impl<'a> Network<'a> {
     }
   }
 
+  async fn create_playlist(&mut self, user_id: String, name: String) {
+    match self
+      .spotify
+      .user_playlist_create(&user_id, &name, None, None)
+      .await
+    {
+      Ok(_) => {
+        self.get_current_user_playlists().await;
+      }
+      Err(e) => {
+        self.handle_error(anyhow!(e)).await;
+      }
+    }
+  }
+
+  async fn change_playlist_detail(
+    &mut self,
+    user_id: String,
+    playlist_id: String,
+    name: Option<String>,
+    description: Option<String>,
+  ) {
+    match self
+      .spotify
+      .user_playlist_change_detail(
+        &user_id,
+        &playlist_id,
+        name.as_deref(),
+        None,
+        description,
+        None,
+      )
+      .await
+    {
+      Ok(_) => {
+        let selected_playlist_index = self.app.lock().await.selected_playlist_index;
+        self.get_current_user_playlists().await;
+        // Keep the edited playlist selected after the reload
+        let mut app = self.app.lock().await;
+        app.selected_playlist_index = selected_playlist_index;
+      }
+      Err(e) => {
+        self.handle_error(anyhow!(e)).await;
+      }
+    }
+  }
+
+  async fn add_tracks_to_playlist(
+    &mut self,
+    user_id: String,
+    playlist_id: String,
+    track_uris: Vec<String>,
+  ) {
+    match self
+      .spotify
+      .user_playlist_add_tracks(&user_id, &playlist_id, &track_uris, None)
+      .await
+    {
+      Ok(result) => {
+        self
+          .set_playlist_snapshot(&playlist_id, result.snapshot_id)
+          .await;
+        self.refresh_active_playlist_tracks(&playlist_id).await;
+      }
+      Err(e) => {
+        self.handle_error(anyhow!(e)).await;
+      }
+    }
+  }
+
+  async fn remove_tracks_from_playlist(
+    &mut self,
+    user_id: String,
+    playlist_id: String,
+    track_uris: Vec<String>,
+  ) {
+    match self
+      .spotify
+      .user_playlist_remove_all_occurrences_of_tracks(&user_id, &playlist_id, &track_uris, None)
+      .await
+    {
+      Ok(result) => {
+        self
+          .set_playlist_snapshot(&playlist_id, result.snapshot_id)
+          .await;
+        self.refresh_active_playlist_tracks(&playlist_id).await;
+      }
+      Err(e) => {
+        self.handle_error(anyhow!(e)).await;
+      }
+    }
+  }
+
+  // Removes the tracks at the given positions, each with the uri that is expected there, and
+  // returns the new snapshot id. rspotify sends the positions under a key the api doesn't
+  // know, which would remove every copy of the tracks instead
+  pub async fn remove_playlist_positions(
+    &self,
+    playlist_id: &str,
+    tracks: &[(String, Vec<usize>)],
+    snapshot_id: &str,
+  ) -> anyhow::Result<String> {
+    let tracks = tracks
+      .iter()
+      .map(|(uri, positions)| json!({ "uri": uri, "positions": positions }))
+      .collect::<Vec<_>>();
+    let response = self
+      .api_request(Method::DELETE, &format!("playlists/{}/tracks", playlist_id))
+      .await?
+      .json(&json!({ "tracks": tracks, "snapshot_id": snapshot_id }))
+      .send()
+      .await?;
+    if !response.status().is_success() {
+      return Err(retry::get_response_error(&response));
+    }
+    let result: CUDResult = serde_json::from_str(&response.text().await?)?;
+    Ok(result.snapshot_id)
+  }
+
+  async fn remove_playlist_track(
+    &mut self,
+    playlist_id: String,
+    track_uri: String,
+    position: usize,
+    snapshot_id: String,
+  ) {
+    match self
+      .remove_playlist_positions(&playlist_id, &[(track_uri, vec![position])], &snapshot_id)
+      .await
+    {
+      Ok(snapshot_id) => {
+        self.set_playlist_snapshot(&playlist_id, snapshot_id).await;
+        self.refresh_active_playlist_tracks(&playlist_id).await;
+      }
+      Err(e) => {
+        self.handle_error(e).await;
+      }
+    }
+  }
+
+  async fn reorder_playlist_tracks(
+    &mut self,
+    user_id: String,
+    playlist_id: String,
+    range_start: usize,
+    insert_before: usize,
+  ) {
+    // The positions are those of the latest snapshot, which every earlier edit of the table
+    // moved on to, so moves made in quick succession still fit
+    let snapshot_id = self.get_playlist_snapshot_id(&playlist_id).await;
+    match self
+      .spotify
+      .user_playlist_recorder_tracks(
+        &user_id,
+        &playlist_id,
+        range_start as i32,
+        None,
+        insert_before as i32,
+        snapshot_id,
+      )
+      .await
+    {
+      // The track table was already reordered optimistically
+      Ok(result) => {
+        self
+          .set_playlist_snapshot(&playlist_id, result.snapshot_id)
+          .await;
+      }
+      Err(e) => {
+        // Put the track table back in sync
+        self.refresh_active_playlist_tracks(&playlist_id).await;
+        self.handle_error(anyhow!(e)).await;
+      }
+    }
+  }
+
+  // The playlist was edited, so its cached tracks are outdated and the snapshot they would be
+  // cached at again is the new one
+  async fn set_playlist_snapshot(&mut self, playlist_id: &str, snapshot_id: String) {
+    self.cache.remove_playlist_tracks(playlist_id);
+    let mut app = self.app.lock().await;
+    if let Some(playlist) = app
+      .playlists
+      .as_mut()
+      .and_then(|playlists| playlists.items.iter_mut().find(|p| p.id == playlist_id))
+    {
+      playlist.snapshot_id = snapshot_id;
+    }
+  }
+
+  // Reloads the track table if it is currently showing the given playlist
+  async fn refresh_active_playlist_tracks(&mut self, playlist_id: &str) {
+    // The playlist may have changed, so the cached tracks are outdated whether it is shown or not
+    self.cache.remove_playlist_tracks(playlist_id);
+
+    let playlist_offset = {
+      let app = self.app.lock().await;
+      let is_active = match (&app.playlists, app.active_playlist_index) {
+        (Some(playlists), Some(active_index)) => playlists
+          .items
+          .get(active_index)
+          .map(|playlist| playlist.id == playlist_id)
+          .unwrap_or(false),
+        _ => false,
+      };
+      if !is_active || app.track_table.context != Some(TrackTableContext::MyPlaylists) {
+        return;
+      }
+      app.playlist_offset
+    };
+
+    if self
+      .refresh_playlist_tracks(playlist_id.to_string(), playlist_offset)
+      .await
+    {
+      // Removing the last track would otherwise leave the selection out of bounds
+      let mut app = self.app.lock().await;
+      let last_index = app.track_table.tracks.len().saturating_sub(1);
+      app.track_table.selected_index = app.track_table.selected_index.min(last_index);
+    }
+  }
+
   async fn made_for_you_search_and_add(&mut self, search_string: String, country: Option<Country>) {
     const SPOTIFY_ID: &str = "spotify";
 
@@ -1346,25 +2448,294 @@ impl<'a> Network<'a> {
     }
   }
 
+  async fn get_lyrics(&mut self, uri: String, query: LyricsQuery) {
+    let lyrics_dir = self
+      .app
+      .lock()
+      .await
+      .user_config
+      .behavior
+      .lyrics_dir
+      .clone();
+    let local_provider =
+      lyrics_dir.map(|dir| Arc::new(LocalLrcProvider { dir }) as Arc<dyn LyricsProvider>);
+
+    let mut lyrics = None;
+    let mut error = None;
+    for provider in local_provider.iter().chain(self.lyrics_providers.iter()) {
+      match provider.get_lyrics(&query).await {
+        Ok(Some(found)) => {
+          lyrics = Some(found);
+          break;
+        }
+        Ok(None) => {}
+        // Another provider may still have them
+        Err(e) => error = Some(e),
+      }
+    }
+
+    let mut app = self.app.lock().await;
+    // The track may have changed while the lyrics were loading
+    if app.lyrics.item_uri.as_ref() == Some(&uri) {
+      app.lyrics.lyrics = lyrics;
+      app.lyrics.is_loading = false;
+      if let (None, Some(e)) = (&app.lyrics.lyrics, error) {
+        app.handle_error(anyhow!("Could not load the lyrics: {}", e));
+      }
+    }
+  }
+
   async fn get_current_user_playlists(&mut self) {
+    let path = format!("me/playlists?limit={}", self.large_search_limit);
     let playlists = self
-      .spotify
-      .current_user_playlists(self.large_search_limit, None)
+      .refresh_cached(path, |cache| &mut cache.playlists)
       .await;
 
     match playlists {
       Ok(p) => {
         let mut app = self.app.lock().await;
+        // Select the first playlist, or keep the selection made while the cached ones were shown
+        let last_index = p.items.len().saturating_sub(1);
+        app.selected_playlist_index =
+          Some(app.selected_playlist_index.unwrap_or(0).min(last_index));
         app.playlists = Some(p);
-        // Select the first playlist
-        app.selected_playlist_index = Some(0);
       }
       Err(e) => {
-        self.handle_error(anyhow!(e)).await;
+        self.handle_error(e).await;
       }
     };
   }
 
+  /// Fetches the whole library page by page, the tracks of the playlists only if asked to
+  // Every playlist of the user, not only the first page
+  pub async fn get_all_playlists(&self) -> anyhow::Result<Vec<SimplifiedPlaylist>> {
+    let mut playlists: Vec<SimplifiedPlaylist> = vec![];
+    loop {
+      let page = self
+        .spotify
+        .current_user_playlists(self.large_search_limit, playlists.len() as u32)
+        .await
+        .map_err(|e| anyhow!(e))?;
+      let is_last_page = page.next.is_none() || page.items.is_empty();
+      playlists.extend(page.items);
+      if is_last_page {
+        return Ok(playlists);
+      }
+    }
+  }
+
+  pub async fn get_library(&mut self, with_playlist_tracks: bool) -> anyhow::Result<Library> {
+    let mut library = Library::default();
+    loop {
+      let offset = library.liked_tracks.len() as u32;
+      let page = self.get_saved_tracks_page(Some(offset)).await?;
+      let items = page
+        .items
+        .iter()
+        .map(|item| export::Item::from(&item.track));
+      library.liked_tracks.extend(items);
+      if page.next.is_none() || page.items.is_empty() {
+        break;
+      }
+    }
+    loop {
+      let offset = library.saved_albums.len() as u32;
+      let page = self.get_saved_albums_page(Some(offset)).await?;
+      let items = page
+        .items
+        .iter()
+        .map(|item| export::Item::from(&item.album));
+      library.saved_albums.extend(items);
+      if page.next.is_none() || page.items.is_empty() {
+        break;
+      }
+    }
+    loop {
+      let offset = library.saved_shows.len() as u32;
+      let page = self.get_saved_shows_page(Some(offset)).await?;
+      let items = page.items.iter().map(|item| export::Item::from(&item.show));
+      library.saved_shows.extend(items);
+      if page.next.is_none() || page.items.is_empty() {
+        break;
+      }
+    }
+    let mut after = None;
+    loop {
+      let page = self.get_followed_artists_page(after).await?.artists;
+      library
+        .followed_artists
+        .extend(page.items.iter().map(export::Item::from));
+      after = page.cursors.after;
+      if page.next.is_none() || after.is_none() {
+        break;
+      }
+    }
+
+    for playlist in self.get_all_playlists().await? {
+      let mut tracks = vec![];
+      if with_playlist_tracks {
+        loop {
+          let page = self
+            .get_playlist_tracks_page(&playlist.id, tracks.len() as u32, MAX_PLAYLIST_TRACKS_LIMIT)
+            .await?;
+          // Unavailable tracks have no track object, so they are left out
+          tracks.extend(
+            page
+              .items
+              .iter()
+              .map(|item| item.track.as_ref().map(export::Item::from)),
+          );
+          if page.next.is_none() || page.items.is_empty() {
+            break;
+          }
+        }
+      }
+      let tracks = tracks.into_iter().flatten().collect();
+      library
+        .playlists
+        .push(export::Playlist::new(&playlist, tracks));
+    }
+    Ok(library)
+  }
+
+  /// Saves and follows everything in the library, which should only be what is missing
+  pub async fn import_library(&mut self, library: &Library) -> anyhow::Result<()> {
+    fn get_ids(items: &[export::Item]) -> Vec<String> {
+      items.iter().map(|item| item.get_id().to_string()).collect()
+    }
+    for ids in get_ids(&library.liked_tracks).chunks(MAX_IDS_PER_REQUEST) {
+      self
+        .spotify
+        .current_user_saved_tracks_add(ids)
+        .await
+        .map_err(|e| anyhow!(e))?;
+    }
+    for ids in get_ids(&library.saved_albums).chunks(MAX_IDS_PER_REQUEST) {
+      self
+        .spotify
+        .current_user_saved_albums_add(ids)
+        .await
+        .map_err(|e| anyhow!(e))?;
+    }
+    for ids in get_ids(&library.followed_artists).chunks(MAX_IDS_PER_REQUEST) {
+      self
+        .spotify
+        .user_follow_artists(ids)
+        .await
+        .map_err(|e| anyhow!(e))?;
+    }
+    for ids in get_ids(&library.saved_shows).chunks(MAX_IDS_PER_REQUEST) {
+      self
+        .spotify
+        .save_shows(ids.to_vec())
+        .await
+        .map_err(|e| anyhow!(e))?;
+    }
+    // Following a deleted playlist of your own restores it
+    for playlist in &library.playlists {
+      self
+        .spotify
+        .user_playlist_follow_playlist(&playlist.owner_id, playlist.get_id(), None)
+        .await
+        .map_err(|e| anyhow!(e))?;
+    }
+    Ok(())
+  }
+
+  async fn export_library(&mut self, format: ExportFormat) {
+    self.app.lock().await.status_message = Some("Exporting the library...".to_string());
+    let result = async {
+      let library = self.get_library(true).await?;
+      let dir = self.client_config.get_or_build_paths()?.export_dir;
+      let name = format!("library-{}", Local::now().format("%Y-%m-%d-%H%M%S"));
+      // M3U and XSPF exports are a directory of playlists
+      let path = if format.is_playlist_format() {
+        dir.join(name)
+      } else {
+        dir.join(format!("{}.{}", name, format.extension()))
+      };
+      library.write_to(format, &path)?;
+      Ok(path)
+    }
+    .await;
+
+    match result {
+      Ok(path) => {
+        let mut app = self.app.lock().await;
+        app.status_message = Some(format!("Exported the library to {}", path.display()));
+      }
+      Err(e) => {
+        self.app.lock().await.status_message = None;
+        self.handle_error(e).await;
+      }
+    }
+  }
+
+  async fn get_stats(&mut self, period: Period) {
+    let plays = self
+      .client_config
+      .get_or_build_paths()
+      .and_then(|paths| history::load(&paths.history_path));
+    let mut app = self.app.lock().await;
+    match plays {
+      Ok(plays) => {
+        let now = Local::now().timestamp();
+        app.stats.stats = Some(Stats::new(&plays, period, now));
+      }
+      Err(e) => app.handle_error(anyhow!("Could not read the listening history: {}", e)),
+    }
+  }
+
+  fn get_scrobbler(&mut self) -> anyhow::Result<Arc<Mutex<Scrobbler>>> {
+    if self.scrobbler.is_none() {
+      let paths = self.client_config.get_or_build_paths()?;
+      self.scrobbler = Some(Arc::new(Mutex::new(Scrobbler::load(
+        paths.scrobble_queue_path,
+        paths.rejected_scrobbles_path,
+      ))));
+    }
+    Ok(Arc::clone(self.scrobbler.as_ref().unwrap()))
+  }
+
+  // Sends to the scrobbling services in a task of its own. The scrobbler is locked for the
+  // whole submission, so the submissions go out in the order they were made
+  async fn spawn_scrobbling(&mut self, listen: Listen, is_scrobble: bool) {
+    let config = self.app.lock().await.user_config.scrobbling.clone();
+    let scrobbler = match self.get_scrobbler() {
+      Ok(scrobbler) => scrobbler,
+      Err(e) => {
+        self.app.lock().await.handle_error(e);
+        return;
+      }
+    };
+    let app = Arc::clone(self.app);
+    tokio::spawn(async move {
+      let mut scrobbler = scrobbler.lock().await;
+      let result = if is_scrobble {
+        scrobbler.scrobble(&config, listen).await
+      } else {
+        async {
+          scrobbler.update_now_playing(&config, &listen).await?;
+          // The services are reachable again, so the scrobbles made while they were not are sent
+          if scrobbler.has_queued_scrobbles() {
+            scrobbler.flush(&config).await?;
+          }
+          Ok(())
+        }
+        .await
+      };
+      handle_scrobble_result(&mut scrobbler, &app, result).await;
+    });
+  }
+
+  async fn update_now_playing(&mut self, listen: Listen) {
+    self.spawn_scrobbling(listen, false).await;
+  }
+
+  async fn scrobble(&mut self, listen: Listen) {
+    self.spawn_scrobbling(listen, true).await;
+  }
+
   async fn get_recently_played(&mut self) {
     match self
       .spotify
@@ -1457,7 +2828,10 @@ impl<'a> Network<'a> {
     match self.client_config.set_device_id(device_id) {
       Ok(()) => {
         let mut app = self.app.lock().await;
-        app.pop_navigation_stack();
+        // Playback can also be transferred from the command palette
+        if app.get_current_route().id == RouteId::SelectedDevice {
+          app.pop_navigation_stack();
+        }
       }
       Err(e) => {
         self.handle_error(e).await;
@@ -1477,16 +2851,150 @@ impl<'a> Network<'a> {
     }
   }
 
+  async fn play_uri(&mut self, uri: String, queue: bool, random: bool) {
+    if uri.contains("spotify:track:") {
+      if queue {
+        self.add_item_to_queue(uri).await;
+      } else {
+        self.start_playback(None, Some(vec![uri]), Some(0)).await;
+      }
+      return;
+    }
+
+    // Only playlists can start at a random track for now
+    let offset = match uri.strip_prefix("spotify:playlist:") {
+      Some(id) if random => match self.spotify.playlist(id, None, None).await {
+        Ok(playlist) if playlist.tracks.total > 0 => {
+          Some(thread_rng().gen_range(0..playlist.tracks.total) as usize)
+        }
+        Ok(_) => None,
+        Err(e) => {
+          self.handle_error(anyhow!(e)).await;
+          return;
+        }
+      },
+      _ => None,
+    };
+    self.start_playback(Some(uri), None, offset).await;
+  }
+
+  async fn play_search_result(
+    &mut self,
+    search: String,
+    search_type: SearchType,
+    queue: bool,
+    random: bool,
+  ) {
+    let result = self
+      .spotify
+      .search(&search, search_type, 1, 0, None, None)
+      .await;
+    let uri = match result {
+      Ok(SearchResult::Tracks(page)) => page.items.into_iter().next().map(|track| track.uri),
+      Ok(SearchResult::Albums(page)) => page.items.into_iter().next().and_then(|album| album.uri),
+      Ok(SearchResult::Artists(page)) => page.items.into_iter().next().map(|artist| artist.uri),
+      Ok(SearchResult::Playlists(page)) => {
+        page.items.into_iter().next().map(|playlist| playlist.uri)
+      }
+      Ok(SearchResult::Shows(page)) => page.items.into_iter().next().map(|show| show.uri),
+      Ok(SearchResult::Episodes(page)) => page.items.into_iter().next().map(|episode| episode.uri),
+      Err(e) => {
+        self.handle_error(anyhow!(e)).await;
+        return;
+      }
+    };
+    match uri {
+      Some(uri) => self.play_uri(uri, queue, random).await,
+      None => {
+        self
+          .handle_error(anyhow!(
+            "no {}s with name '{}'",
+            search_type.as_str(),
+            search
+          ))
+          .await
+      }
+    }
+  }
+
   async fn add_item_to_queue(&mut self, item: String) {
     match self
       .spotify
-      .add_item_to_queue(item, self.client_config.device_id.clone())
+      .add_item_to_queue(item.clone(), self.client_config.device_id.clone())
       .await
     {
-      Ok(()) => (),
+      Ok(()) => {
+        let mut app = self.app.lock().await;
+        app.queue.locally_added.push(item);
+        if app.get_current_route().id == RouteId::Queue {
+          app.dispatch(IoEvent::GetQueue);
+        }
+      }
+      Err(e) => {
+        self.handle_error(anyhow!(e)).await;
+      }
+    }
+  }
+
+  async fn get_queue(&mut self) {
+    match self.api_get::<QueueResponse>("me/player/queue").await {
+      Ok(response) => {
+        let (currently_playing, items) = match response {
+          Some(response) => (response.currently_playing, response.queue),
+          None => (None, vec![]),
+        };
+        let mut app = self.app.lock().await;
+        if let Some(item) = &currently_playing {
+          if app.queue.take_skipped_head(item) {
+            app.dispatch(IoEvent::NextTrack);
+          }
+        }
+        app.queue.set_items(currently_playing, items);
+      }
       Err(e) => {
+        self.handle_error(e).await;
+      }
+    }
+  }
+
+  async fn get_album_art(&mut self, url: String) {
+    // A cover that can't be loaded leaves its square empty, it isn't worth the error screen.
+    // The url stays requested, so it isn't downloaded again on every frame
+    if let Ok(image) = self.load_album_art(&url).await {
+      let mut app = self.app.lock().await;
+      app.album_art.insert(url, image);
+    }
+  }
+
+  // Reads the image from the disk cache, downloading it the first time
+  async fn load_album_art(&self, url: &str) -> anyhow::Result<RgbImage> {
+    let dir = self.client_config.get_or_build_paths()?.album_art_dir;
+    let path = album_art::get_cache_path(&dir, url);
+    let bytes = if path.exists() {
+      fs::read(&path)?
+    } else {
+      let response = self.http_client.get(url).send().await?.error_for_status()?;
+      let bytes = response.bytes().await?.to_vec();
+      fs::create_dir_all(&dir)?;
+      fs::write(&path, &bytes)?;
+      bytes
+    };
+    album_art::decode(&bytes)
+  }
+
+  // There is no endpoint to play a queue entry directly, so skip until it is reached
+  async fn jump_to_queue_item(&mut self, position: usize) {
+    for _ in 0..=position {
+      if let Err(e) = self
+        .spotify
+        .next_track(self.client_config.device_id.clone())
+        .await
+      {
         self.handle_error(anyhow!(e)).await;
+        return;
       }
     }
+    self.get_current_playback().await;
+    self.get_queue().await;
   }
 }
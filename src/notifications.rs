@@ -0,0 +1,203 @@
+use crate::app::App;
+use crate::cli::{format_output, Format};
+use crate::user_config::UserConfig;
+use anyhow::Result;
+use rspotify::model::{context::CurrentlyPlaybackContext, PlayingItem};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+  pub summary: String,
+  pub body: String,
+}
+
+impl Notification {
+  /// The first line of the formatted `notification_format` is the summary, the rest the body
+  pub fn new(context: &CurrentlyPlaybackContext, is_liked: bool, config: &UserConfig) -> Self {
+    let text = format_output(
+      config.behavior.notification_format.clone(),
+      Format::from_context(context, is_liked),
+      config,
+    );
+    let mut lines = text.splitn(2, '\n');
+    Notification {
+      summary: lines.next().unwrap_or_default().to_string(),
+      body: lines.next().unwrap_or_default().trim().to_string(),
+    }
+  }
+}
+
+pub fn get_item_uri(context: &CurrentlyPlaybackContext) -> Option<&str> {
+  match &context.item {
+    Some(PlayingItem::Track(track)) => Some(&track.uri),
+    Some(PlayingItem::Episode(episode)) => Some(&episode.uri),
+    None => None,
+  }
+}
+
+#[cfg(feature = "notifications")]
+pub use dbus::Notifier;
+
+#[cfg(feature = "notifications")]
+mod dbus {
+  use super::*;
+  use anyhow::anyhow;
+  use std::{
+    collections::HashMap,
+    sync::mpsc::{channel, Sender},
+    thread,
+  };
+  use zbus::{block_on, blocking::Connection, zvariant::Value};
+
+  const DESTINATION: &str = "org.freedesktop.Notifications";
+  const OBJECT_PATH: &str = "/org/freedesktop/Notifications";
+  const APP_NAME: &str = "spotify-tui";
+  // Let the notification server decide how long notifications are shown
+  const DEFAULT_TIMEOUT: i32 = -1;
+
+  /// Sends notifications from its own thread, so the network thread never waits on D-Bus
+  #[derive(Clone)]
+  pub struct Notifier {
+    sender: Sender<Notification>,
+  }
+
+  impl Notifier {
+    pub fn start(app: Arc<Mutex<App>>) -> Result<Self> {
+      let (sender, receiver) = channel::<Notification>();
+      thread::spawn(move || {
+        let result = Connection::session().and_then(|connection| {
+          // Each notification replaces the previous one instead of piling up
+          let mut replaces_id = 0;
+          for notification in receiver {
+            replaces_id = send(&connection, &notification, replaces_id)?;
+          }
+          Ok(())
+        });
+        if let Err(e) = result {
+          block_on(app.lock()).handle_error(anyhow!("Could not send a notification: {}", e));
+        }
+      });
+      Ok(Notifier { sender })
+    }
+
+    pub fn notify(&self, notification: Notification) {
+      // The thread is gone after an error, which it has reported already
+      let _ = self.sender.send(notification);
+    }
+  }
+
+  /// Calls `Notify` of the notification server, returning the id of the notification
+  pub fn send(
+    connection: &Connection,
+    notification: &Notification,
+    replaces_id: u32,
+  ) -> zbus::Result<u32> {
+    let actions: Vec<&str> = vec![];
+    let hints: HashMap<&str, Value> = HashMap::new();
+    connection
+      .call_method(
+        Some(DESTINATION),
+        OBJECT_PATH,
+        Some(DESTINATION),
+        "Notify",
+        &(
+          APP_NAME,
+          replaces_id,
+          "",
+          notification.summary.as_str(),
+          notification.body.as_str(),
+          actions,
+          hints,
+          DEFAULT_TIMEOUT,
+        ),
+      )?
+      .body::<u32>()
+  }
+
+  #[cfg(test)]
+  mod tests {
+    use super::*;
+    use std::sync::mpsc::Receiver;
+    use zbus::{blocking::ConnectionBuilder, dbus_interface};
+
+    // The replaced id, summary and body of a notification
+    type Received = (u32, String, String);
+
+    struct FakeServer {
+      sender: std::sync::Mutex<Sender<Received>>,
+    }
+
+    #[dbus_interface(name = "org.freedesktop.Notifications")]
+    impl FakeServer {
+      #[allow(clippy::too_many_arguments)]
+      fn notify(
+        &self,
+        _app_name: &str,
+        replaces_id: u32,
+        _app_icon: &str,
+        summary: &str,
+        body: &str,
+        _actions: Vec<&str>,
+        _hints: HashMap<&str, Value<'_>>,
+        _expire_timeout: i32,
+      ) -> u32 {
+        self
+          .sender
+          .lock()
+          .unwrap()
+          .send((replaces_id, summary.to_string(), body.to_string()))
+          .unwrap();
+        replaces_id + 1
+      }
+    }
+
+    fn start_fake_server() -> (Connection, Receiver<Received>) {
+      let (sender, receiver) = channel();
+      let server = FakeServer {
+        sender: std::sync::Mutex::new(sender),
+      };
+      let connection = ConnectionBuilder::session()
+        .and_then(|builder| builder.name(DESTINATION))
+        .and_then(|builder| builder.serve_at(OBJECT_PATH, server))
+        .and_then(|builder| builder.build())
+        .expect("the notification server name should be free on the session bus");
+      (connection, receiver)
+    }
+
+    #[test]
+    #[ignore = "needs a session bus without a notification server, run it with \
+`dbus-run-session -- cargo test --features notifications -- --ignored`"]
+    fn sends_notifications_to_the_session_bus() {
+      let (_server, received) = start_fake_server();
+      let connection = Connection::session().unwrap();
+      let notification = Notification {
+        summary: "Title".to_string(),
+        body: "Artist - Album".to_string(),
+      };
+
+      let id = send(&connection, &notification, 0).unwrap();
+      assert_eq!(
+        received.recv().unwrap(),
+        (0, "Title".to_string(), "Artist - Album".to_string())
+      );
+      send(&connection, &notification, id).unwrap();
+      assert_eq!(received.recv().unwrap().0, id);
+    }
+  }
+}
+
+#[cfg(not(feature = "notifications"))]
+#[derive(Clone)]
+pub struct Notifier;
+
+#[cfg(not(feature = "notifications"))]
+impl Notifier {
+  pub fn start(_app: Arc<Mutex<App>>) -> Result<Self> {
+    Err(anyhow::anyhow!(
+      "Notifications need spt to be built with the `notifications` feature"
+    ))
+  }
+
+  pub fn notify(&self, _notification: Notification) {}
+}
@@ -0,0 +1,127 @@
+use reqwest::{header::RETRY_AFTER, Response, StatusCode};
+use rspotify::client::ApiError;
+use std::time::Duration;
+
+// Transient failures of a request are tried again this many times
+pub const MAX_RETRIES: u32 = 4;
+// Doubled with each retry, unless the api says how long to wait
+const BASE_DELAY: Duration = Duration::from_millis(500);
+const MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// A failure that may go away by itself, like a rate limit or a dropped connection
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransientError {
+  pub message: String,
+  pub retry_after: Option<Duration>,
+  // The connection could not be made, so the request surely never reached the api
+  pub is_unsent: bool,
+}
+
+fn get_api_error(e: &anyhow::Error) -> Option<&ApiError> {
+  e.downcast_ref::<ApiError>().or_else(|| {
+    e.downcast_ref::<failure::Error>()?
+      .downcast_ref::<ApiError>()
+  })
+}
+
+fn get_reqwest_error(e: &anyhow::Error) -> Option<&reqwest::Error> {
+  e.downcast_ref::<reqwest::Error>().or_else(|| {
+    e.downcast_ref::<failure::Error>()?
+      .downcast_ref::<reqwest::Error>()
+  })
+}
+
+// Whether the request failed before the connection was made
+fn is_connect_error(error: &reqwest::Error) -> bool {
+  let mut source = std::error::Error::source(error);
+  while let Some(error) = source {
+    if let Some(error) = error.downcast_ref::<hyper::Error>() {
+      return error.is_connect();
+    }
+    source = error.source();
+  }
+  false
+}
+
+/// Returns the error as transient if trying the request again later can succeed
+pub fn get_transient_error(e: &anyhow::Error) -> Option<TransientError> {
+  let retry_after = match get_api_error(e) {
+    Some(ApiError::RateLimited(seconds)) => seconds.map(|s| Duration::from_secs(s as u64)),
+    Some(ApiError::RegularError { status, .. })
+    | Some(ApiError::PlayerError { status, .. })
+    | Some(ApiError::Other(status))
+      if *status >= 500 =>
+    {
+      None
+    }
+    Some(_) => return None,
+    None => match get_reqwest_error(e) {
+      // The request could not be sent or the response was cut off
+      Some(error) if !error.is_builder() && !error.is_redirect() && !error.is_status() => None,
+      _ => return None,
+    },
+  };
+  Some(TransientError {
+    message: e.to_string(),
+    retry_after,
+    is_unsent: get_reqwest_error(e).is_some_and(is_connect_error),
+  })
+}
+
+/// How long to wait before the retry after `attempt` failed ones. The wait the api asks for is
+/// capped as well, since every other request waits behind the retry
+pub fn get_delay(attempt: u32, retry_after: Option<Duration>) -> Duration {
+  retry_after
+    .unwrap_or_else(|| BASE_DELAY * 2u32.saturating_pow(attempt))
+    .min(MAX_DELAY)
+}
+
+/// The error of a response to a request rspotify does not cover, in the way rspotify reports
+/// rate limits and server errors
+pub fn get_response_error(response: &Response) -> anyhow::Error {
+  let status = response.status();
+  if status == StatusCode::TOO_MANY_REQUESTS {
+    let seconds = response
+      .headers()
+      .get(RETRY_AFTER)
+      .and_then(|value| value.to_str().ok())
+      .and_then(|value| value.parse().ok());
+    anyhow::Error::msg(ApiError::RateLimited(seconds))
+  } else {
+    anyhow::Error::msg(ApiError::Other(status.as_u16()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use anyhow::anyhow;
+
+  #[test]
+  fn retries_rate_limits_and_server_errors() {
+    let rate_limited = anyhow!(failure::Error::from(ApiError::RateLimited(Some(3))));
+    assert_eq!(
+      get_transient_error(&rate_limited).unwrap().retry_after,
+      Some(Duration::from_secs(3))
+    );
+    let unavailable = anyhow::Error::msg(ApiError::Other(503));
+    assert_eq!(get_transient_error(&unavailable).unwrap().retry_after, None);
+    assert!(!get_transient_error(&unavailable).unwrap().is_unsent);
+
+    let not_found = anyhow!(failure::Error::from(ApiError::Other(404)));
+    assert!(get_transient_error(&not_found).is_none());
+    assert!(get_transient_error(&anyhow!("No device_id selected")).is_none());
+  }
+
+  #[test]
+  fn backs_off_exponentially() {
+    assert_eq!(get_delay(0, None), Duration::from_millis(500));
+    assert_eq!(get_delay(3, None), Duration::from_secs(4));
+    assert_eq!(get_delay(10, None), MAX_DELAY);
+    assert_eq!(
+      get_delay(0, Some(Duration::from_secs(3))),
+      Duration::from_secs(3)
+    );
+    assert_eq!(get_delay(0, Some(Duration::from_secs(3600))), MAX_DELAY);
+  }
+}
@@ -0,0 +1,679 @@
+use anyhow::{anyhow, Result};
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use rspotify::model::track::FullTrack;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{
+  fmt,
+  fs::{self, OpenOptions},
+  io::Write,
+  path::PathBuf,
+  time::Duration,
+};
+
+use crate::retry;
+
+// Tracks shorter than this are never scrobbled
+const MIN_DURATION_MS: u32 = 30 * 1000;
+// A track is scrobbled once half of it, or this much, has been played
+const MAX_LISTEN_MS: u32 = 4 * 60 * 1000;
+// Going back further than this after a scrobble counts as playing the track again
+const REPLAY_MARGIN_MS: u32 = 10 * 1000;
+// Both services take at most this many scrobbles in one request
+const MAX_BATCH_SIZE: usize = 50;
+
+const CLIENT_NAME: &str = "spotify-tui";
+// A service that doesn't answer must not keep the scrobbles after it waiting forever
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+// The Last.fm errors that go away by themselves: the service is offline, temporarily
+// unavailable or rate limits the client
+const LASTFM_TRANSIENT_ERRORS: [u64; 3] = [11, 16, 29];
+
+fn default_listenbrainz_url() -> String {
+  "https://api.listenbrainz.org".to_string()
+}
+
+fn default_lastfm_url() -> String {
+  "https://ws.audioscrobbler.com/2.0/".to_string()
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ListenBrainzConfig {
+  // The user token from https://listenbrainz.org/profile/
+  pub token: String,
+  #[serde(default = "default_listenbrainz_url")]
+  pub url: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LastFmConfig {
+  pub api_key: String,
+  pub api_secret: String,
+  // Obtained once with `auth.getSession`, it does not expire
+  pub session_key: String,
+  #[serde(default = "default_lastfm_url")]
+  pub url: String,
+}
+
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScrobblingConfig {
+  pub listenbrainz: Option<ListenBrainzConfig>,
+  pub lastfm: Option<LastFmConfig>,
+}
+
+impl ScrobblingConfig {
+  pub fn is_enabled(&self) -> bool {
+    self.listenbrainz.is_some() || self.lastfm.is_some()
+  }
+}
+
+/// A play of a track, as the scrobbling services want it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Listen {
+  pub artists: Vec<String>,
+  pub track: String,
+  pub album: String,
+  pub duration_ms: u32,
+  pub uri: String,
+  // When the track started playing, in seconds since the unix epoch
+  pub listened_at: i64,
+}
+
+impl Listen {
+  pub fn new(track: &FullTrack, listened_at: i64) -> Self {
+    Listen {
+      artists: track.artists.iter().map(|a| a.name.clone()).collect(),
+      track: track.name.clone(),
+      album: track.album.name.clone(),
+      duration_ms: track.duration_ms,
+      uri: track.uri.clone(),
+      listened_at,
+    }
+  }
+
+  fn artist(&self) -> &str {
+    self.artists.first().map_or("", String::as_str)
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScrobbleEvent {
+  NowPlaying(Listen),
+  Scrobble(Listen),
+}
+
+/// Follows the playing track to tell when it was listened to long enough to be scrobbled
+#[derive(Default)]
+pub struct ScrobbleState {
+  listen: Option<Listen>,
+  progress_ms: u32,
+  is_now_playing_sent: bool,
+  is_scrobbled: bool,
+}
+
+impl ScrobbleState {
+  /// Called with the playing track on every tick, `now` is in seconds since the unix epoch
+  pub fn update(
+    &mut self,
+    track: Option<&FullTrack>,
+    is_playing: bool,
+    progress_ms: u32,
+    now: i64,
+  ) -> Option<ScrobbleEvent> {
+    let track = match track {
+      // Local files can't be looked up by the services
+      Some(track) if !track.is_local => track,
+      _ => {
+        *self = ScrobbleState::default();
+        return None;
+      }
+    };
+
+    let is_same_track = self.listen.as_ref().map(|l| &l.uri) == Some(&track.uri);
+    let is_replayed = self.is_scrobbled && progress_ms + REPLAY_MARGIN_MS < self.progress_ms;
+    if !is_same_track || is_replayed {
+      *self = ScrobbleState {
+        listen: Some(Listen::new(track, now - i64::from(progress_ms / 1000))),
+        ..Default::default()
+      };
+    }
+    self.progress_ms = progress_ms;
+    let listen = self.listen.as_ref()?;
+
+    if is_playing && !self.is_now_playing_sent {
+      self.is_now_playing_sent = true;
+      return Some(ScrobbleEvent::NowPlaying(listen.clone()));
+    }
+    let threshold = (listen.duration_ms / 2).min(MAX_LISTEN_MS);
+    if !self.is_scrobbled && listen.duration_ms >= MIN_DURATION_MS && progress_ms >= threshold {
+      self.is_scrobbled = true;
+      return Some(ScrobbleEvent::Scrobble(listen.clone()));
+    }
+    None
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Service {
+  ListenBrainz,
+  LastFm,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct QueuedScrobble {
+  service: Service,
+  listen: Listen,
+}
+
+/// Sends now playing updates and scrobbles, keeping the scrobbles that could not be sent in a
+/// file until they can
+#[derive(Clone)]
+pub struct Scrobbler {
+  http_client: reqwest::Client,
+  queue_path: PathBuf,
+  // The scrobbles a service refused are moved here, one json object per line, so they don't
+  // hold up the ones queued after them
+  rejected_path: PathBuf,
+  queue: Vec<QueuedScrobble>,
+  // The last failure that was shown, to not show it again on every track
+  pub shown_error: Option<String>,
+}
+
+impl Scrobbler {
+  pub fn load(queue_path: PathBuf, rejected_path: PathBuf) -> Self {
+    let queue = fs::read_to_string(&queue_path)
+      .ok()
+      .and_then(|queue| serde_json::from_str(&queue).ok())
+      .unwrap_or_default();
+    let http_client = reqwest::Client::builder()
+      .connect_timeout(CONNECT_TIMEOUT)
+      .timeout(REQUEST_TIMEOUT)
+      .build()
+      .unwrap_or_default();
+    Scrobbler {
+      http_client,
+      queue_path,
+      rejected_path,
+      queue,
+      shown_error: None,
+    }
+  }
+
+  pub fn has_queued_scrobbles(&self) -> bool {
+    !self.queue.is_empty()
+  }
+
+  pub async fn update_now_playing(&self, config: &ScrobblingConfig, listen: &Listen) -> Result<()> {
+    if let Some(listenbrainz) = &config.listenbrainz {
+      let body = get_listenbrainz_body("playing_now", &[listen]);
+      submit_listenbrainz(&self.http_client, listenbrainz, &body).await?;
+    }
+    if let Some(lastfm) = &config.lastfm {
+      let mut params = get_lastfm_track_params("", listen);
+      params.push(("method".to_string(), "track.updateNowPlaying".to_string()));
+      submit_lastfm(&self.http_client, lastfm, params).await?;
+    }
+    Ok(())
+  }
+
+  /// Queues the scrobble for every configured service and sends everything that is queued
+  pub async fn scrobble(&mut self, config: &ScrobblingConfig, listen: Listen) -> Result<()> {
+    let services = [
+      (Service::ListenBrainz, config.listenbrainz.is_some()),
+      (Service::LastFm, config.lastfm.is_some()),
+    ];
+    for (service, _) in services.iter().filter(|(_, is_enabled)| *is_enabled) {
+      self.queue.push(QueuedScrobble {
+        service: *service,
+        listen: listen.clone(),
+      });
+    }
+    self.flush(config).await
+  }
+
+  /// Sends the queued scrobbles in batches. The ones that fail with a transient error stay
+  /// queued, the ones that are rejected are moved to the rejected file, which comes first in the
+  /// returned error
+  pub async fn flush(&mut self, config: &ScrobblingConfig) -> Result<()> {
+    // Scrobbles of services that were removed from the config can't be sent anymore
+    self.queue.retain(|queued| match queued.service {
+      Service::ListenBrainz => config.listenbrainz.is_some(),
+      Service::LastFm => config.lastfm.is_some(),
+    });
+
+    let mut rejected = None;
+    let mut transient = None;
+    for service in &[Service::ListenBrainz, Service::LastFm] {
+      let listens = self
+        .queue
+        .iter()
+        .filter(|queued| queued.service == *service)
+        .map(|queued| &queued.listen)
+        .collect::<Vec<_>>();
+      // Sent or rejected
+      let mut done = 0;
+      for batch in listens.chunks(MAX_BATCH_SIZE) {
+        let submitted = match service {
+          Service::ListenBrainz => {
+            let config = config.listenbrainz.as_ref().unwrap();
+            let listen_type = if batch.len() == 1 { "single" } else { "import" };
+            let body = get_listenbrainz_body(listen_type, batch);
+            submit_listenbrainz(&self.http_client, config, &body).await
+          }
+          Service::LastFm => {
+            let config = config.lastfm.as_ref().unwrap();
+            let mut params = vec![("method".to_string(), "track.scrobble".to_string())];
+            for (i, listen) in batch.iter().enumerate() {
+              let index = format!("[{}]", i);
+              params.extend(get_lastfm_track_params(&index, listen));
+              params.push((
+                format!("timestamp{}", index),
+                listen.listened_at.to_string(),
+              ));
+            }
+            submit_lastfm(&self.http_client, config, params).await
+          }
+        };
+        match submitted {
+          Ok(()) => done += batch.len(),
+          Err(e) if is_transient(&e) => {
+            transient = Some(e);
+            break;
+          }
+          Err(e) => {
+            self.reject(*service, batch)?;
+            done += batch.len();
+            rejected = Some(anyhow!(
+              "{} scrobbles were rejected and kept in {}: {}",
+              batch.len(),
+              self.rejected_path.display(),
+              e
+            ));
+          }
+        }
+      }
+
+      // The scrobbles of the service are queued in order, so the done ones come first
+      let mut skipped = 0;
+      self.queue.retain(|queued| {
+        if queued.service != *service || skipped >= done {
+          return true;
+        }
+        skipped += 1;
+        false
+      });
+    }
+
+    self.save()?;
+    match rejected.or(transient) {
+      Some(e) => Err(e),
+      None => Ok(()),
+    }
+  }
+
+  fn reject(&self, service: Service, listens: &[&Listen]) -> Result<()> {
+    let mut file = OpenOptions::new()
+      .create(true)
+      .append(true)
+      .open(&self.rejected_path)?;
+    for listen in listens {
+      let rejected = QueuedScrobble {
+        service,
+        listen: (*listen).clone(),
+      };
+      writeln!(file, "{}", serde_json::to_string(&rejected)?)?;
+    }
+    Ok(())
+  }
+
+  fn save(&self) -> Result<()> {
+    if self.queue.is_empty() {
+      if self.queue_path.exists() {
+        fs::remove_file(&self.queue_path)?;
+      }
+    } else {
+      fs::write(&self.queue_path, serde_json::to_string(&self.queue)?)?;
+    }
+    Ok(())
+  }
+}
+
+/// An error Last.fm answers with a successful status, but which goes away by itself
+#[derive(Debug)]
+struct LastFmTransientError(String);
+
+impl fmt::Display for LastFmTransientError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "Last.fm: {}", self.0)
+  }
+}
+
+impl std::error::Error for LastFmTransientError {}
+
+/// Whether a failed request is worth trying again later, otherwise it is reported
+pub fn is_transient(e: &anyhow::Error) -> bool {
+  retry::get_transient_error(e).is_some() || e.downcast_ref::<LastFmTransientError>().is_some()
+}
+
+fn get_listenbrainz_body(listen_type: &str, listens: &[&Listen]) -> serde_json::Value {
+  let payload = listens
+    .iter()
+    .map(|listen| {
+      let mut item = json!({
+        "track_metadata": {
+          "artist_name": listen.artists.join(", "),
+          "track_name": listen.track,
+          "release_name": listen.album,
+          "additional_info": {
+            "duration_ms": listen.duration_ms,
+            "spotify_id": format!(
+              "https://open.spotify.com/track/{}",
+              listen.uri.rsplit(':').next().unwrap_or_default()
+            ),
+            "music_service": "spotify.com",
+            "submission_client": CLIENT_NAME,
+            "submission_client_version": env!("CARGO_PKG_VERSION"),
+          },
+        },
+      });
+      // Now playing updates have no time
+      if listen_type != "playing_now" {
+        item["listened_at"] = json!(listen.listened_at);
+      }
+      item
+    })
+    .collect::<Vec<_>>();
+  json!({ "listen_type": listen_type, "payload": payload })
+}
+
+async fn submit_listenbrainz(
+  http_client: &reqwest::Client,
+  config: &ListenBrainzConfig,
+  body: &serde_json::Value,
+) -> Result<()> {
+  let url = format!("{}/1/submit-listens", config.url.trim_end_matches('/'));
+  let response = http_client
+    .post(&url)
+    .header(AUTHORIZATION, format!("Token {}", config.token))
+    .header(CONTENT_TYPE, "application/json")
+    .body(body.to_string())
+    .send()
+    .await?;
+  if response.status().is_success() {
+    Ok(())
+  } else {
+    Err(retry::get_response_error(&response))
+  }
+}
+
+// The parameters of a track, `index` tells them apart in a batch of scrobbles
+fn get_lastfm_track_params(index: &str, listen: &Listen) -> Vec<(String, String)> {
+  vec![
+    (format!("artist{}", index), listen.artist().to_string()),
+    (format!("track{}", index), listen.track.clone()),
+    (format!("album{}", index), listen.album.clone()),
+    (
+      format!("duration{}", index),
+      (listen.duration_ms / 1000).to_string(),
+    ),
+  ]
+}
+
+// The md5 of the parameters sorted by name and the secret
+// https://www.last.fm/api/desktopauth#_6-sign-your-calls
+fn get_lastfm_signature(params: &[(String, String)], secret: &str) -> String {
+  let mut params = params.iter().collect::<Vec<_>>();
+  params.sort();
+  let mut signature = params
+    .iter()
+    .map(|(name, value)| format!("{}{}", name, value))
+    .collect::<String>();
+  signature.push_str(secret);
+  format!("{:x}", md5::compute(signature))
+}
+
+async fn submit_lastfm(
+  http_client: &reqwest::Client,
+  config: &LastFmConfig,
+  mut params: Vec<(String, String)>,
+) -> Result<()> {
+  params.push(("api_key".to_string(), config.api_key.clone()));
+  params.push(("sk".to_string(), config.session_key.clone()));
+  let signature = get_lastfm_signature(&params, &config.api_secret);
+  params.push(("api_sig".to_string(), signature));
+  params.push(("format".to_string(), "json".to_string()));
+
+  let response = http_client.post(&config.url).form(&params).send().await?;
+  if !response.status().is_success() {
+    return Err(retry::get_response_error(&response));
+  }
+  // Some errors come with a successful status
+  let body: serde_json::Value = serde_json::from_str(&response.text().await?)?;
+  match (body.get("error"), body.get("message")) {
+    (Some(code), Some(message))
+      if code
+        .as_u64()
+        .is_some_and(|code| LASTFM_TRANSIENT_ERRORS.contains(&code)) =>
+    {
+      Err(anyhow!(LastFmTransientError(message.to_string())))
+    }
+    (Some(_), Some(message)) => Err(anyhow!("Last.fm: {}", message)),
+    _ => Ok(()),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+  };
+
+  fn get_track(duration_ms: u32) -> FullTrack {
+    serde_json::from_value(json!({
+      "album": {"artists": [], "available_markets": [], "external_urls": {}, "href": null,
+        "id": null, "images": [], "name": "Album", "type": "album", "uri": null},
+      "artists": [{"external_urls": {}, "href": null, "id": null, "name": "Artist",
+        "type": "artist", "uri": null}],
+      "available_markets": [], "disc_number": 1, "duration_ms": duration_ms,
+      "explicit": false, "external_ids": {}, "external_urls": {}, "href": null, "id": "1",
+      "is_local": false, "name": "Song", "popularity": 0, "preview_url": null,
+      "track_number": 1, "type": "track", "uri": "spotify:track:1"
+    }))
+    .unwrap()
+  }
+
+  #[test]
+  fn scrobbles_after_half_the_track_or_four_minutes() {
+    let track = get_track(200_000);
+    let mut state = ScrobbleState::default();
+    let listen = Listen::new(&track, 1000);
+    assert_eq!(
+      state.update(Some(&track), true, 0, 1000),
+      Some(ScrobbleEvent::NowPlaying(listen.clone()))
+    );
+    assert_eq!(state.update(Some(&track), true, 99_000, 1099), None);
+    assert_eq!(
+      state.update(Some(&track), true, 100_000, 1100),
+      Some(ScrobbleEvent::Scrobble(listen))
+    );
+    assert_eq!(state.update(Some(&track), true, 150_000, 1150), None);
+
+    // Playing it again scrobbles it again
+    assert_eq!(
+      state.update(Some(&track), true, 0, 1200),
+      Some(ScrobbleEvent::NowPlaying(Listen::new(&track, 1200)))
+    );
+
+    let long_track = get_track(3_600_000);
+    let mut state = ScrobbleState::default();
+    state.update(Some(&long_track), true, 0, 0);
+    assert_eq!(state.update(Some(&long_track), true, 239_000, 239), None);
+    assert!(state
+      .update(Some(&long_track), true, 240_000, 240)
+      .is_some());
+
+    let short_track = get_track(20_000);
+    let mut state = ScrobbleState::default();
+    state.update(Some(&short_track), true, 0, 0);
+    assert_eq!(state.update(Some(&short_track), true, 19_000, 19), None);
+  }
+
+  #[test]
+  fn signs_lastfm_calls() {
+    let params = vec![
+      ("method".to_string(), "track.scrobble".to_string()),
+      ("api_key".to_string(), "key".to_string()),
+    ];
+    // md5 of "api_keykeymethodtrack.scrobblesecret"
+    assert_eq!(
+      get_lastfm_signature(&params, "secret"),
+      format!("{:x}", md5::compute("api_keykeymethodtrack.scrobblesecret"))
+    );
+  }
+
+  // Answers a single request with the status and body, returning the body of the request
+  async fn serve_once(listener: &mut TcpListener, status: &str, body: &str) -> String {
+    let (mut stream, _) = listener.accept().await.unwrap();
+    let mut request = vec![];
+    let mut buf = [0; 1024];
+    let body_start = loop {
+      let read = stream.read(&mut buf).await.unwrap();
+      request.extend_from_slice(&buf[..read]);
+      if let Some(i) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+        break i + 4;
+      }
+    };
+    let headers = String::from_utf8_lossy(&request[..body_start]).to_lowercase();
+    let length = headers
+      .lines()
+      .find_map(|line| line.strip_prefix("content-length: "))
+      .and_then(|length| length.trim().parse::<usize>().ok())
+      .unwrap_or(0);
+    while request.len() < body_start + length {
+      let read = stream.read(&mut buf).await.unwrap();
+      request.extend_from_slice(&buf[..read]);
+    }
+    let response = format!(
+      "HTTP/1.1 {}\r\ncontent-length: {}\r\n\r\n{}",
+      status,
+      body.len(),
+      body
+    );
+    stream.write_all(response.as_bytes()).await.unwrap();
+    String::from_utf8_lossy(&request[body_start..]).to_string()
+  }
+
+  #[tokio::test]
+  async fn queues_scrobbles_until_they_are_sent() {
+    let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let config = ScrobblingConfig {
+      listenbrainz: Some(ListenBrainzConfig {
+        token: "token".to_string(),
+        url: format!("http://{}", listener.local_addr().unwrap()),
+      }),
+      lastfm: None,
+    };
+    let queue_path =
+      std::env::temp_dir().join(format!("spt-scrobbles-{}.json", std::process::id()));
+    let rejected_path = std::env::temp_dir().join(format!(
+      "spt-rejected-scrobbles-{}.jsonl",
+      std::process::id()
+    ));
+    let mut scrobbler = Scrobbler::load(queue_path.clone(), rejected_path.clone());
+    let listen = Listen::new(&get_track(200_000), 1000);
+
+    let server = tokio::spawn(async move {
+      let failed = serve_once(&mut listener, "503 Service Unavailable", "{}").await;
+      let sent = serve_once(&mut listener, "200 OK", "{}").await;
+      (failed, sent)
+    });
+    let error = scrobbler
+      .scrobble(&config, listen.clone())
+      .await
+      .unwrap_err();
+    assert!(is_transient(&error));
+    // The queue survives a restart
+    let mut scrobbler = Scrobbler::load(queue_path.clone(), rejected_path.clone());
+    assert!(scrobbler.has_queued_scrobbles());
+    scrobbler.flush(&config).await.unwrap();
+    assert!(!scrobbler.has_queued_scrobbles());
+    assert!(!queue_path.exists());
+
+    let (_, sent) = server.await.unwrap();
+    let sent: serde_json::Value = serde_json::from_str(&sent).unwrap();
+    assert_eq!(sent["listen_type"], "single");
+    assert_eq!(sent["payload"][0]["listened_at"], 1000);
+    assert_eq!(sent["payload"][0]["track_metadata"]["track_name"], "Song");
+    assert!(!rejected_path.exists());
+  }
+
+  #[tokio::test]
+  async fn keeps_scrobbles_lastfm_can_not_take_yet() {
+    let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let config = ScrobblingConfig {
+      listenbrainz: None,
+      lastfm: Some(LastFmConfig {
+        api_key: "key".to_string(),
+        api_secret: "secret".to_string(),
+        session_key: "session".to_string(),
+        url: format!("http://{}", listener.local_addr().unwrap()),
+      }),
+    };
+    let dir = std::env::temp_dir();
+    let queue_path = dir.join(format!("spt-lastfm-queue-{}.json", std::process::id()));
+    let rejected_path = dir.join(format!("spt-lastfm-rejected-{}.jsonl", std::process::id()));
+    let mut scrobbler = Scrobbler::load(queue_path.clone(), rejected_path.clone());
+
+    let server = tokio::spawn(async move {
+      let body = r#"{"error":29,"message":"Rate Limit Exceeded"}"#;
+      serve_once(&mut listener, "200 OK", body).await;
+    });
+    let listen = Listen::new(&get_track(200_000), 1000);
+    let error = scrobbler.scrobble(&config, listen).await.unwrap_err();
+    server.await.unwrap();
+    assert!(is_transient(&error));
+    assert!(scrobbler.has_queued_scrobbles());
+    assert!(!rejected_path.exists());
+    fs::remove_file(&queue_path).unwrap();
+  }
+
+  #[tokio::test]
+  async fn moves_rejected_scrobbles_out_of_the_queue() {
+    let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let config = ScrobblingConfig {
+      listenbrainz: Some(ListenBrainzConfig {
+        token: "token".to_string(),
+        url: format!("http://{}", listener.local_addr().unwrap()),
+      }),
+      lastfm: None,
+    };
+    let dir = std::env::temp_dir();
+    let queue_path = dir.join(format!("spt-rejecting-{}.json", std::process::id()));
+    let rejected_path = dir.join(format!("spt-rejected-{}.jsonl", std::process::id()));
+    let mut scrobbler = Scrobbler::load(queue_path.clone(), rejected_path.clone());
+    let listen = Listen::new(&get_track(200_000), 1000);
+
+    let server = tokio::spawn(async move {
+      serve_once(&mut listener, "400 Bad Request", "{}").await;
+    });
+    let error = scrobbler
+      .scrobble(&config, listen.clone())
+      .await
+      .unwrap_err();
+    assert!(!is_transient(&error));
+    server.await.unwrap();
+    assert!(!scrobbler.has_queued_scrobbles());
+
+    let rejected = fs::read_to_string(&rejected_path).unwrap();
+    fs::remove_file(&rejected_path).unwrap();
+    let rejected: QueuedScrobble = serde_json::from_str(rejected.trim()).unwrap();
+    assert_eq!(rejected.listen, listen);
+  }
+}
@@ -0,0 +1,339 @@
+use anyhow::{anyhow, Result};
+use rspotify::model::{context::CurrentlyPlaybackContext, PlayingItem};
+use std::time::{Duration, Instant};
+
+use crate::app::get_playing_item_uri;
+
+// The volume goes down over the end of the timer, in steps to not send a request every tick
+const FADE_DURATION: Duration = Duration::from_secs(30);
+const FADE_STEPS: u32 = 10;
+// Playback is paused this early at the end of a track, so the next one doesn't start
+const END_OF_TRACK_MARGIN: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SleepMode {
+  After(Duration),
+  EndOfTrack,
+  EndOfAlbum,
+}
+
+impl SleepMode {
+  /// Parses `track`, `album` or a duration like `30` (minutes), `45s`, `20m` or `1h30m`
+  pub fn parse(mode: &str) -> Option<Self> {
+    match mode.trim() {
+      "track" => Some(SleepMode::EndOfTrack),
+      "album" => Some(SleepMode::EndOfAlbum),
+      duration => parse_duration(duration).map(SleepMode::After),
+    }
+  }
+}
+
+fn parse_duration(duration: &str) -> Option<Duration> {
+  // A timer that is already over is rejected like any other bad duration
+  if let Ok(minutes) = duration.parse::<u64>() {
+    return Some(Duration::from_secs(minutes * 60)).filter(|_| minutes > 0);
+  }
+  let mut seconds = 0;
+  let mut number = String::new();
+  for c in duration.chars() {
+    if c.is_ascii_digit() {
+      number.push(c);
+      continue;
+    }
+    let unit = match c {
+      'h' => 60 * 60,
+      'm' => 60,
+      's' => 1,
+      _ => return None,
+    };
+    seconds += number.parse::<u64>().ok()? * unit;
+    number.clear();
+  }
+  if number.is_empty() && seconds > 0 {
+    Some(Duration::from_secs(seconds))
+  } else {
+    None
+  }
+}
+
+fn format_duration(duration: Duration) -> String {
+  let seconds = duration.as_secs();
+  if seconds >= 60 * 60 {
+    format!(
+      "{}:{:02}:{:02}",
+      seconds / 60 / 60,
+      seconds / 60 % 60,
+      seconds % 60
+    )
+  } else {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+  }
+}
+
+// The album of a track, or the show of an episode
+fn get_album_uri(item: &PlayingItem) -> Option<&str> {
+  match item {
+    PlayingItem::Track(track) => track.album.uri.as_deref(),
+    PlayingItem::Episode(episode) => Some(&episode.show.uri),
+  }
+}
+
+fn get_duration_ms(item: &PlayingItem) -> u32 {
+  match item {
+    PlayingItem::Track(track) => track.duration_ms,
+    PlayingItem::Episode(episode) => episode.duration_ms,
+  }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SleepAction {
+  SetVolume(u8),
+  // Pauses if it is playing and sets the volume back to what it was before the fade
+  Stop { pause: bool, volume: Option<u8> },
+}
+
+pub struct SleepTimer {
+  pub mode: SleepMode,
+  ends_at: Instant,
+  // The item or album the timer waits for the end of
+  item_uri: Option<String>,
+  album_uri: Option<String>,
+  // Set when the fade starts
+  volume_before_fade: Option<u8>,
+  faded_volume: Option<u8>,
+}
+
+impl SleepTimer {
+  pub fn new(
+    mode: SleepMode,
+    context: Option<&CurrentlyPlaybackContext>,
+    now: Instant,
+  ) -> Result<Self> {
+    let item = context.and_then(|context| context.item.as_ref());
+    let (item_uri, album_uri) = match (mode, item) {
+      (SleepMode::After(_), _) => (None, None),
+      (_, Some(item)) => (
+        Some(get_playing_item_uri(item).to_string()),
+        get_album_uri(item).map(String::from),
+      ),
+      (_, None) => return Err(anyhow!("nothing is playing to wait for the end of")),
+    };
+    let ends_at = match mode {
+      SleepMode::After(duration) => now + duration,
+      _ => now,
+    };
+    Ok(SleepTimer {
+      mode,
+      ends_at,
+      item_uri,
+      album_uri,
+      volume_before_fade: None,
+      faded_volume: None,
+    })
+  }
+
+  /// The volume to set back when the timer is cancelled during the fade
+  pub fn get_volume_before_fade(&self) -> Option<u8> {
+    self.volume_before_fade
+  }
+
+  // The time until playback stops, `None` until the end of the album is known
+  fn get_remaining(
+    &self,
+    context: Option<&CurrentlyPlaybackContext>,
+    progress_ms: u32,
+    next_item: Option<&PlayingItem>,
+    now: Instant,
+  ) -> Option<Duration> {
+    let item = context.and_then(|context| context.item.as_ref());
+    let remaining_of_item = |item: &PlayingItem| {
+      let remaining =
+        Duration::from_millis(get_duration_ms(item).saturating_sub(progress_ms).into());
+      remaining
+        .checked_sub(END_OF_TRACK_MARGIN)
+        .unwrap_or_default()
+    };
+    match (self.mode, item) {
+      (SleepMode::After(_), _) => Some(self.ends_at.saturating_duration_since(now)),
+      (SleepMode::EndOfTrack, Some(item))
+        if Some(get_playing_item_uri(item)) == self.item_uri.as_deref() =>
+      {
+        Some(remaining_of_item(item))
+      }
+      (SleepMode::EndOfAlbum, Some(item)) if get_album_uri(item) == self.album_uri.as_deref() => {
+        // The item is the last of the album when the queue goes on with another one, or
+        // doesn't go on at all
+        if next_item.is_some_and(|next| get_album_uri(next) == self.album_uri.as_deref()) {
+          None
+        } else {
+          Some(remaining_of_item(item))
+        }
+      }
+      // Another item plays already
+      _ => Some(Duration::default()),
+    }
+  }
+
+  /// Moves the timer on with the playback, `progress_ms` is the progress of the playing item
+  /// and `next_item` the first item of the queue
+  pub fn update(
+    &mut self,
+    context: Option<&CurrentlyPlaybackContext>,
+    progress_ms: u32,
+    next_item: Option<&PlayingItem>,
+    now: Instant,
+  ) -> Option<SleepAction> {
+    let remaining = self.get_remaining(context, progress_ms, next_item, now)?;
+    let is_playing = context.is_some_and(|context| context.is_playing);
+    if remaining == Duration::default() {
+      return Some(SleepAction::Stop {
+        pause: is_playing,
+        volume: self.volume_before_fade,
+      });
+    }
+
+    if is_playing && remaining < FADE_DURATION {
+      let volume = context.map(|context| context.device.volume_percent as u8)?;
+      let volume_before_fade = match self.volume_before_fade {
+        Some(volume_before_fade) => volume_before_fade,
+        None => {
+          self.volume_before_fade = Some(volume);
+          self.faded_volume = Some(volume);
+          volume
+        }
+      };
+      let step =
+        (remaining.as_millis() * u128::from(FADE_STEPS) / FADE_DURATION.as_millis()) as u32 + 1;
+      let faded_volume = (u32::from(volume_before_fade) * step / FADE_STEPS) as u8;
+      if self.faded_volume != Some(faded_volume) {
+        self.faded_volume = Some(faded_volume);
+        return Some(SleepAction::SetVolume(faded_volume));
+      }
+    }
+    None
+  }
+
+  /// What is shown in the playbar, e.g. "12:30" or "end of track"
+  pub fn get_label(&self, now: Instant) -> String {
+    match self.mode {
+      SleepMode::After(_) => format_duration(self.ends_at.saturating_duration_since(now)),
+      SleepMode::EndOfTrack => "end of track".to_string(),
+      SleepMode::EndOfAlbum => "end of album".to_string(),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn get_context(uri: &str, album_uri: &str, volume: u32) -> CurrentlyPlaybackContext {
+    serde_json::from_value(json!({
+      "device": {"id": "1", "is_active": true, "is_restricted": false, "name": "Speaker",
+        "type": "Speaker", "volume_percent": volume},
+      "repeat_state": "off", "shuffle_state": false, "context": null, "timestamp": 0,
+      "progress_ms": 0, "is_playing": true, "currently_playing_type": "track",
+      "actions": {"disallows": {}},
+      "item": get_track(uri, album_uri),
+    }))
+    .unwrap()
+  }
+
+  fn get_track(uri: &str, album_uri: &str) -> serde_json::Value {
+    json!({
+      "album": {"artists": [], "available_markets": [], "external_urls": {}, "href": null,
+        "id": null, "images": [], "name": "Album", "type": "album", "uri": album_uri},
+      "artists": [], "available_markets": [], "disc_number": 1, "duration_ms": 200_000,
+      "explicit": false, "external_ids": {}, "external_urls": {}, "href": null, "id": null,
+      "is_local": false, "name": "Song", "popularity": 0, "preview_url": null,
+      "track_number": 1, "type": "track", "uri": uri
+    })
+  }
+
+  #[test]
+  fn parses_modes() {
+    assert_eq!(SleepMode::parse("track"), Some(SleepMode::EndOfTrack));
+    assert_eq!(
+      SleepMode::parse("30"),
+      Some(SleepMode::After(Duration::from_secs(30 * 60)))
+    );
+    assert_eq!(
+      SleepMode::parse("1h30m"),
+      Some(SleepMode::After(Duration::from_secs(90 * 60)))
+    );
+    assert_eq!(
+      SleepMode::parse("45s"),
+      Some(SleepMode::After(Duration::from_secs(45)))
+    );
+    assert_eq!(SleepMode::parse("1h30"), None);
+    assert_eq!(SleepMode::parse("0"), None);
+    assert_eq!(SleepMode::parse("0m"), None);
+    assert_eq!(SleepMode::parse("soon"), None);
+  }
+
+  #[test]
+  fn fades_out_then_stops() {
+    let start = Instant::now();
+    let context = get_context("spotify:track:1", "spotify:album:1", 50);
+    let mode = SleepMode::After(Duration::from_secs(60));
+    let mut timer = SleepTimer::new(mode, Some(&context), start).unwrap();
+    assert_eq!(timer.get_label(start), "1:00");
+
+    let at = |seconds: f64| start + Duration::from_secs_f64(seconds);
+    assert_eq!(timer.update(Some(&context), 0, None, at(20.0)), None);
+    assert_eq!(timer.update(Some(&context), 0, None, at(31.0)), None);
+    assert_eq!(
+      timer.update(Some(&context), 0, None, at(44.0)),
+      Some(SleepAction::SetVolume(30))
+    );
+    assert_eq!(timer.update(Some(&context), 0, None, at(44.5)), None);
+    assert_eq!(
+      timer.update(Some(&context), 0, None, at(60.0)),
+      Some(SleepAction::Stop {
+        pause: true,
+        volume: Some(50)
+      })
+    );
+  }
+
+  #[test]
+  fn waits_for_the_end_of_the_track_or_album() {
+    let start = Instant::now();
+    let context = get_context("spotify:track:1", "spotify:album:1", 50);
+    let mut timer = SleepTimer::new(SleepMode::EndOfTrack, Some(&context), start).unwrap();
+    assert_eq!(timer.update(Some(&context), 100_000, None, start), None);
+    assert_eq!(
+      timer.update(Some(&context), 199_500, None, start),
+      Some(SleepAction::Stop {
+        pause: true,
+        volume: None
+      })
+    );
+
+    let mut timer = SleepTimer::new(SleepMode::EndOfAlbum, Some(&context), start).unwrap();
+    let same_album =
+      serde_json::from_value(get_track("spotify:track:2", "spotify:album:1")).unwrap();
+    let other_album =
+      serde_json::from_value(get_track("spotify:track:3", "spotify:album:2")).unwrap();
+    let next_item = PlayingItem::Track(same_album);
+    assert_eq!(
+      timer.update(Some(&context), 199_500, Some(&next_item), start),
+      None
+    );
+    let next_item = PlayingItem::Track(other_album);
+    assert!(matches!(
+      timer.update(Some(&context), 199_500, Some(&next_item), start),
+      Some(SleepAction::Stop { .. })
+    ));
+
+    // Nothing comes after the last track of the album without autoplay
+    let mut timer = SleepTimer::new(SleepMode::EndOfAlbum, Some(&context), start).unwrap();
+    assert!(matches!(
+      timer.update(Some(&context), 199_500, None, start),
+      Some(SleepAction::Stop { .. })
+    ));
+
+    assert!(SleepTimer::new(SleepMode::EndOfTrack, None, start).is_err());
+  }
+}
@@ -0,0 +1,320 @@
+use crate::app::{ActiveBlock, App, TrackTableContext};
+use crate::ui::util::create_artist_string;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, collections::BTreeMap, fs, path::PathBuf};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+  Title,
+  Artist,
+  Album,
+  Duration,
+  DateAdded,
+  Popularity,
+}
+
+impl SortKey {
+  fn name(self) -> &'static str {
+    match self {
+      SortKey::Title => "title",
+      SortKey::Artist => "artist",
+      SortKey::Album => "album",
+      SortKey::Duration => "duration",
+      SortKey::DateAdded => "date added",
+      SortKey::Popularity => "popularity",
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+  Ascending,
+  Descending,
+}
+
+/// How the rows of a table are ordered, tables without one keep the order of the api
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TableSort {
+  pub key: SortKey,
+  pub order: SortOrder,
+}
+
+// Rows without a value for the key, e.g. tracks without a date, go first
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum SortValue {
+  Missing,
+  Number(i64),
+  Text(String),
+}
+
+fn text(value: &str) -> SortValue {
+  SortValue::Text(value.to_lowercase())
+}
+
+/// The name the sort of the table is kept under in the sort file, `None` if it can't be sorted
+pub fn get_context(app: &App, block: ActiveBlock) -> Option<&'static str> {
+  match block {
+    ActiveBlock::TrackTable => app
+      .track_table
+      .context
+      .as_ref()
+      .map(|context| match context {
+        TrackTableContext::MyPlaylists => "my_playlists",
+        TrackTableContext::AlbumSearch => "album_search",
+        TrackTableContext::PlaylistSearch => "playlist_search",
+        TrackTableContext::SavedTracks => "saved_tracks",
+        TrackTableContext::RecommendedTracks => "recommended_tracks",
+        TrackTableContext::MadeForYou => "made_for_you",
+      }),
+    ActiveBlock::AlbumList => Some("saved_albums"),
+    ActiveBlock::EpisodeTable => Some("show_episodes"),
+    _ => None,
+  }
+}
+
+/// The sort of the tables in each context, see `get_context`. It is kept in a state file of its
+/// own, as writing it to the config would drop the comments and the key order of the user
+#[derive(Default)]
+pub struct TableSorts {
+  path: Option<PathBuf>,
+  sorts: BTreeMap<String, TableSort>,
+}
+
+impl TableSorts {
+  /// Reads the sorts at `path`, a missing or outdated file gives no sorts
+  pub fn load(path: PathBuf) -> TableSorts {
+    let sorts = fs::read_to_string(&path)
+      .ok()
+      .and_then(|content| serde_json::from_str(&content).ok())
+      .unwrap_or_default();
+    TableSorts {
+      path: Some(path),
+      sorts,
+    }
+  }
+
+  /// Sets the sort of the context, `None` for the order of the api, and writes the sorts
+  pub fn set(&mut self, context: &str, sort: Option<TableSort>) -> Result<()> {
+    match sort {
+      Some(sort) => self.sorts.insert(context.to_string(), sort),
+      None => self.sorts.remove(context),
+    };
+    if let Some(path) = &self.path {
+      fs::write(path, serde_json::to_string(&self.sorts)?)?;
+    }
+    Ok(())
+  }
+}
+
+pub fn get_sort(app: &App, block: ActiveBlock) -> Option<TableSort> {
+  let context = get_context(app, block)?;
+  app.table_sorts.sorts.get(context).copied()
+}
+
+// The keys the rows of a table can be sorted by
+fn get_keys(app: &App, block: ActiveBlock) -> Vec<SortKey> {
+  match block {
+    ActiveBlock::TrackTable => {
+      let mut keys = vec![
+        SortKey::Title,
+        SortKey::Artist,
+        SortKey::Album,
+        SortKey::Duration,
+        SortKey::Popularity,
+      ];
+      if get_track_dates(app).is_some() {
+        keys.insert(4, SortKey::DateAdded);
+      }
+      keys
+    }
+    ActiveBlock::AlbumList => vec![
+      SortKey::Title,
+      SortKey::Artist,
+      SortKey::DateAdded,
+      SortKey::Popularity,
+    ],
+    ActiveBlock::EpisodeTable => vec![SortKey::Title, SortKey::Duration],
+    _ => vec![],
+  }
+}
+
+/// The sort that follows `sort`: every key ascending then descending, then the api order
+pub fn get_next_sort(app: &App, block: ActiveBlock, sort: Option<TableSort>) -> Option<TableSort> {
+  let keys = get_keys(app, block);
+  let next_key = |key| {
+    let position = keys.iter().position(|&k| k == key)?;
+    keys.get(position + 1).copied()
+  };
+  let (key, order) = match sort {
+    None => (keys.first().copied(), SortOrder::Ascending),
+    Some(TableSort {
+      key,
+      order: SortOrder::Ascending,
+    }) if keys.contains(&key) => (Some(key), SortOrder::Descending),
+    Some(TableSort { key, .. }) => (next_key(key), SortOrder::Ascending),
+  };
+  key.map(|key| TableSort { key, order })
+}
+
+// When the tracks were added, only known for playlists and liked songs
+fn get_track_dates(app: &App) -> Option<Vec<i64>> {
+  let dates: Vec<i64> = match app.track_table.context {
+    Some(TrackTableContext::MyPlaylists) => app
+      .playlist_tracks
+      .as_ref()?
+      .items
+      .iter()
+      .filter(|item| item.track.is_some())
+      .map(|item| item.added_at.timestamp())
+      .collect(),
+    Some(TrackTableContext::SavedTracks) => app
+      .library
+      .saved_tracks
+      .get_results(None)?
+      .items
+      .iter()
+      .map(|item| item.added_at.timestamp())
+      .collect(),
+    _ => return None,
+  };
+  // The dates belong to another page while the table is being replaced
+  if dates.len() == app.track_table.tracks.len() {
+    Some(dates)
+  } else {
+    None
+  }
+}
+
+fn get_values(app: &App, block: ActiveBlock, key: SortKey) -> Vec<SortValue> {
+  match block {
+    ActiveBlock::TrackTable => {
+      let dates = get_track_dates(app);
+      app
+        .track_table
+        .tracks
+        .iter()
+        .enumerate()
+        .map(|(index, track)| match key {
+          SortKey::Title => text(&track.name),
+          SortKey::Artist => text(&create_artist_string(&track.artists)),
+          SortKey::Album => text(&track.album.name),
+          SortKey::Duration => SortValue::Number(i64::from(track.duration_ms)),
+          SortKey::Popularity => SortValue::Number(i64::from(track.popularity)),
+          SortKey::DateAdded => match &dates {
+            Some(dates) => SortValue::Number(dates[index]),
+            None => SortValue::Missing,
+          },
+        })
+        .collect()
+    }
+    ActiveBlock::AlbumList => match app.library.saved_albums.get_results(None) {
+      Some(albums) => albums
+        .items
+        .iter()
+        .map(|saved| match key {
+          SortKey::Title | SortKey::Album => text(&saved.album.name),
+          SortKey::Artist => text(&create_artist_string(&saved.album.artists)),
+          SortKey::DateAdded => SortValue::Number(saved.added_at.timestamp()),
+          SortKey::Popularity => SortValue::Number(i64::from(saved.album.popularity)),
+          SortKey::Duration => SortValue::Missing,
+        })
+        .collect(),
+      None => vec![],
+    },
+    ActiveBlock::EpisodeTable => match app.library.show_episodes.get_results(None) {
+      Some(episodes) => episodes
+        .items
+        .iter()
+        .map(|episode| match key {
+          SortKey::Duration => SortValue::Number(i64::from(episode.duration_ms)),
+          _ => text(&episode.name),
+        })
+        .collect(),
+      None => vec![],
+    },
+    _ => vec![],
+  }
+}
+
+/// Orders the indices of rows of the table by its sort
+pub fn sort_indices(app: &App, block: ActiveBlock, indices: &mut [usize]) {
+  if let Some(sort) = get_sort(app, block) {
+    let values = get_values(app, block, sort.key);
+    let compare = |a: &usize, b: &usize| match (values.get(*a), values.get(*b)) {
+      (Some(a), Some(b)) => a.cmp(b),
+      _ => Ordering::Equal,
+    };
+    match sort.order {
+      SortOrder::Ascending => indices.sort_by(compare),
+      SortOrder::Descending => indices.sort_by(|a, b| compare(b, a)),
+    }
+  }
+}
+
+/// Describes the sort in the title of the table
+pub fn get_title(app: &App, block: ActiveBlock, title: &str) -> String {
+  match get_sort(app, block) {
+    Some(sort) => format!(
+      "{} (by {} {})",
+      title,
+      sort.key.name(),
+      match sort.order {
+        SortOrder::Ascending => "▲",
+        SortOrder::Descending => "▼",
+      }
+    ),
+    None => title.to_string(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn cycles_through_keys_and_orders() {
+    let app = App::default();
+    let ascending = |key| TableSort {
+      key,
+      order: SortOrder::Ascending,
+    };
+    let descending = |key| TableSort {
+      key,
+      order: SortOrder::Descending,
+    };
+    let block = ActiveBlock::EpisodeTable;
+
+    let sort = get_next_sort(&app, block, None);
+    assert_eq!(sort, Some(ascending(SortKey::Title)));
+    let sort = get_next_sort(&app, block, sort);
+    assert_eq!(sort, Some(descending(SortKey::Title)));
+    let sort = get_next_sort(&app, block, sort);
+    assert_eq!(sort, Some(ascending(SortKey::Duration)));
+    let sort = get_next_sort(&app, block, Some(descending(SortKey::Duration)));
+    assert_eq!(sort, None);
+    // A key that does not apply to the table goes back to the order of the api
+    let sort = get_next_sort(&app, block, Some(ascending(SortKey::Popularity)));
+    assert_eq!(sort, None);
+  }
+
+  #[test]
+  fn sorts_survive_a_restart() {
+    let path = std::env::temp_dir().join(format!("spt-sort-test-{}.json", std::process::id()));
+    let sort = TableSort {
+      key: SortKey::DateAdded,
+      order: SortOrder::Descending,
+    };
+    let mut sorts = TableSorts::load(path.clone());
+    sorts.set("saved_tracks", Some(sort)).unwrap();
+    sorts.set("saved_albums", Some(sort)).unwrap();
+    sorts.set("saved_albums", None).unwrap();
+
+    let sorts = TableSorts::load(path.clone());
+    fs::remove_file(path).unwrap();
+    assert_eq!(sorts.sorts.get("saved_tracks"), Some(&sort));
+    assert_eq!(sorts.sorts.get("saved_albums"), None);
+  }
+}
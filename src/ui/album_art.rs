@@ -0,0 +1,90 @@
+use crate::album_art::{self, GraphicsProtocol, Placement};
+use crate::app::App;
+use image::{imageops::FilterType, RgbImage};
+use tui::{
+  backend::Backend,
+  buffer::Buffer,
+  layout::Rect,
+  style::Color,
+  widgets::{Clear, Widget},
+  Frame,
+};
+
+// Draws two pixels in each cell, the upper one with the foreground color of `▀`
+struct HalfBlocks<'a> {
+  image: &'a RgbImage,
+}
+
+impl<'a> Widget for HalfBlocks<'a> {
+  fn render(self, area: Rect, buf: &mut Buffer) {
+    if area.width == 0 || area.height == 0 {
+      return;
+    }
+    let image = image::imageops::resize(
+      self.image,
+      u32::from(area.width),
+      u32::from(area.height) * 2,
+      FilterType::Triangle,
+    );
+    let color = |x: u16, y: u16| {
+      let pixel = image.get_pixel(u32::from(x), u32::from(y));
+      Color::Rgb(pixel[0], pixel[1], pixel[2])
+    };
+    for y in 0..area.height {
+      for x in 0..area.width {
+        buf
+          .get_mut(area.x + x, area.y + y)
+          .set_symbol("▀")
+          .set_fg(color(x, y * 2))
+          .set_bg(color(x, y * 2 + 1));
+      }
+    }
+  }
+}
+
+/// Draws the cover at the top left of the area, if it has been loaded
+pub fn draw<B>(f: &mut Frame<B>, app: &App, url: Option<&str>, area: Rect)
+where
+  B: Backend,
+{
+  let area = album_art::fit(area);
+  let (url, image) = match url.and_then(|url| Some((url, app.album_art.get(url)?))) {
+    Some(loaded) => loaded,
+    None => return,
+  };
+  match album_art::get_protocol(app) {
+    GraphicsProtocol::HalfBlocks => f.render_widget(HalfBlocks { image }, area),
+    // The terminal draws the image over these cells once the frame is done
+    GraphicsProtocol::Kitty | GraphicsProtocol::Sixel => {
+      f.render_widget(Clear, area);
+      app.album_art.placements.borrow_mut().push(Placement {
+        area,
+        url: url.to_string(),
+      });
+    }
+  }
+}
+
+/// Splits a square for the cover off the left of the area, returning it and the rest
+pub fn split(app: &App, area: Rect, max_width: u16) -> (Option<Rect>, Rect) {
+  if !album_art::is_shown(app) {
+    return (None, area);
+  }
+  let art = album_art::fit(Rect {
+    width: area.width.min(max_width),
+    ..area
+  });
+  if art.width == 0 {
+    return (None, area);
+  }
+  // Leaves a column between the cover and the rest
+  let offset = (art.width + 1).min(area.width);
+  (
+    Some(art),
+    Rect {
+      x: area.x + offset,
+      width: area.width - offset,
+      ..area
+    },
+  )
+}
@@ -3,7 +3,6 @@ use crate::app::App;
 use tui::{
   backend::Backend,
   layout::{Constraint, Direction, Layout},
-  style::Style,
   text::{Span, Spans},
   widgets::{BarChart, Block, Borders, Paragraph},
   Frame,
@@ -25,15 +24,12 @@ where
     .split(f.size());
 
   let analysis_block = Block::default()
-    .title(Span::styled(
-      "Analysis",
-      Style::default().fg(app.user_config.theme.inactive),
-    ))
+    .title(Span::styled("Analysis", app.user_config.theme.inactive))
     .borders(Borders::ALL)
-    .border_style(Style::default().fg(app.user_config.theme.inactive));
+    .border_style(app.user_config.theme.inactive);
 
-  let white = Style::default().fg(app.user_config.theme.text);
-  let gray = Style::default().fg(app.user_config.theme.inactive);
+  let white = app.user_config.theme.text;
+  let gray = app.user_config.theme.inactive;
   let width = (chunks[1].width) as f32 / (1 + PITCHES.len()) as f32;
   let tick_rate = app.user_config.behavior.tick_rate_milliseconds;
   let bar_chart_title = &format!("Pitches | Tick Rate {} {}FPS", tick_rate, 1000 / tick_rate);
@@ -47,12 +43,12 @@ where
   let empty_analysis_block = || {
     Paragraph::new("No analysis available")
       .block(analysis_block.clone())
-      .style(Style::default().fg(app.user_config.theme.text))
+      .style(app.user_config.theme.text)
   };
   let empty_pitches_block = || {
     Paragraph::new("No pitch information available")
       .block(bar_chart_block.clone())
-      .style(Style::default().fg(app.user_config.theme.text))
+      .style(app.user_config.theme.text)
   };
 
   if let Some(analysis) = &app.audio_analysis {
@@ -95,7 +91,7 @@ where
       ];
       let p = Paragraph::new(texts)
         .block(analysis_block)
-        .style(Style::default().fg(app.user_config.theme.text));
+        .style(app.user_config.theme.text);
       f.render_widget(p, chunks[0]);
 
       let data: Vec<(&str, u64)> = segment
@@ -118,12 +114,12 @@ where
         .block(bar_chart_block)
         .data(&data)
         .bar_width(width as u16)
-        .bar_style(Style::default().fg(app.user_config.theme.analysis_bar))
-        .value_style(
-          Style::default()
-            .fg(app.user_config.theme.analysis_bar_text)
-            .bg(app.user_config.theme.analysis_bar),
-        );
+        .bar_style(app.user_config.theme.analysis_bar)
+        .value_style(match app.user_config.theme.analysis_bar.fg {
+          // The values are drawn on the bars
+          Some(bar_color) => app.user_config.theme.analysis_bar_text.bg(bar_color),
+          None => app.user_config.theme.analysis_bar_text,
+        });
       f.render_widget(analysis_bar, chunks[1]);
     } else {
       f.render_widget(empty_analysis_block(), chunks[0]);
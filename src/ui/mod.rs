@@ -1,20 +1,24 @@
+pub mod album_art;
 pub mod audio_analysis;
 pub mod help;
 pub mod util;
 use super::{
   app::{
-    ActiveBlock, AlbumTableContext, App, ArtistBlock, EpisodeTableContext, RecommendationsContext,
-    RouteId, SearchResultBlock, LIBRARY_OPTIONS,
+    ActiveBlock, AlbumTableContext, App, ArtistBlock, DialogContext, EpisodeTableContext,
+    NetworkStatus, RecommendationsContext, RouteId, SearchResultBlock, LIBRARY_OPTIONS,
   },
   banner::BANNER,
+  command_palette, filter, handlers,
+  history::Count,
 };
 use help::get_help_docs;
 use rspotify::model::show::ResumePoint;
 use rspotify::model::PlayingItem;
 use rspotify::senum::RepeatState;
+use std::time::Instant;
 use tui::{
   backend::Backend,
-  layout::{Alignment, Constraint, Direction, Layout, Rect},
+  layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
   style::{Modifier, Style},
   text::{Span, Spans, Text},
   widgets::{Block, Borders, Clear, Gauge, List, ListItem, ListState, Paragraph, Row, Table, Wrap},
@@ -35,6 +39,7 @@ pub enum TableId {
   RecentlyPlayed,
   MadeForYou,
   PodcastEpisodes,
+  Queue,
 }
 
 #[derive(PartialEq)]
@@ -88,7 +93,7 @@ where
   let format_row =
     |r: Vec<String>| -> Vec<String> { vec![format!("{:50}{:40}{:20}", r[0], r[1], r[2])] };
 
-  let help_menu_style = Style::default().fg(app.user_config.theme.text);
+  let help_menu_style = app.user_config.theme.text;
   let header = ["Description", "Event", "Context"];
   let header = format_row(header.iter().map(|s| s.to_string()).collect());
 
@@ -120,12 +125,10 @@ where
   f.render_widget(help_menu, chunks[0]);
 }
 
-pub fn draw_input_and_help_box<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
-where
-  B: Backend,
-{
+// Returns the areas of the search input and the help box
+pub fn get_input_and_help_chunks(app: &App, layout_chunk: Rect) -> Vec<Rect> {
   // Check for the width and change the contraints accordingly
-  let chunks = Layout::default()
+  Layout::default()
     .direction(Direction::Horizontal)
     .constraints(
       if app.size.width >= SMALL_TERMINAL_WIDTH && !app.user_config.behavior.enforce_wide_search_bar
@@ -135,7 +138,14 @@ where
         [Constraint::Percentage(90), Constraint::Percentage(10)].as_ref()
       },
     )
-    .split(layout_chunk);
+    .split(layout_chunk)
+}
+
+pub fn draw_input_and_help_box<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+where
+  B: Backend,
+{
+  let chunks = get_input_and_help_chunks(app, layout_chunk);
 
   let current_route = app.get_current_route();
 
@@ -158,28 +168,51 @@ where
   f.render_widget(input, chunks[0]);
 
   let show_loading = app.is_loading && app.user_config.behavior.show_loading_indicator;
-  let help_block_text = if show_loading {
+  let pending_text = handlers::get_pending_text(app);
+  // Failing requests are shown here instead of the error screen while they are retried
+  let network_text = match &app.network_status {
+    NetworkStatus::Online => None,
+    NetworkStatus::Retrying(_) => Some("Retrying...".to_string()),
+    NetworkStatus::Offline {
+      queued_actions: 0, ..
+    } => Some("Offline".to_string()),
+    NetworkStatus::Offline { queued_actions, .. } => {
+      Some(format!("Offline, {} queued", queued_actions))
+    }
+  };
+  let help_block_text = if let Some(pending_text) = &pending_text {
+    (app.user_config.theme.hint, pending_text.as_str())
+  } else if let Some(network_text) = &network_text {
+    (app.user_config.theme.error_text, network_text.as_str())
+  } else if let Some(status_message) = &app.status_message {
+    (app.user_config.theme.hint, status_message.as_str())
+  } else if show_loading {
     (app.user_config.theme.hint, "Loading...")
   } else {
     (app.user_config.theme.inactive, "Type ?")
   };
 
   let block = Block::default()
-    .title(Span::styled("Help", Style::default().fg(help_block_text.0)))
+    .title(Span::styled("Help", help_block_text.0))
     .borders(Borders::ALL)
-    .border_style(Style::default().fg(help_block_text.0));
+    .border_style(help_block_text.0);
 
   let lines = Text::from(help_block_text.1);
-  let help = Paragraph::new(lines)
-    .block(block)
-    .style(Style::default().fg(help_block_text.0));
+  let help = Paragraph::new(lines).block(block).style(help_block_text.0);
   f.render_widget(help, chunks[1]);
 }
 
-pub fn draw_main_layout<B>(f: &mut Frame<B>, app: &App)
-where
-  B: Backend,
-{
+// The areas of the blocks on the main screen. These are shared between drawing and the mouse
+// handler, so that clicks land on what is shown
+pub struct MainLayout {
+  pub search: Rect,
+  pub library: Rect,
+  pub playlists: Rect,
+  pub routes: Rect,
+  pub playbar: Rect,
+}
+
+pub fn get_main_layout(app: &App, size: Rect) -> MainLayout {
   let margin = util::get_main_layout_margin(app);
   // Responsive layout: new one kicks in at width 150 or higher
   if app.size.width >= SMALL_TERMINAL_WIDTH && !app.user_config.behavior.enforce_wide_search_bar {
@@ -187,13 +220,29 @@ where
       .direction(Direction::Vertical)
       .constraints([Constraint::Min(1), Constraint::Length(6)].as_ref())
       .margin(margin)
-      .split(f.size());
+      .split(size);
 
-    // Nested main block with potential routes
-    draw_routes(f, app, parent_layout[0]);
+    let route_chunks = get_route_chunks(parent_layout[0]);
 
-    // Currently playing
-    draw_playbar(f, app, parent_layout[1]);
+    let user_chunks = Layout::default()
+      .direction(Direction::Vertical)
+      .constraints(
+        [
+          Constraint::Length(3),
+          Constraint::Percentage(30),
+          Constraint::Percentage(70),
+        ]
+        .as_ref(),
+      )
+      .split(route_chunks[0]);
+
+    MainLayout {
+      search: user_chunks[0],
+      library: user_chunks[1],
+      playlists: user_chunks[2],
+      routes: route_chunks[1],
+      playbar: parent_layout[1],
+    }
   } else {
     let parent_layout = Layout::default()
       .direction(Direction::Vertical)
@@ -206,71 +255,106 @@ where
         .as_ref(),
       )
       .margin(margin)
-      .split(f.size());
-
-    // Search input and help
-    draw_input_and_help_box(f, app, parent_layout[0]);
+      .split(size);
 
-    // Nested main block with potential routes
-    draw_routes(f, app, parent_layout[1]);
+    let route_chunks = get_route_chunks(parent_layout[1]);
 
-    // Currently playing
-    draw_playbar(f, app, parent_layout[2]);
+    let user_chunks = Layout::default()
+      .direction(Direction::Vertical)
+      .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
+      .split(route_chunks[0]);
+
+    MainLayout {
+      search: parent_layout[0],
+      library: user_chunks[0],
+      playlists: user_chunks[1],
+      routes: route_chunks[1],
+      playbar: parent_layout[2],
+    }
   }
+}
+
+fn get_route_chunks(layout_chunk: Rect) -> Vec<Rect> {
+  Layout::default()
+    .direction(Direction::Horizontal)
+    .constraints([Constraint::Percentage(20), Constraint::Percentage(80)].as_ref())
+    .split(layout_chunk)
+}
+
+pub fn draw_main_layout<B>(f: &mut Frame<B>, app: &App)
+where
+  B: Backend,
+{
+  let layout = get_main_layout(app, f.size());
+
+  // Search input and help
+  draw_input_and_help_box(f, app, layout.search);
+  draw_library_block(f, app, layout.library);
+  draw_playlist_block(f, app, layout.playlists);
+
+  // Nested main block with potential routes
+  draw_routes(f, app, layout.routes);
+
+  // Currently playing
+  draw_playbar(f, app, layout.playbar);
 
   // Possibly draw confirm dialog
   draw_dialog(f, app);
+
+  draw_command_palette(f, app);
 }
 
 pub fn draw_routes<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
 where
   B: Backend,
 {
-  let chunks = Layout::default()
-    .direction(Direction::Horizontal)
-    .constraints([Constraint::Percentage(20), Constraint::Percentage(80)].as_ref())
-    .split(layout_chunk);
-
-  draw_user_block(f, app, chunks[0]);
-
   let current_route = app.get_current_route();
 
   match current_route.id {
     RouteId::Search => {
-      draw_search_results(f, app, chunks[1]);
+      draw_search_results(f, app, layout_chunk);
     }
     RouteId::TrackTable => {
-      draw_song_table(f, app, chunks[1]);
+      draw_song_table(f, app, layout_chunk);
     }
     RouteId::AlbumTracks => {
-      draw_album_table(f, app, chunks[1]);
+      draw_album_table(f, app, layout_chunk);
     }
     RouteId::RecentlyPlayed => {
-      draw_recently_played_table(f, app, chunks[1]);
+      draw_recently_played_table(f, app, layout_chunk);
     }
     RouteId::Artist => {
-      draw_artist_albums(f, app, chunks[1]);
+      draw_artist_albums(f, app, layout_chunk);
     }
     RouteId::AlbumList => {
-      draw_album_list(f, app, chunks[1]);
+      draw_album_list(f, app, layout_chunk);
     }
     RouteId::PodcastEpisodes => {
-      draw_show_episodes(f, app, chunks[1]);
+      draw_show_episodes(f, app, layout_chunk);
     }
     RouteId::Home => {
-      draw_home(f, app, chunks[1]);
+      draw_home(f, app, layout_chunk);
     }
     RouteId::MadeForYou => {
-      draw_made_for_you(f, app, chunks[1]);
+      draw_made_for_you(f, app, layout_chunk);
     }
     RouteId::Artists => {
-      draw_artist_table(f, app, chunks[1]);
+      draw_artist_table(f, app, layout_chunk);
     }
     RouteId::Podcasts => {
-      draw_podcast_table(f, app, chunks[1]);
+      draw_podcast_table(f, app, layout_chunk);
     }
     RouteId::Recommendations => {
-      draw_recommendations_table(f, app, chunks[1]);
+      draw_recommendations_table(f, app, layout_chunk);
+    }
+    RouteId::Queue => {
+      draw_queue_table(f, app, layout_chunk);
+    }
+    RouteId::Lyrics => {
+      draw_lyrics(f, app, layout_chunk);
+    }
+    RouteId::Stats => {
+      draw_stats(f, app, layout_chunk);
     }
     RouteId::Error => {} // This is handled as a "full screen" route in main.rs
     RouteId::SelectedDevice => {} // This is handled as a "full screen" route in main.rs
@@ -316,51 +400,21 @@ where
     current_route.hovered_block == ActiveBlock::MyPlaylists,
   );
 
+  let (playlist_items, selected_index) =
+    filter::get_visible_rows(app, ActiveBlock::MyPlaylists, playlist_items);
+  let title = filter::get_title(app, ActiveBlock::MyPlaylists, "Playlists");
+
   draw_selectable_list(
     f,
     app,
     layout_chunk,
-    "Playlists",
+    &title,
     &playlist_items,
     highlight_state,
-    app.selected_playlist_index,
+    app.selected_playlist_index.map(|_| selected_index),
   );
 }
 
-pub fn draw_user_block<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
-where
-  B: Backend,
-{
-  // Check for width to make a responsive layout
-  if app.size.width >= SMALL_TERMINAL_WIDTH && !app.user_config.behavior.enforce_wide_search_bar {
-    let chunks = Layout::default()
-      .direction(Direction::Vertical)
-      .constraints(
-        [
-          Constraint::Length(3),
-          Constraint::Percentage(30),
-          Constraint::Percentage(70),
-        ]
-        .as_ref(),
-      )
-      .split(layout_chunk);
-
-    // Search input and help
-    draw_input_and_help_box(f, app, chunks[0]);
-    draw_library_block(f, app, chunks[1]);
-    draw_playlist_block(f, app, chunks[2]);
-  } else {
-    let chunks = Layout::default()
-      .direction(Direction::Vertical)
-      .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
-      .split(layout_chunk);
-
-    // Search input and help
-    draw_library_block(f, app, chunks[0]);
-    draw_playlist_block(f, app, chunks[1]);
-  }
-}
-
 pub fn draw_search_results<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
 where
   B: Backend,
@@ -576,14 +630,16 @@ where
       format: vec![item.name.to_owned()],
     })
     .collect::<Vec<TableItem>>();
+  let (items, selected_index) = filter::get_visible_rows(app, ActiveBlock::Artists, items);
+  let title = filter::get_title(app, ActiveBlock::Artists, "Artists");
 
   draw_table(
     f,
     app,
     layout_chunk,
-    ("Artists", &header),
+    (&title, &header),
     &items,
-    app.artists_list_index,
+    selected_index,
     highlight_state,
   )
 }
@@ -627,14 +683,16 @@ where
         ],
       })
       .collect::<Vec<TableItem>>();
+    let (items, selected_index) = filter::get_visible_rows(app, ActiveBlock::Podcasts, items);
+    let title = filter::get_title(app, ActiveBlock::Podcasts, "Podcasts");
 
     draw_table(
       f,
       app,
       layout_chunk,
-      ("Podcasts", &header),
+      (&title, &header),
       &items,
-      app.shows_list_index,
+      selected_index,
       highlight_state,
     )
   };
@@ -644,6 +702,13 @@ pub fn draw_album_table<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
 where
   B: Backend,
 {
+  // The cover is shown to the left of the tracks
+  let (art_area, layout_chunk) = album_art::split(app, layout_chunk, layout_chunk.width / 3);
+  if let Some(art_area) = art_area {
+    let url = crate::album_art::get_album_image_url(app);
+    album_art::draw(f, app, url, art_area);
+  }
+
   let header = TableHeader {
     id: TableId::Album,
     items: vec![
@@ -887,14 +952,20 @@ where
       ],
     })
     .collect::<Vec<TableItem>>();
+  let (items, selected_index) = filter::get_visible_rows(app, ActiveBlock::TrackTable, items);
+  let title = match app.playlist_tracks_progress {
+    Some((loaded, total)) => format!("Songs (loading {}/{})", loaded, total),
+    None => "Songs".to_string(),
+  };
+  let title = filter::get_title(app, ActiveBlock::TrackTable, &title);
 
   draw_table(
     f,
     app,
     layout_chunk,
-    ("Songs", &header),
+    (&title, &header),
     &items,
-    app.track_table.selected_index,
+    selected_index,
     highlight_state,
   )
 }
@@ -903,8 +974,14 @@ pub fn draw_basic_view<B>(f: &mut Frame<B>, app: &App)
 where
   B: Backend,
 {
+  if let Some(playbar) = get_basic_view_playbar(app, f.size()) {
+    draw_playbar(f, app, playbar);
+  }
+}
+
+pub fn get_basic_view_playbar(app: &App, size: Rect) -> Option<Rect> {
   // If space is negative, do nothing because the widget would not fit
-  if let Some(s) = app.size.height.checked_sub(BASIC_VIEW_HEIGHT) {
+  app.size.height.checked_sub(BASIC_VIEW_HEIGHT).map(|s| {
     let space = s / 2;
     let chunks = Layout::default()
       .direction(Direction::Vertical)
@@ -916,17 +993,24 @@ where
         ]
         .as_ref(),
       )
-      .split(f.size());
+      .split(size);
 
-    draw_playbar(f, app, chunks[1]);
-  }
+    chunks[1]
+  })
 }
 
-pub fn draw_playbar<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
-where
-  B: Backend,
-{
-  let chunks = Layout::default()
+// Returns the areas of the track title, the artist and the progress gauge of the playbar
+// The cover takes the left of the playbar when it is shown
+fn split_playbar(app: &App, layout_chunk: Rect) -> (Option<Rect>, Rect) {
+  let inner = layout_chunk.inner(&Margin {
+    vertical: 1,
+    horizontal: 1,
+  });
+  album_art::split(app, inner, inner.width / 4)
+}
+
+pub fn get_playbar_chunks(app: &App, layout_chunk: Rect) -> Vec<Rect> {
+  Layout::default()
     .direction(Direction::Vertical)
     .constraints(
       [
@@ -936,8 +1020,14 @@ where
       ]
       .as_ref(),
     )
-    .margin(1)
-    .split(layout_chunk);
+    .split(split_playbar(app, layout_chunk).1)
+}
+
+pub fn draw_playbar<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+where
+  B: Backend,
+{
+  let chunks = get_playbar_chunks(app, layout_chunk);
 
   // If no track is playing, render paragraph showing which device is selected, if no selected
   // give hint to choose a device
@@ -961,13 +1051,22 @@ where
         RepeatState::Context => "All",
       };
 
+      let sleep_text = match &app.sleep_timer {
+        Some(timer) => format!(" | Sleep: {}", timer.get_label(Instant::now())),
+        None => String::new(),
+      };
+
       let title = format!(
-        "{:-7} ({} | Shuffle: {:-3} | Repeat: {:-5} | Volume: {:-2}%)",
+        "{:-7} ({} | Shuffle: {:-3} | Repeat: {:-5} | Volume: {:-2}%{})",
         play_title,
-        current_playback_context.device.name,
+        app
+          .user_config
+          .devices
+          .display_name(&current_playback_context.device.name),
         shuffle_text,
         repeat_text,
-        current_playback_context.device.volume_percent
+        current_playback_context.device.volume_percent,
+        sleep_text
       );
 
       let current_route = app.get_current_route();
@@ -986,6 +1085,11 @@ where
 
       f.render_widget(title_block, layout_chunk);
 
+      if let (Some(art_area), _) = split_playbar(app, layout_chunk) {
+        let url = crate::album_art::get_playing_image_url(app);
+        album_art::draw(f, app, url, art_area);
+      }
+
       let (item_id, name, duration_ms) = match track_item {
         PlayingItem::Track(track) => (
           track.id.to_owned().unwrap_or_else(|| "".to_string()),
@@ -1012,19 +1116,15 @@ where
 
       let lines = Text::from(Span::styled(
         play_bar_text,
-        Style::default().fg(app.user_config.theme.playbar_text),
+        app.user_config.theme.playbar_text,
       ));
 
       let artist = Paragraph::new(lines)
-        .style(Style::default().fg(app.user_config.theme.playbar_text))
-        .block(
-          Block::default().title(Span::styled(
-            &track_name,
-            Style::default()
-              .fg(app.user_config.theme.selected)
-              .add_modifier(Modifier::BOLD),
-          )),
-        );
+        .style(app.user_config.theme.playbar_text)
+        .block(Block::default().title(Span::styled(
+          &track_name,
+          app.user_config.theme.selected.add_modifier(Modifier::BOLD),
+        )));
       f.render_widget(artist, chunks[0]);
 
       let progress_ms = match app.seek_ms {
@@ -1042,15 +1142,17 @@ where
       };
       let song_progress = Gauge::default()
         .gauge_style(
-          Style::default()
-            .fg(app.user_config.theme.playbar_progress)
-            .bg(app.user_config.theme.playbar_background)
+          app
+            .user_config
+            .theme
+            .playbar_progress
+            .patch(app.user_config.theme.playbar_background)
             .add_modifier(modifier),
         )
         .percent(perc)
         .label(Span::styled(
           &song_progress_label,
-          Style::default().fg(app.user_config.theme.playbar_progress_text),
+          app.user_config.theme.playbar_progress_text,
         ));
       f.render_widget(song_progress, chunks[2]);
     }
@@ -1072,48 +1174,45 @@ where
       Span::raw("Api response: "),
       Span::styled(
         &app.api_error,
-        Style::default().fg(app.user_config.theme.error_text),
+        app.user_config.theme.error_text,
       ),
     ]),
     Spans::from(Span::styled(
       "If you are trying to play a track, please check that",
-      Style::default().fg(app.user_config.theme.text),
+      app.user_config.theme.text,
     )),
     Spans::from(Span::styled(
       " 1. You have a Spotify Premium Account",
-      Style::default().fg(app.user_config.theme.text),
+      app.user_config.theme.text,
     )),
     Spans::from(Span::styled(
       " 2. Your playback device is active and selected - press `d` to go to device selection menu",
-      Style::default().fg(app.user_config.theme.text),
+      app.user_config.theme.text,
     )),
     Spans::from(Span::styled(
       " 3. If you're using spotifyd as a playback device, your device name must not contain spaces",
-      Style::default().fg(app.user_config.theme.text),
+      app.user_config.theme.text,
     )),
     Spans::from(Span::styled("Hint: a playback device must be either an official spotify client or a light weight alternative such as spotifyd",
-        Style::default().fg(app.user_config.theme.hint)
+        app.user_config.theme.hint
         ),
     ),
     Spans::from(
       Span::styled(
           "\nPress <Esc> to return",
-          Style::default().fg(app.user_config.theme.inactive),
+          app.user_config.theme.inactive,
       ),
     )
   ];
 
   let playing_paragraph = Paragraph::new(playing_text)
     .wrap(Wrap { trim: true })
-    .style(Style::default().fg(app.user_config.theme.text))
+    .style(app.user_config.theme.text)
     .block(
       Block::default()
         .borders(Borders::ALL)
-        .title(Span::styled(
-          "Error",
-          Style::default().fg(app.user_config.theme.error_border),
-        ))
-        .border_style(Style::default().fg(app.user_config.theme.error_border)),
+        .title(Span::styled("Error", app.user_config.theme.error_border))
+        .border_style(app.user_config.theme.error_border),
     );
   f.render_widget(playing_paragraph, chunks[0]);
 }
@@ -1155,7 +1254,7 @@ where
 
   // Banner text with correct styling
   let mut top_text = Text::from(BANNER);
-  top_text.patch_style(Style::default().fg(app.user_config.theme.banner));
+  top_text.patch_style(app.user_config.theme.banner);
 
   let bottom_text_raw = format!(
     "{}{}",
@@ -1166,13 +1265,13 @@ where
 
   // Contains the banner
   let top_text = Paragraph::new(top_text)
-    .style(Style::default().fg(app.user_config.theme.text))
+    .style(app.user_config.theme.text)
     .block(Block::default());
   f.render_widget(top_text, chunks[0]);
 
   // CHANGELOG
   let bottom_text = Paragraph::new(bottom_text)
-    .style(Style::default().fg(app.user_config.theme.text))
+    .style(app.user_config.theme.text)
     .block(Block::default())
     .wrap(Wrap { trim: false })
     .scroll((app.home_scroll, 0));
@@ -1293,24 +1392,24 @@ where
     .margin(5)
     .split(f.size());
 
-  let device_instructions: Vec<Spans> = vec![
+  let mut device_instructions: Vec<Spans> = vec![
         "To play tracks, please select a device. ",
         "Use `j/k` or up/down arrow keys to move up and down and <Enter> to select. ",
         "Your choice here will be cached so you can jump straight back in when you next open `spotify-tui`. ",
         "You can change the playback device at any time by pressing `d`.",
     ].into_iter().map(|instruction| Spans::from(Span::raw(instruction))).collect();
+  device_instructions.push(Spans::from(format!(
+    "Press `{}` and `{}` to change the volume of the selected device.",
+    app.user_config.keys.decrease_volume, app.user_config.keys.increase_volume
+  )));
 
   let instructions = Paragraph::new(device_instructions)
-    .style(Style::default().fg(app.user_config.theme.text))
+    .style(app.user_config.theme.text)
     .wrap(Wrap { trim: true })
-    .block(
-      Block::default().borders(Borders::NONE).title(Span::styled(
-        "Welcome to spotify-tui!",
-        Style::default()
-          .fg(app.user_config.theme.active)
-          .add_modifier(Modifier::BOLD),
-      )),
-    );
+    .block(Block::default().borders(Borders::NONE).title(Span::styled(
+      "Welcome to spotify-tui!",
+      app.user_config.theme.active.add_modifier(Modifier::BOLD),
+    )));
   f.render_widget(instructions, chunks[0]);
 
   let no_device_message = Span::raw("No devices found: Make sure a device is active");
@@ -1323,7 +1422,42 @@ where
         items
           .devices
           .iter()
-          .map(|device| ListItem::new(Span::raw(&device.name)))
+          .map(|device| {
+            let mut spans = vec![
+              Span::styled(
+                format!(
+                  "{} ",
+                  if device.is_active {
+                    &app.user_config.behavior.playing_icon
+                  } else {
+                    " "
+                  }
+                ),
+                app.user_config.theme.playbar_text,
+              ),
+              Span::raw(app.user_config.devices.display_name(&device.name)),
+              Span::styled(
+                format!(" ({})", device.device_type),
+                app.user_config.theme.inactive,
+              ),
+            ];
+            if let Some(volume) = device.volume_percent {
+              spans.push(Span::raw(format!(" {}%", volume)));
+            }
+            if device.is_private_session {
+              spans.push(Span::styled(
+                " [private session]",
+                app.user_config.theme.hint,
+              ));
+            }
+            if !device.is_controllable() {
+              spans.push(Span::styled(
+                " [restricted]",
+                app.user_config.theme.error_text,
+              ));
+            }
+            ListItem::new(Spans::from(spans))
+          })
           .collect()
       }
     }
@@ -1335,19 +1469,12 @@ where
   let list = List::new(items)
     .block(
       Block::default()
-        .title(Span::styled(
-          "Devices",
-          Style::default().fg(app.user_config.theme.active),
-        ))
+        .title(Span::styled("Devices", app.user_config.theme.active))
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(app.user_config.theme.inactive)),
+        .border_style(app.user_config.theme.inactive),
     )
-    .style(Style::default().fg(app.user_config.theme.text))
-    .highlight_style(
-      Style::default()
-        .fg(app.user_config.theme.active)
-        .add_modifier(Modifier::BOLD),
-    );
+    .style(app.user_config.theme.text)
+    .highlight_style(app.user_config.theme.active.add_modifier(Modifier::BOLD));
   f.render_stateful_widget(list, chunks[1], &mut state);
 }
 
@@ -1383,8 +1510,6 @@ where
     current_route.hovered_block == ActiveBlock::AlbumList,
   );
 
-  let selected_song_index = app.album_list_index;
-
   if let Some(saved_albums) = app.library.saved_albums.get_results(None) {
     let items = saved_albums
       .items
@@ -1402,14 +1527,16 @@ where
         ],
       })
       .collect::<Vec<TableItem>>();
+    let (items, selected_index) = filter::get_visible_rows(app, ActiveBlock::AlbumList, items);
+    let title = filter::get_title(app, ActiveBlock::AlbumList, "Saved Albums");
 
     draw_table(
       f,
       app,
       layout_chunk,
-      ("Saved Albums", &header),
+      (&title, &header),
       &items,
-      selected_song_index,
+      selected_index,
       highlight_state,
     )
   };
@@ -1513,6 +1640,8 @@ where
         None => "Episodes".to_owned(),
       },
     };
+    let (items, selected_index) = filter::get_visible_rows(app, ActiveBlock::EpisodeTable, items);
+    let title = filter::get_title(app, ActiveBlock::EpisodeTable, &title);
 
     draw_table(
       f,
@@ -1520,7 +1649,7 @@ where
       layout_chunk,
       (&title, &header),
       &items,
-      app.episode_list_index,
+      selected_index,
       highlight_state,
     );
   };
@@ -1606,8 +1735,6 @@ where
       current_route.hovered_block == ActiveBlock::RecentlyPlayed,
     );
 
-    let selected_song_index = app.recently_played.index;
-
     let items = recently_played
       .items
       .iter()
@@ -1621,19 +1748,259 @@ where
         ],
       })
       .collect::<Vec<TableItem>>();
+    let (items, selected_index) = filter::get_visible_rows(app, ActiveBlock::RecentlyPlayed, items);
+    let title = filter::get_title(app, ActiveBlock::RecentlyPlayed, "Recently Played Tracks");
 
     draw_table(
       f,
       app,
       layout_chunk,
-      ("Recently Played Tracks", &header),
+      (&title, &header),
       &items,
-      selected_song_index,
+      selected_index,
       highlight_state,
     )
   };
 }
 
+pub fn draw_queue_table<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+where
+  B: Backend,
+{
+  let header = TableHeader {
+    id: TableId::Queue,
+    items: vec![
+      TableHeaderItem {
+        id: ColumnId::Liked,
+        text: "",
+        width: 2,
+      },
+      TableHeaderItem {
+        text: "",
+        width: 2,
+        ..Default::default()
+      },
+      TableHeaderItem {
+        id: ColumnId::Title,
+        text: "Title",
+        // We need to subtract the fixed value of the previous columns
+        width: get_percentage_width(layout_chunk.width, 2.0 / 5.0) - 4,
+      },
+      TableHeaderItem {
+        text: "Artist",
+        width: get_percentage_width(layout_chunk.width, 2.0 / 5.0),
+        ..Default::default()
+      },
+      TableHeaderItem {
+        text: "Length",
+        width: get_percentage_width(layout_chunk.width, 1.0 / 5.0),
+        ..Default::default()
+      },
+    ],
+  };
+
+  let current_route = app.get_current_route();
+  let highlight_state = (
+    current_route.active_block == ActiveBlock::Queue,
+    current_route.hovered_block == ActiveBlock::Queue,
+  );
+
+  let items = app
+    .queue
+    .rows()
+    .into_iter()
+    .map(|item| {
+      // Mark the entries that were queued from spt
+      let origin = if app.queue.is_locally_added(item) {
+        "+".to_string()
+      } else {
+        "".to_string()
+      };
+      match item {
+        PlayingItem::Track(track) => TableItem {
+          id: track.id.clone().unwrap_or_else(|| "".to_string()),
+          format: vec![
+            "".to_string(),
+            origin,
+            track.name.to_owned(),
+            create_artist_string(&track.artists),
+            millis_to_minutes(u128::from(track.duration_ms)),
+          ],
+        },
+        PlayingItem::Episode(episode) => TableItem {
+          id: episode.id.to_owned(),
+          format: vec![
+            "".to_string(),
+            origin,
+            episode.name.to_owned(),
+            episode.show.name.to_owned(),
+            millis_to_minutes(u128::from(episode.duration_ms)),
+          ],
+        },
+      }
+    })
+    .collect::<Vec<TableItem>>();
+
+  draw_table(
+    f,
+    app,
+    layout_chunk,
+    ("Queue", &header),
+    &items,
+    app.queue.selected_index,
+    highlight_state,
+  )
+}
+
+pub fn draw_lyrics<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+where
+  B: Backend,
+{
+  let current_route = app.get_current_route();
+  let highlight_state = (
+    current_route.active_block == ActiveBlock::Lyrics,
+    current_route.hovered_block == ActiveBlock::Lyrics,
+  );
+
+  let (title, text, focused_index) = match (&app.lyrics.lyrics, &app.lyrics.item_uri) {
+    (Some(lyrics), _) => {
+      let current_index = app.lyrics.get_current_line(app.song_progress_ms);
+      let lines = lyrics
+        .lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+          let style = if Some(index) == app.lyrics.selected_index {
+            app.user_config.theme.selected.add_modifier(Modifier::BOLD)
+          } else if Some(index) == current_index {
+            app.user_config.theme.active.add_modifier(Modifier::BOLD)
+          } else {
+            app.user_config.theme.text
+          };
+          Spans::from(Span::styled(line.text.as_str(), style))
+        })
+        .collect::<Vec<Spans>>();
+      let title = if lyrics.is_synced() {
+        "Lyrics"
+      } else {
+        "Lyrics (unsynced)"
+      };
+      (
+        title,
+        Text::from(lines),
+        app.lyrics.selected_index.or(current_index),
+      )
+    }
+    (None, Some(_)) if app.lyrics.is_loading => ("Lyrics", Text::from("Loading lyrics..."), None),
+    (None, Some(_)) => ("Lyrics", Text::from("No lyrics found for this track"), None),
+    (None, None) => ("Lyrics", Text::from("Nothing is playing"), None),
+  };
+
+  // Keep the focused line in the middle of the view
+  let half_height = layout_chunk.height.saturating_sub(2) / 2;
+  let scroll = focused_index
+    .map(|index| (index as u16).saturating_sub(half_height))
+    .unwrap_or(0);
+
+  let lyrics = Paragraph::new(text)
+    .style(app.user_config.theme.text)
+    .block(
+      Block::default()
+        .title(Span::styled(
+          title,
+          get_color(highlight_state, app.user_config.theme),
+        ))
+        .borders(Borders::ALL)
+        .border_style(get_color(highlight_state, app.user_config.theme)),
+    )
+    .alignment(Alignment::Center)
+    .scroll((scroll, 0));
+  f.render_widget(lyrics, layout_chunk);
+}
+
+pub fn draw_stats<B>(f: &mut Frame<B>, app: &App, layout_chunk: Rect)
+where
+  B: Backend,
+{
+  let current_route = app.get_current_route();
+  let highlight_state = (
+    current_route.active_block == ActiveBlock::Stats,
+    current_route.hovered_block == ActiveBlock::Stats,
+  );
+
+  let chunks = Layout::default()
+    .direction(Direction::Vertical)
+    .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+    .split(layout_chunk);
+
+  let summary = match &app.stats.stats {
+    Some(stats) if stats.plays == 0 => format!(
+      "{}: nothing was played yet, plays are recorded while spt runs",
+      stats.period.label()
+    ),
+    Some(stats) => stats.summary(),
+    None => "Loading the listening history...".to_string(),
+  };
+  let title = format!(
+    "Statistics (press {} to change the period)",
+    app.user_config.keys.cycle_stats_period
+  );
+  let summary = Paragraph::new(summary)
+    .style(app.user_config.theme.text)
+    .block(
+      Block::default()
+        .title(Span::styled(
+          title,
+          get_color(highlight_state, app.user_config.theme),
+        ))
+        .borders(Borders::ALL)
+        .border_style(get_color(highlight_state, app.user_config.theme)),
+    );
+  f.render_widget(summary, chunks[0]);
+
+  let top_chunks = Layout::default()
+    .direction(Direction::Horizontal)
+    .constraints(
+      [
+        Constraint::Ratio(1, 3),
+        Constraint::Ratio(1, 3),
+        Constraint::Ratio(1, 3),
+      ]
+      .as_ref(),
+    )
+    .split(chunks[1]);
+
+  let format_counts = |counts: &[Count]| {
+    counts
+      .iter()
+      .map(|count| {
+        let name = if count.artists.is_empty() {
+          count.name.clone()
+        } else {
+          format!("{} - {}", count.name, count.artists)
+        };
+        format!("{:>4}  {}", count.plays, name)
+      })
+      .collect::<Vec<String>>()
+  };
+  let (tracks, artists, albums) = match &app.stats.stats {
+    Some(stats) => (
+      format_counts(&stats.top_tracks),
+      format_counts(&stats.top_artists),
+      format_counts(&stats.top_albums),
+    ),
+    None => (vec![], vec![], vec![]),
+  };
+  let lists = [
+    ("Top tracks", tracks),
+    ("Top artists", artists),
+    ("Top albums", albums),
+  ];
+  for ((title, items), chunk) in lists.iter().zip(top_chunks) {
+    draw_selectable_list(f, app, chunk, title, items, highlight_state, None);
+  }
+}
+
 fn draw_selectable_list<B, S>(
   f: &mut Frame<B>,
   app: &App,
@@ -1665,7 +2032,7 @@ fn draw_selectable_list<B, S>(
         .borders(Borders::ALL)
         .border_style(get_color(highlight_state, app.user_config.theme)),
     )
-    .style(Style::default().fg(app.user_config.theme.text))
+    .style(app.user_config.theme.text)
     .highlight_style(
       get_color(highlight_state, app.user_config.theme).add_modifier(Modifier::BOLD),
     );
@@ -1676,79 +2043,212 @@ fn draw_dialog<B>(f: &mut Frame<B>, app: &App)
 where
   B: Backend,
 {
-  if let ActiveBlock::Dialog(_) = app.get_current_route().active_block {
-    if let Some(playlist) = app.dialog.as_ref() {
-      let bounds = f.size();
-      // maybe do this better
-      let width = std::cmp::min(bounds.width - 2, 45);
-      let height = 8;
-      let left = (bounds.width - width) / 2;
-      let top = bounds.height / 4;
+  if let ActiveBlock::Dialog(context) = app.get_current_route().active_block {
+    match context {
+      DialogContext::PlaylistWindow | DialogContext::PlaylistSearch => {
+        draw_confirm_dialog(f, app, "Are you sure you want to delete the playlist: ")
+      }
+      DialogContext::RemoveFromPlaylist => draw_confirm_dialog(
+        f,
+        app,
+        "Are you sure you want to remove from the playlist the track: ",
+      ),
+      DialogContext::AddToPlaylist => draw_playlist_picker(f, app),
+      DialogContext::CreatePlaylist => draw_text_dialog(f, app, "New playlist name"),
+      DialogContext::RenamePlaylist => draw_text_dialog(f, app, "Rename playlist"),
+      DialogContext::DescribePlaylist => draw_text_dialog(f, app, "Playlist description"),
+    }
+  }
+}
 
-      let rect = Rect::new(left, top, width, height);
+fn draw_command_palette<B>(f: &mut Frame<B>, app: &App)
+where
+  B: Backend,
+{
+  let palette = match &app.command_palette {
+    Some(palette) if app.get_current_route().active_block == ActiveBlock::CommandPalette => palette,
+    _ => return,
+  };
 
-      f.render_widget(Clear, rect);
+  let bounds = f.size();
+  let width = std::cmp::min(bounds.width.saturating_sub(2), 70);
+  let height = std::cmp::min(bounds.height, 18);
+  let rect = Rect::new((bounds.width - width) / 2, bounds.height / 6, width, height);
+  f.render_widget(Clear, rect);
+
+  let chunks = Layout::default()
+    .direction(Direction::Vertical)
+    .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+    .split(rect);
 
-      let block = Block::default()
+  let query = palette.query.iter().collect::<String>();
+  let input = Paragraph::new(format!("{}_", query))
+    .style(app.user_config.theme.text)
+    .block(
+      Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(app.user_config.theme.inactive));
-
-      f.render_widget(block, rect);
-
-      let vchunks = Layout::default()
-        .direction(Direction::Vertical)
-        .margin(2)
-        .constraints([Constraint::Min(3), Constraint::Length(3)].as_ref())
-        .split(rect);
-
-      // suggestion: possibly put this as part of
-      // app.dialog, but would have to introduce lifetime
-      let text = vec![
-        Spans::from(Span::raw("Are you sure you want to delete the playlist: ")),
-        Spans::from(Span::styled(
-          playlist.as_str(),
-          Style::default().add_modifier(Modifier::BOLD),
-        )),
-        Spans::from(Span::raw("?")),
-      ];
-
-      let text = Paragraph::new(text)
-        .wrap(Wrap { trim: true })
-        .alignment(Alignment::Center);
-
-      f.render_widget(text, vchunks[0]);
-
-      let hchunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .horizontal_margin(3)
-        .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)].as_ref())
-        .split(vchunks[1]);
-
-      let ok_text = Span::raw("Ok");
-      let ok = Paragraph::new(ok_text)
-        .style(Style::default().fg(if app.confirm {
-          app.user_config.theme.hovered
-        } else {
-          app.user_config.theme.inactive
-        }))
-        .alignment(Alignment::Center);
+        .title(Span::styled(
+          "Command palette",
+          app.user_config.theme.selected,
+        ))
+        .border_style(app.user_config.theme.selected),
+    );
+  f.render_widget(input, chunks[0]);
 
-      f.render_widget(ok, hchunks[0]);
+  let entries = command_palette::get_entries(app);
+  let items: Vec<ListItem> = entries
+    .iter()
+    .map(|entry| {
+      let key = entry
+        .key
+        .as_ref()
+        .map(|key| key.to_string())
+        .unwrap_or_default();
+      ListItem::new(Spans::from(vec![
+        Span::raw(entry.title.to_owned()),
+        Span::styled(format!("  {}", key), app.user_config.theme.inactive),
+      ]))
+    })
+    .collect();
+  let mut state = ListState::default();
+  if !entries.is_empty() {
+    state.select(Some(palette.selected_index));
+  }
+  let list = List::new(items)
+    .block(
+      Block::default()
+        .borders(Borders::ALL)
+        .border_style(app.user_config.theme.inactive),
+    )
+    .style(app.user_config.theme.text)
+    .highlight_style(app.user_config.theme.hovered.add_modifier(Modifier::BOLD));
+  f.render_stateful_widget(list, chunks[1], &mut state);
+}
 
-      let cancel_text = Span::raw("Cancel");
-      let cancel = Paragraph::new(cancel_text)
-        .style(Style::default().fg(if app.confirm {
-          app.user_config.theme.inactive
-        } else {
-          app.user_config.theme.hovered
-        }))
-        .alignment(Alignment::Center);
+fn get_dialog_rect(bounds: Rect, height: u16) -> Rect {
+  // maybe do this better
+  let width = std::cmp::min(bounds.width - 2, 45);
+  let height = std::cmp::min(bounds.height, height);
+  let left = (bounds.width - width) / 2;
+  let top = bounds.height / 4;
 
-      f.render_widget(cancel, hchunks[1]);
-    }
+  Rect::new(left, top, width, height)
+}
+
+fn draw_confirm_dialog<B>(f: &mut Frame<B>, app: &App, prompt: &str)
+where
+  B: Backend,
+{
+  if let Some(name) = app.dialog.as_ref() {
+    let rect = get_dialog_rect(f.size(), 8);
+
+    f.render_widget(Clear, rect);
+
+    let block = Block::default()
+      .borders(Borders::ALL)
+      .border_style(app.user_config.theme.inactive);
+
+    f.render_widget(block, rect);
+
+    let vchunks = Layout::default()
+      .direction(Direction::Vertical)
+      .margin(2)
+      .constraints([Constraint::Min(3), Constraint::Length(3)].as_ref())
+      .split(rect);
+
+    // suggestion: possibly put this as part of
+    // app.dialog, but would have to introduce lifetime
+    let text = vec![
+      Spans::from(Span::raw(prompt)),
+      Spans::from(Span::styled(
+        name.as_str(),
+        Style::default().add_modifier(Modifier::BOLD),
+      )),
+      Spans::from(Span::raw("?")),
+    ];
+
+    let text = Paragraph::new(text)
+      .wrap(Wrap { trim: true })
+      .alignment(Alignment::Center);
+
+    f.render_widget(text, vchunks[0]);
+
+    let hchunks = Layout::default()
+      .direction(Direction::Horizontal)
+      .horizontal_margin(3)
+      .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)].as_ref())
+      .split(vchunks[1]);
+
+    let ok_text = Span::raw("Ok");
+    let ok = Paragraph::new(ok_text)
+      .style(if app.confirm {
+        app.user_config.theme.hovered
+      } else {
+        app.user_config.theme.inactive
+      })
+      .alignment(Alignment::Center);
+
+    f.render_widget(ok, hchunks[0]);
+
+    let cancel_text = Span::raw("Cancel");
+    let cancel = Paragraph::new(cancel_text)
+      .style(if app.confirm {
+        app.user_config.theme.inactive
+      } else {
+        app.user_config.theme.hovered
+      })
+      .alignment(Alignment::Center);
+
+    f.render_widget(cancel, hchunks[1]);
   }
 }
 
+fn draw_playlist_picker<B>(f: &mut Frame<B>, app: &App)
+where
+  B: Backend,
+{
+  let rect = get_dialog_rect(f.size(), 15);
+
+  f.render_widget(Clear, rect);
+
+  let items = app
+    .get_editable_playlists()
+    .iter()
+    .map(|playlist| playlist.name.as_str())
+    .collect::<Vec<&str>>();
+
+  draw_selectable_list(
+    f,
+    app,
+    rect,
+    "Add to playlist",
+    &items,
+    (true, true),
+    Some(app.playlist_picker_index),
+  );
+}
+
+fn draw_text_dialog<B>(f: &mut Frame<B>, app: &App, title: &str)
+where
+  B: Backend,
+{
+  let rect = get_dialog_rect(f.size(), 3);
+
+  f.render_widget(Clear, rect);
+
+  let input_string: String = app.dialog_input.iter().collect();
+  let input = Paragraph::new(format!("{}_", input_string))
+    .style(app.user_config.theme.text)
+    .block(
+      Block::default()
+        .borders(Borders::ALL)
+        .title(Span::styled(title, app.user_config.theme.selected))
+        .border_style(app.user_config.theme.selected),
+    );
+
+  f.render_widget(input, rect);
+}
+
 fn draw_table<B>(
   f: &mut Frame<B>,
   app: &App,
@@ -1774,22 +2274,15 @@ fn draw_table<B>(
 
   let (title, header) = table_layout;
 
-  // Make sure that the selected item is visible on the page. Need to add some rows of padding
-  // to chunk height for header and header space to get a true table height
-  let padding = 5;
-  let offset = layout_chunk
-    .height
-    .checked_sub(padding)
-    .and_then(|height| selected_index.checked_sub(height as usize))
-    .unwrap_or(0);
+  let offset = util::get_table_offset(layout_chunk, selected_index);
 
   let rows = items.iter().skip(offset).enumerate().map(|(i, item)| {
     let mut formatted_row = item.format.clone();
-    let mut style = Style::default().fg(app.user_config.theme.text); // default styling
+    let mut style = app.user_config.theme.text; // default styling
 
     // if table displays songs
     match header.id {
-      TableId::Song | TableId::RecentlyPlayed | TableId::Album => {
+      TableId::Song | TableId::RecentlyPlayed | TableId::Album | TableId::Queue => {
         // First check if the song should be highlighted because it is currently playing
         if let Some(title_idx) = header.get_index(ColumnId::Title) {
           if let Some(track_playing_offset_index) =
@@ -1797,9 +2290,7 @@ fn draw_table<B>(
           {
             if i == track_playing_offset_index {
               formatted_row[title_idx] = format!("▶ {}", &formatted_row[title_idx]);
-              style = Style::default()
-                .fg(app.user_config.theme.active)
-                .add_modifier(Modifier::BOLD);
+              style = app.user_config.theme.active.add_modifier(Modifier::BOLD);
             }
           }
         }
@@ -1818,9 +2309,7 @@ fn draw_table<B>(
           {
             if i == track_playing_offset_index {
               formatted_row[name_idx] = format!("▶ {}", &formatted_row[name_idx]);
-              style = Style::default()
-                .fg(app.user_config.theme.active)
-                .add_modifier(Modifier::BOLD);
+              style = app.user_config.theme.active.add_modifier(Modifier::BOLD);
             }
           }
         }
@@ -1844,21 +2333,18 @@ fn draw_table<B>(
     .collect::<Vec<tui::layout::Constraint>>();
 
   let table = Table::new(rows)
-    .header(
-      Row::new(header.items.iter().map(|h| h.text))
-        .style(Style::default().fg(app.user_config.theme.header)),
-    )
+    .header(Row::new(header.items.iter().map(|h| h.text)).style(app.user_config.theme.header))
     .block(
       Block::default()
         .borders(Borders::ALL)
-        .style(Style::default().fg(app.user_config.theme.text))
+        .style(app.user_config.theme.text)
         .title(Span::styled(
           title,
           get_color(highlight_state, app.user_config.theme),
         ))
         .border_style(get_color(highlight_state, app.user_config.theme)),
     )
-    .style(Style::default().fg(app.user_config.theme.text))
+    .style(app.user_config.theme.text)
     .widths(&widths);
   f.render_widget(table, layout_chunk);
 }
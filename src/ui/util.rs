@@ -1,7 +1,7 @@
 use super::super::app::{ActiveBlock, App, ArtistBlock, SearchResultBlock};
 use crate::user_config::Theme;
 use rspotify::model::artist::SimplifiedArtist;
-use tui::style::Style;
+use tui::{layout::Rect, style::Style};
 
 pub const BASIC_VIEW_HEIGHT: u16 = 6;
 pub const SMALL_TERMINAL_WIDTH: u16 = 150;
@@ -33,9 +33,9 @@ pub fn get_artist_highlight_state(app: &App, block_to_match: ArtistBlock) -> (bo
 
 pub fn get_color((is_active, is_hovered): (bool, bool), theme: Theme) -> Style {
   match (is_active, is_hovered) {
-    (true, _) => Style::default().fg(theme.selected),
-    (false, true) => Style::default().fg(theme.hovered),
-    _ => Style::default().fg(theme.inactive),
+    (true, _) => theme.selected,
+    (false, true) => theme.hovered,
+    _ => theme.inactive,
   }
 }
 
@@ -95,6 +95,25 @@ pub fn get_main_layout_margin(app: &App) -> u16 {
   }
 }
 
+// Index of the first row that `draw_table` shows when `selected_index` is selected
+pub fn get_table_offset(layout_chunk: Rect, selected_index: usize) -> usize {
+  // Make sure that the selected item is visible on the page. Need to add some rows of padding
+  // to chunk height for header and header space to get a true table height
+  let padding = 5;
+  layout_chunk
+    .height
+    .checked_sub(padding)
+    .and_then(|height| selected_index.checked_sub(height as usize))
+    .unwrap_or(0)
+}
+
+// Index of the first item that a `List` shows when `selected_index` is selected. The list state
+// is created fresh on every draw, so the list scrolls just enough to show the selection
+pub fn get_list_offset(layout_chunk: Rect, selected_index: usize) -> usize {
+  let height = layout_chunk.height.saturating_sub(2) as usize;
+  (selected_index + 1).saturating_sub(height)
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
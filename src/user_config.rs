@@ -1,77 +1,230 @@
+use crate::album_art::GraphicsProtocol;
+use crate::app::ActiveBlock;
+use crate::device::DeviceConfig;
 use crate::event::Key;
+use crate::scrobbler::ScrobblingConfig;
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use std::{
   fs,
   path::{Path, PathBuf},
 };
-use tui::style::Color;
+use tui::style::{Color, Modifier, Style};
 
 const FILE_NAME: &str = "config.yml";
 const CONFIG_DIR: &str = ".config";
 const APP_CONFIG_DIR: &str = "spotify-tui";
+const LYRICS_DIR: &str = "lyrics";
+const THEMES_DIR: &str = "themes";
+const MAX_CHORD_LENGTH: usize = 4;
+const THEME_FILE_EXTENSION: &str = "yml";
 
+/// A color by name, as `r, g, b`, as `#rrggbb` or as an index into the 256 color palette
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum UserColor {
+  Indexed(u8),
+  Name(String),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct UserStyle {
+  pub fg: Option<UserColor>,
+  pub bg: Option<UserColor>,
+  pub bold: Option<bool>,
+  pub italic: Option<bool>,
+  pub underline: Option<bool>,
+}
+
+/// Either just a color or the full style of a theme item
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum UserThemeItem {
+  Color(UserColor),
+  Style(UserStyle),
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct UserTheme {
-  pub active: Option<String>,
-  pub banner: Option<String>,
-  pub error_border: Option<String>,
-  pub error_text: Option<String>,
-  pub hint: Option<String>,
-  pub hovered: Option<String>,
-  pub inactive: Option<String>,
-  pub playbar_background: Option<String>,
-  pub playbar_progress: Option<String>,
-  pub playbar_progress_text: Option<String>,
-  pub playbar_text: Option<String>,
-  pub selected: Option<String>,
-  pub text: Option<String>,
-  pub header: Option<String>,
+  // A built-in preset or a file in the themes directory the items below are applied on top of
+  pub name: Option<String>,
+  pub analysis_bar: Option<UserThemeItem>,
+  pub analysis_bar_text: Option<UserThemeItem>,
+  pub active: Option<UserThemeItem>,
+  pub banner: Option<UserThemeItem>,
+  pub error_border: Option<UserThemeItem>,
+  pub error_text: Option<UserThemeItem>,
+  pub hint: Option<UserThemeItem>,
+  pub hovered: Option<UserThemeItem>,
+  pub inactive: Option<UserThemeItem>,
+  pub playbar_background: Option<UserThemeItem>,
+  pub playbar_progress: Option<UserThemeItem>,
+  pub playbar_progress_text: Option<UserThemeItem>,
+  pub playbar_text: Option<UserThemeItem>,
+  pub selected: Option<UserThemeItem>,
+  pub text: Option<UserThemeItem>,
+  pub header: Option<UserThemeItem>,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Theme {
-  pub analysis_bar: Color,
-  pub analysis_bar_text: Color,
-  pub active: Color,
-  pub banner: Color,
-  pub error_border: Color,
-  pub error_text: Color,
-  pub hint: Color,
-  pub hovered: Color,
-  pub inactive: Color,
-  pub playbar_background: Color,
-  pub playbar_progress: Color,
-  pub playbar_progress_text: Color,
-  pub playbar_text: Color,
-  pub selected: Color,
-  pub text: Color,
-  pub header: Color,
+  pub analysis_bar: Style,
+  pub analysis_bar_text: Style,
+  pub active: Style,
+  pub banner: Style,
+  pub error_border: Style,
+  pub error_text: Style,
+  pub hint: Style,
+  pub hovered: Style,
+  pub inactive: Style,
+  // Drawn behind the progress of the playbar, so a plain color sets the background
+  pub playbar_background: Style,
+  pub playbar_progress: Style,
+  pub playbar_progress_text: Style,
+  pub playbar_text: Style,
+  pub selected: Style,
+  pub text: Style,
+  pub header: Style,
+}
+
+fn fg(color: Color) -> Style {
+  Style::default().fg(color)
 }
 
 impl Default for Theme {
   fn default() -> Self {
     Theme {
-      analysis_bar: Color::LightCyan,
-      analysis_bar_text: Color::Reset,
-      active: Color::Cyan,
-      banner: Color::LightCyan,
-      error_border: Color::Red,
-      error_text: Color::LightRed,
-      hint: Color::Yellow,
-      hovered: Color::Magenta,
-      inactive: Color::Gray,
-      playbar_background: Color::Black,
-      playbar_progress: Color::LightCyan,
-      playbar_progress_text: Color::LightCyan,
-      playbar_text: Color::Reset,
-      selected: Color::LightCyan,
-      text: Color::Reset,
-      header: Color::Reset,
+      analysis_bar: fg(Color::LightCyan),
+      analysis_bar_text: fg(Color::Reset),
+      active: fg(Color::Cyan),
+      banner: fg(Color::LightCyan),
+      error_border: fg(Color::Red),
+      error_text: fg(Color::LightRed),
+      hint: fg(Color::Yellow),
+      hovered: fg(Color::Magenta),
+      inactive: fg(Color::Gray),
+      playbar_background: Style::default().bg(Color::Black),
+      playbar_progress: fg(Color::LightCyan),
+      playbar_progress_text: fg(Color::LightCyan),
+      playbar_text: fg(Color::Reset),
+      selected: fg(Color::LightCyan),
+      text: fg(Color::Reset),
+      header: fg(Color::Reset),
     }
   }
 }
 
+/// The themes that ship with spotify-tui, in the format of the files in the themes directory
+const THEME_PRESETS: &[(&str, &str)] = &[
+  ("default", ""),
+  (
+    "gruvbox",
+    r##"
+active: "#fabd2f"
+banner: "#fe8019"
+error_border: "#fb4934"
+error_text: "#fb4934"
+hint: "#fabd2f"
+hovered: "#d3869b"
+inactive: "#928374"
+playbar_background: "#3c3836"
+playbar_progress: "#b8bb26"
+playbar_progress_text: "#ebdbb2"
+playbar_text: "#ebdbb2"
+selected: { fg: "#8ec07c", bold: true }
+text: "#ebdbb2"
+header: { fg: "#83a598", bold: true }
+analysis_bar: "#b8bb26"
+analysis_bar_text: "#282828"
+"##,
+  ),
+  (
+    "nord",
+    r##"
+active: "#88c0d0"
+banner: "#81a1c1"
+error_border: "#bf616a"
+error_text: "#bf616a"
+hint: "#ebcb8b"
+hovered: "#b48ead"
+inactive: "#4c566a"
+playbar_background: "#3b4252"
+playbar_progress: "#88c0d0"
+playbar_progress_text: "#eceff4"
+playbar_text: "#d8dee9"
+selected: { fg: "#8fbcbb", bold: true }
+text: "#d8dee9"
+header: { fg: "#81a1c1", bold: true }
+analysis_bar: "#88c0d0"
+analysis_bar_text: "#2e3440"
+"##,
+  ),
+  (
+    "dracula",
+    r##"
+active: "#bd93f9"
+banner: "#ff79c6"
+error_border: "#ff5555"
+error_text: "#ff5555"
+hint: "#f1fa8c"
+hovered: "#ff79c6"
+inactive: "#6272a4"
+playbar_background: "#44475a"
+playbar_progress: "#50fa7b"
+playbar_progress_text: "#f8f8f2"
+playbar_text: "#f8f8f2"
+selected: { fg: "#8be9fd", bold: true }
+text: "#f8f8f2"
+header: { fg: "#bd93f9", bold: true }
+analysis_bar: "#50fa7b"
+analysis_bar_text: "#282a36"
+"##,
+  ),
+  (
+    "solarized",
+    r##"
+active: "#268bd2"
+banner: "#cb4b16"
+error_border: "#dc322f"
+error_text: "#dc322f"
+hint: "#b58900"
+hovered: "#d33682"
+inactive: "#586e75"
+playbar_background: "#073642"
+playbar_progress: "#2aa198"
+playbar_progress_text: "#93a1a1"
+playbar_text: "#839496"
+selected: { fg: "#2aa198", bold: true }
+text: "#839496"
+header: { fg: "#268bd2", bold: true }
+analysis_bar: "#2aa198"
+analysis_bar_text: "#002b36"
+"##,
+  ),
+  (
+    "monochrome",
+    r##"
+active: { fg: White, bold: true }
+banner: White
+error_border: White
+error_text: { fg: White, bold: true }
+hint: { fg: Gray, italic: true }
+hovered: { fg: White, underline: true }
+inactive: DarkGray
+playbar_background: 236
+playbar_progress: 250
+playbar_progress_text: White
+playbar_text: Gray
+selected: { fg: Black, bg: White }
+text: Gray
+header: { fg: White, bold: true }
+analysis_bar: 250
+analysis_bar_text: Black
+"##,
+  ),
+];
+
 fn parse_key(key: String) -> Result<Key> {
   fn get_single_char(string: &str) -> char {
     match string.chars().next() {
@@ -112,15 +265,33 @@ fn parse_key(key: String) -> Result<Key> {
   }
 }
 
-fn check_reserved_keys(key: Key) -> Result<()> {
+// Keys separated by spaces make a chord, in which `<leader>` stands for the leader key
+fn parse_key_binding(binding: String, leader: &Key) -> Result<Key> {
+  // A single space is the space key
+  if binding.trim().is_empty() {
+    return parse_key(binding);
+  }
+  let keys = binding
+    .split_whitespace()
+    .map(|section| match section {
+      "<leader>" => Ok(leader.clone()),
+      _ => parse_key(section.to_string()),
+    })
+    .collect::<Result<Vec<Key>>>()?;
+  match keys.as_slice() {
+    [key] => Ok(key.clone()),
+    _ if keys.len() > MAX_CHORD_LENGTH => Err(anyhow!(
+      "Chords can only have {} keys, \"{}\" has {}",
+      MAX_CHORD_LENGTH,
+      binding,
+      keys.len()
+    )),
+    _ => Ok(Key::chord(&keys)),
+  }
+}
+
+fn check_reserved_keys(key: &Key) -> Result<()> {
   let reserved = [
-    Key::Char('h'),
-    Key::Char('j'),
-    Key::Char('k'),
-    Key::Char('l'),
-    Key::Char('H'),
-    Key::Char('M'),
-    Key::Char('L'),
     Key::Up,
     Key::Down,
     Key::Left,
@@ -129,7 +300,7 @@ fn check_reserved_keys(key: Key) -> Result<()> {
     Key::Enter,
   ];
   for item in reserved.iter() {
-    if key == *item {
+    if key == item {
       // TODO: Add pretty print for key
       return Err(anyhow!(
         "The key {:?} is reserved and cannot be remapped",
@@ -140,69 +311,227 @@ fn check_reserved_keys(key: Key) -> Result<()> {
   Ok(())
 }
 
+// Paths in the config file may start with `~` for the home directory
+fn expand_home_dir(path: &str) -> PathBuf {
+  match (path.strip_prefix("~/"), dirs::home_dir()) {
+    (Some(rest), Some(home)) => home.join(rest),
+    _ => PathBuf::from(path),
+  }
+}
+
 #[derive(Clone)]
 pub struct UserConfigPaths {
   pub config_file_path: PathBuf,
 }
 
-#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
-pub struct KeyBindingsString {
-  back: Option<String>,
-  next_page: Option<String>,
-  previous_page: Option<String>,
-  jump_to_start: Option<String>,
-  jump_to_end: Option<String>,
-  jump_to_album: Option<String>,
-  jump_to_artist_album: Option<String>,
-  jump_to_context: Option<String>,
-  manage_devices: Option<String>,
-  decrease_volume: Option<String>,
-  increase_volume: Option<String>,
-  toggle_playback: Option<String>,
-  seek_backwards: Option<String>,
-  seek_forwards: Option<String>,
-  next_track: Option<String>,
-  previous_track: Option<String>,
-  help: Option<String>,
-  shuffle: Option<String>,
-  repeat: Option<String>,
-  search: Option<String>,
-  submit: Option<String>,
-  copy_song_url: Option<String>,
-  copy_album_url: Option<String>,
-  audio_analysis: Option<String>,
-  basic_view: Option<String>,
-  add_item_to_queue: Option<String>,
+/// An action that can be bound to a key
+pub struct KeyBinding {
+  pub name: &'static str,
+  pub key: Key,
+  pub description: &'static str,
+  // Groups the action in the help menu
+  pub section: &'static str,
+  // The blocks the action is handled in, empty when it works everywhere
+  pub blocks: &'static [ActiveBlock],
 }
 
-#[derive(Clone)]
-pub struct KeyBindings {
-  pub back: Key,
-  pub next_page: Key,
-  pub previous_page: Key,
-  pub jump_to_start: Key,
-  pub jump_to_end: Key,
-  pub jump_to_album: Key,
-  pub jump_to_artist_album: Key,
-  pub jump_to_context: Key,
-  pub manage_devices: Key,
-  pub decrease_volume: Key,
-  pub increase_volume: Key,
-  pub toggle_playback: Key,
-  pub seek_backwards: Key,
-  pub seek_forwards: Key,
-  pub next_track: Key,
-  pub previous_track: Key,
-  pub help: Key,
-  pub shuffle: Key,
-  pub repeat: Key,
-  pub search: Key,
-  pub submit: Key,
-  pub copy_song_url: Key,
-  pub copy_album_url: Key,
-  pub audio_analysis: Key,
-  pub basic_view: Key,
-  pub add_item_to_queue: Key,
+const GLOBAL: &[ActiveBlock] = &[];
+
+macro_rules! key_bindings {
+  ($($name: ident: $key: expr, $section: expr, $blocks: expr, $description: expr;)*) => {
+    #[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct KeyBindingsString {
+      leader: Option<String>,
+      $($name: Option<String>,)*
+    }
+
+    #[derive(Clone)]
+    pub struct KeyBindings {
+      // Stands for `<leader>` in chords
+      pub leader: Key,
+      $(pub $name: Key,)*
+    }
+
+    impl Default for KeyBindings {
+      fn default() -> Self {
+        KeyBindings {
+          leader: Key::Char('\\'),
+          $($name: $key,)*
+        }
+      }
+    }
+
+    impl KeyBindings {
+      /// Every action and its key, in the order of the help menu
+      pub fn get_bindings(&self) -> Vec<KeyBinding> {
+        vec![$(KeyBinding {
+          name: stringify!($name),
+          key: self.$name.clone(),
+          description: $description,
+          section: $section,
+          blocks: $blocks,
+        },)*]
+      }
+
+      fn load(&mut self, keybindings: KeyBindingsString) -> Result<()> {
+        if let Some(leader) = keybindings.leader {
+          self.leader = parse_key(leader)?;
+          check_reserved_keys(&self.leader)?;
+        }
+        $(if let Some(key_string) = keybindings.$name {
+          self.$name = parse_key_binding(key_string, &self.leader)?;
+          // Only the first key of a chord is taken before the other handlers see it
+          check_reserved_keys(&self.$name.get_keys()[0])?;
+        })*
+        Ok(())
+      }
+    }
+  };
+}
+
+key_bindings! {
+  next_page: Key::Ctrl('d'), "Pagination", GLOBAL, "Scroll down to next result page";
+  previous_page: Key::Ctrl('u'), "Pagination", GLOBAL, "Scroll up to previous result page";
+  jump_to_start: Key::Ctrl('a'), "Pagination", GLOBAL, "Jump to start of playlist";
+  jump_to_end: Key::Ctrl('e'), "Pagination", GLOBAL, "Jump to end of playlist";
+  jump_to_album: Key::Char('a'), "General", GLOBAL, "Jump to currently playing album";
+  jump_to_artist_album: Key::Char('A'), "General", GLOBAL,
+    "Jump to currently playing artist's album list";
+  jump_to_context: Key::Char('o'), "General", GLOBAL, "Jump to current play context";
+  increase_volume: Key::Char('+'), "General", GLOBAL, "Increase volume";
+  decrease_volume: Key::Char('-'), "General", GLOBAL, "Decrease volume";
+  next_track: Key::Char('n'), "General", GLOBAL, "Skip to next track";
+  previous_track: Key::Char('p'), "General", GLOBAL, "Skip to previous track";
+  seek_backwards: Key::Char('<'), "General", GLOBAL, "Seek backwards";
+  seek_forwards: Key::Char('>'), "General", GLOBAL, "Seek forwards";
+  shuffle: Key::Ctrl('s'), "General", GLOBAL, "Toggle shuffle";
+  copy_song_url: Key::Char('c'), "General", GLOBAL, "Copy url to currently playing song/episode";
+  copy_album_url: Key::Char('C'), "General", GLOBAL, "Copy url to currently playing album/show";
+  repeat: Key::Ctrl('r'), "General", GLOBAL, "Cycle repeat mode";
+  move_left: Key::Char('h'), "General", GLOBAL,
+    "Move selection left (also <Left Arrow Key> | <Ctrl+b>)";
+  move_down: Key::Char('j'), "General", GLOBAL,
+    "Move selection down (also <Down Arrow Key> | <Ctrl+n>)";
+  move_up: Key::Char('k'), "General", GLOBAL, "Move selection up (also <Up Arrow Key> | <Ctrl+p>)";
+  move_right: Key::Char('l'), "General", GLOBAL,
+    "Move selection right (also <Right Arrow Key> | <Ctrl+f>)";
+  move_to_top: Key::Char('H'), "General", GLOBAL, "Move selection to top of list";
+  move_to_middle: Key::Char('M'), "General", GLOBAL, "Move selection to middle of list";
+  move_to_bottom: Key::Char('L'), "General", GLOBAL, "Move selection to bottom of list";
+  search: Key::Char('/'), "General", GLOBAL, "Enter input for search";
+  toggle_playback: Key::Char(' '), "General", GLOBAL, "Pause/Resume playback";
+  audio_analysis: Key::Char('v'), "General", GLOBAL, "Go to audio analysis screen";
+  basic_view: Key::Char('B'), "General", GLOBAL, "Go to playbar only screen (basic view)";
+  show_queue: Key::Char('Q'), "General", GLOBAL, "Show the play queue";
+  show_lyrics: Key::Char('y'), "General", GLOBAL, "Show the lyrics of the playing track";
+  show_stats: Key::Char('T'), "General", GLOBAL, "Show the statistics of the listening history";
+  filter: Key::Char('f'), "General", GLOBAL, "Filter the rows of the current list or table";
+  sort: Key::Char('O'), "General", GLOBAL, "Cycle the sort of the track, album or episode table";
+  back: Key::Char('q'), "General", GLOBAL, "Go back or exit when nowhere left to back to";
+  manage_devices: Key::Char('d'), "General", GLOBAL, "Select device to play music on";
+  help: Key::Char('?'), "General", GLOBAL, "Show this help";
+  command_palette: Key::Char(':'), "General", GLOBAL,
+    "Open the command palette to search the actions of the current view";
+  submit: Key::Enter, "Selected block", GLOBAL, "Start playback or enter album/artist/playlist";
+  save: Key::Char('s'), "Selected block", &[
+    ActiveBlock::TrackTable,
+    ActiveBlock::AlbumTracks,
+    ActiveBlock::EpisodeTable,
+    ActiveBlock::RecentlyPlayed,
+    ActiveBlock::Queue,
+    ActiveBlock::PlayBar,
+    ActiveBlock::BasicView,
+  ], "Save track or episode in list or table, or follow the show of the episodes";
+  save_context: Key::Char('w'), "Selected block", &[
+    ActiveBlock::AlbumTracks,
+    ActiveBlock::ArtistBlock,
+    ActiveBlock::SearchResultBlock,
+  ], "Save (like) album or follow artist/playlist/show";
+  delete: Key::Char('D'), "Selected block", &[
+    ActiveBlock::AlbumList,
+    ActiveBlock::Artists,
+    ActiveBlock::ArtistBlock,
+    ActiveBlock::EpisodeTable,
+    ActiveBlock::MyPlaylists,
+    ActiveBlock::Podcasts,
+    ActiveBlock::Queue,
+    ActiveBlock::SearchResultBlock,
+    ActiveBlock::TrackTable,
+  ], "Delete saved album/playlist/show, unfollow artist or remove track from playlist/queue";
+  play_recommendations: Key::Char('r'), "Selected block", &[
+    ActiveBlock::AlbumTracks,
+    ActiveBlock::Artists,
+    ActiveBlock::ArtistBlock,
+    ActiveBlock::RecentlyPlayed,
+    ActiveBlock::SearchResultBlock,
+    ActiveBlock::TrackTable,
+  ], "Play recommendations for song/artist";
+  play_artist: Key::Char('e'), "Library -> Artists", &[ActiveBlock::Artists],
+    "Play all tracks for artist";
+  create_playlist: Key::Char('N'), "Playlist", &[ActiveBlock::MyPlaylists],
+    "Create a new playlist";
+  rename_playlist: Key::Char('R'), "Playlist", &[ActiveBlock::MyPlaylists], "Rename playlist";
+  describe_playlist: Key::Char('E'), "Playlist", &[ActiveBlock::MyPlaylists],
+    "Change playlist description";
+  move_track_up: Key::Char('K'), "Selected Playlist", &[ActiveBlock::TrackTable],
+    "Move track up in playlist";
+  move_track_down: Key::Char('J'), "Selected Playlist", &[ActiveBlock::TrackTable],
+    "Move track down in playlist";
+  play_random_song: Key::Char('S'), "Selected Playlist", &[ActiveBlock::TrackTable],
+    "Play random song in playlist";
+  clear_queue: Key::Char('x'), "Queue", &[ActiveBlock::Queue], "Clear entries queued from spt";
+  cycle_stats_period: Key::Char('t'), "Statistics", &[ActiveBlock::Stats],
+    "Cycle the period of the statistics (week, month, year, all time)";
+  toggle_episode_order: Key::Char('S'), "Selected Show", &[ActiveBlock::EpisodeTable],
+    "Toggle sort order of podcast episodes";
+  add_item_to_queue: Key::Char('z'), "Hovered over track", GLOBAL, "Add track to queue";
+  add_to_playlist: Key::Char('P'), "Hovered over track", GLOBAL, "Add track to playlist";
+}
+
+impl KeyBindings {
+  /// The bindings that take more than one key
+  pub fn get_chords(&self) -> Vec<Key> {
+    self
+      .get_bindings()
+      .into_iter()
+      .map(|binding| binding.key)
+      .filter(|key| matches!(key, Key::Chord(_)))
+      .collect()
+  }
+
+  /// Whether the key is bound on its own or starts a chord
+  pub fn is_bound(&self, key: &Key) -> bool {
+    self
+      .get_bindings()
+      .iter()
+      .any(|binding| binding.key.get_keys()[0] == *key)
+  }
+
+  // Two actions can't share a key, or the start of a chord, in a block they are both handled in
+  fn check_conflicts(&self) -> Result<()> {
+    let bindings = self.get_bindings();
+    for (index, binding) in bindings.iter().enumerate() {
+      for other in &bindings[index + 1..] {
+        let shares_block = binding.blocks.is_empty()
+          || other.blocks.is_empty()
+          || binding
+            .blocks
+            .iter()
+            .any(|block| other.blocks.contains(block));
+        let (keys, other_keys) = (binding.key.get_keys(), other.key.get_keys());
+        if shares_block && (keys.starts_with(other_keys) || other_keys.starts_with(keys)) {
+          return Err(anyhow!(
+            "The keys of {} ({}) and {} ({}) conflict",
+            binding.name,
+            binding.key,
+            other.name,
+            other.key
+          ));
+        }
+      }
+    }
+    Ok(())
+  }
 }
 
 #[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -220,6 +549,12 @@ pub struct BehaviorConfigString {
   pub playing_icon: Option<String>,
   pub paused_icon: Option<String>,
   pub set_window_title: Option<bool>,
+  pub lyrics_dir: Option<String>,
+  pub load_full_playlists: Option<bool>,
+  pub enable_notifications: Option<bool>,
+  pub notification_format: Option<String>,
+  pub show_album_art: Option<bool>,
+  pub album_art_protocol: Option<String>,
 }
 
 #[derive(Clone)]
@@ -237,6 +572,14 @@ pub struct BehaviorConfig {
   pub playing_icon: String,
   pub paused_icon: String,
   pub set_window_title: bool,
+  pub lyrics_dir: Option<PathBuf>,
+  pub load_full_playlists: bool,
+  pub enable_notifications: bool,
+  // Uses the placeholders of `spt --format`, the first line is the summary of the notification
+  pub notification_format: String,
+  pub show_album_art: bool,
+  // Detected from the terminal if not set
+  pub album_art_protocol: Option<GraphicsProtocol>,
 }
 
 #[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -244,6 +587,8 @@ pub struct UserConfigString {
   keybindings: Option<KeyBindingsString>,
   behavior: Option<BehaviorConfigString>,
   theme: Option<UserTheme>,
+  devices: Option<DeviceConfig>,
+  scrobbling: Option<ScrobblingConfig>,
 }
 
 #[derive(Clone)]
@@ -251,6 +596,8 @@ pub struct UserConfig {
   pub keys: KeyBindings,
   pub theme: Theme,
   pub behavior: BehaviorConfig,
+  pub devices: DeviceConfig,
+  pub scrobbling: ScrobblingConfig,
   pub path_to_config: Option<UserConfigPaths>,
 }
 
@@ -258,34 +605,7 @@ impl UserConfig {
   pub fn new() -> UserConfig {
     UserConfig {
       theme: Default::default(),
-      keys: KeyBindings {
-        back: Key::Char('q'),
-        next_page: Key::Ctrl('d'),
-        previous_page: Key::Ctrl('u'),
-        jump_to_start: Key::Ctrl('a'),
-        jump_to_end: Key::Ctrl('e'),
-        jump_to_album: Key::Char('a'),
-        jump_to_artist_album: Key::Char('A'),
-        jump_to_context: Key::Char('o'),
-        manage_devices: Key::Char('d'),
-        decrease_volume: Key::Char('-'),
-        increase_volume: Key::Char('+'),
-        toggle_playback: Key::Char(' '),
-        seek_backwards: Key::Char('<'),
-        seek_forwards: Key::Char('>'),
-        next_track: Key::Char('n'),
-        previous_track: Key::Char('p'),
-        help: Key::Char('?'),
-        shuffle: Key::Ctrl('s'),
-        repeat: Key::Ctrl('r'),
-        search: Key::Char('/'),
-        submit: Key::Enter,
-        copy_song_url: Key::Char('c'),
-        copy_album_url: Key::Char('C'),
-        audio_analysis: Key::Char('v'),
-        basic_view: Key::Char('B'),
-        add_item_to_queue: Key::Char('z'),
-      },
+      keys: KeyBindings::default(),
       behavior: BehaviorConfig {
         seek_milliseconds: 5 * 1000,
         volume_increment: 10,
@@ -300,7 +620,16 @@ impl UserConfig {
         playing_icon: "▶".to_string(),
         paused_icon: "⏸".to_string(),
         set_window_title: true,
+        lyrics_dir: dirs::home_dir()
+          .map(|home| home.join(CONFIG_DIR).join(APP_CONFIG_DIR).join(LYRICS_DIR)),
+        load_full_playlists: false,
+        enable_notifications: false,
+        notification_format: "%t\n%a - %b".to_string(),
+        show_album_art: false,
+        album_art_protocol: None,
       },
+      devices: DeviceConfig::default(),
+      scrobbling: ScrobblingConfig::default(),
       path_to_config: None,
     }
   }
@@ -333,54 +662,62 @@ impl UserConfig {
   }
 
   pub fn load_keybindings(&mut self, keybindings: KeyBindingsString) -> Result<()> {
-    macro_rules! to_keys {
-      ($name: ident) => {
-        if let Some(key_string) = keybindings.$name {
-          self.keys.$name = parse_key(key_string)?;
-          check_reserved_keys(self.keys.$name)?;
-        }
-      };
+    self.keys.load(keybindings)?;
+    self.keys.check_conflicts()
+  }
+
+  pub fn load_theme(&mut self, theme: UserTheme) -> Result<()> {
+    if let Some(name) = &theme.name {
+      self.theme = Theme::default();
+      let preset = self.get_theme_preset(name)?;
+      self.apply_theme_items(preset)?;
     }
+    self.apply_theme_items(theme)
+  }
 
-    to_keys!(back);
-    to_keys!(next_page);
-    to_keys!(previous_page);
-    to_keys!(jump_to_start);
-    to_keys!(jump_to_end);
-    to_keys!(jump_to_album);
-    to_keys!(jump_to_artist_album);
-    to_keys!(jump_to_context);
-    to_keys!(manage_devices);
-    to_keys!(decrease_volume);
-    to_keys!(increase_volume);
-    to_keys!(toggle_playback);
-    to_keys!(seek_backwards);
-    to_keys!(seek_forwards);
-    to_keys!(next_track);
-    to_keys!(previous_track);
-    to_keys!(help);
-    to_keys!(shuffle);
-    to_keys!(repeat);
-    to_keys!(search);
-    to_keys!(submit);
-    to_keys!(copy_song_url);
-    to_keys!(copy_album_url);
-    to_keys!(audio_analysis);
-    to_keys!(basic_view);
-    to_keys!(add_item_to_queue);
+  // Reads the named theme from the themes directory, falling back to the built-in presets
+  fn get_theme_preset(&self, name: &str) -> Result<UserTheme> {
+    let theme_file = self
+      .get_themes_dir()
+      .map(|dir| dir.join(format!("{}.{}", name, THEME_FILE_EXTENSION)))
+      .filter(|path| path.exists());
+    let theme_string = match theme_file {
+      Some(path) => fs::read_to_string(path)?,
+      None => THEME_PRESETS
+        .iter()
+        .find(|(preset, _)| *preset == name)
+        .map(|(_, theme_string)| theme_string.to_string())
+        .ok_or_else(|| anyhow!("Theme {} not found", name))?,
+    };
+    if theme_string.trim().is_empty() {
+      return Ok(UserTheme::default());
+    }
+    let theme: UserTheme = serde_yaml::from_str(&theme_string)?;
+    if theme.name.is_some() {
+      return Err(anyhow!("Theme {} can't be based on another theme", name));
+    }
+    Ok(theme)
+  }
 
-    Ok(())
+  pub fn get_themes_dir(&self) -> Option<PathBuf> {
+    let paths = self.path_to_config.as_ref()?;
+    Some(paths.config_file_path.parent()?.join(THEMES_DIR))
   }
 
-  pub fn load_theme(&mut self, theme: UserTheme) -> Result<()> {
+  fn apply_theme_items(&mut self, theme: UserTheme) -> Result<()> {
     macro_rules! to_theme_item {
       ($name: ident) => {
+        to_theme_item!($name, fg)
+      };
+      ($name: ident, $color: ident) => {
         if let Some(theme_item) = theme.$name {
-          self.theme.$name = parse_theme_item(&theme_item)?;
+          self.theme.$name = parse_theme_style(&theme_item, Style::$color)?;
         }
       };
     }
 
+    to_theme_item!(analysis_bar);
+    to_theme_item!(analysis_bar_text);
     to_theme_item!(active);
     to_theme_item!(banner);
     to_theme_item!(error_border);
@@ -388,7 +725,7 @@ impl UserConfig {
     to_theme_item!(hint);
     to_theme_item!(hovered);
     to_theme_item!(inactive);
-    to_theme_item!(playbar_background);
+    to_theme_item!(playbar_background, bg);
     to_theme_item!(playbar_progress);
     to_theme_item!(playbar_progress_text);
     to_theme_item!(playbar_text);
@@ -461,6 +798,30 @@ impl UserConfig {
       self.behavior.set_window_title = set_window_title;
     }
 
+    if let Some(lyrics_dir) = behavior_config.lyrics_dir {
+      self.behavior.lyrics_dir = Some(expand_home_dir(&lyrics_dir));
+    }
+
+    if let Some(load_full_playlists) = behavior_config.load_full_playlists {
+      self.behavior.load_full_playlists = load_full_playlists;
+    }
+
+    if let Some(enable_notifications) = behavior_config.enable_notifications {
+      self.behavior.enable_notifications = enable_notifications;
+    }
+
+    if let Some(notification_format) = behavior_config.notification_format {
+      self.behavior.notification_format = notification_format;
+    }
+
+    if let Some(show_album_art) = behavior_config.show_album_art {
+      self.behavior.show_album_art = show_album_art;
+    }
+
+    if let Some(album_art_protocol) = behavior_config.album_art_protocol {
+      self.behavior.album_art_protocol = GraphicsProtocol::parse(&album_art_protocol)?;
+    }
+
     Ok(())
   }
 
@@ -491,6 +852,12 @@ impl UserConfig {
       if let Some(theme) = config_yml.theme {
         self.load_theme(theme)?;
       }
+      if let Some(devices) = config_yml.devices {
+        self.devices = devices;
+      }
+      if let Some(scrobbling) = config_yml.scrobbling {
+        self.scrobbling = scrobbling;
+      }
 
       Ok(())
     } else {
@@ -498,6 +865,15 @@ impl UserConfig {
     }
   }
 
+  /// Reads the config file again into a new config, so options removed from it go back to their
+  /// defaults
+  pub fn reload(&self) -> Result<UserConfig> {
+    let mut user_config = UserConfig::new();
+    user_config.path_to_config = self.path_to_config.clone();
+    user_config.load_config()?;
+    Ok(user_config)
+  }
+
   pub fn padded_liked_icon(&self) -> String {
     format!("{} ", &self.behavior.liked_icon)
   }
@@ -522,17 +898,29 @@ fn parse_theme_item(theme_item: &str) -> Result<Color> {
     "LightMagenta" => Color::LightMagenta,
     "LightCyan" => Color::LightCyan,
     "White" => Color::White,
+    _ if theme_item.starts_with('#') => {
+      let hex = &theme_item[1..];
+      if hex.len() != 6 {
+        return Err(anyhow!("Unexpected hex color {}", theme_item));
+      }
+      let rgb = u32::from_str_radix(hex, 16)?;
+      Color::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+    }
     _ => {
       let colors = theme_item.split(',').collect::<Vec<&str>>();
-      if let (Some(r), Some(g), Some(b)) = (colors.get(0), colors.get(1), colors.get(2)) {
-        Color::Rgb(
+      match colors.as_slice() {
+        [r, g, b] => Color::Rgb(
           r.trim().parse::<u8>()?,
           g.trim().parse::<u8>()?,
           b.trim().parse::<u8>()?,
-        )
-      } else {
-        println!("Unexpected color {}", theme_item);
-        Color::Black
+        ),
+        [index] => Color::Indexed(
+          index
+            .trim()
+            .parse::<u8>()
+            .map_err(|_| anyhow!("Unexpected color {}", theme_item))?,
+        ),
+        _ => return Err(anyhow!("Unexpected color {}", theme_item)),
       }
     }
   };
@@ -540,6 +928,46 @@ fn parse_theme_item(theme_item: &str) -> Result<Color> {
   Ok(color)
 }
 
+fn parse_user_color(color: &UserColor) -> Result<Color> {
+  match color {
+    UserColor::Indexed(index) => Ok(Color::Indexed(*index)),
+    UserColor::Name(name) => parse_theme_item(name),
+  }
+}
+
+// A plain color is set with `color`, as the foreground of most items
+fn parse_theme_style(
+  theme_item: &UserThemeItem,
+  color: fn(Style, Color) -> Style,
+) -> Result<Style> {
+  let user_style = match theme_item {
+    UserThemeItem::Color(user_color) => {
+      return Ok(color(Style::default(), parse_user_color(user_color)?))
+    }
+    UserThemeItem::Style(user_style) => user_style,
+  };
+  let mut style = Style::default();
+  if let Some(fg) = &user_style.fg {
+    style = style.fg(parse_user_color(fg)?);
+  }
+  if let Some(bg) = &user_style.bg {
+    style = style.bg(parse_user_color(bg)?);
+  }
+  let modifiers = [
+    (user_style.bold, Modifier::BOLD),
+    (user_style.italic, Modifier::ITALIC),
+    (user_style.underline, Modifier::UNDERLINED),
+  ];
+  for (enabled, modifier) in modifiers.iter() {
+    style = match enabled {
+      Some(true) => style.add_modifier(*modifier),
+      Some(false) => style.remove_modifier(*modifier),
+      None => style,
+    };
+  }
+  Ok(style)
+}
+
 #[cfg(test)]
 mod tests {
   #[test]
@@ -583,6 +1011,108 @@ mod tests {
       parse_theme_item("23, 43, 45").unwrap(),
       Color::Rgb(23, 43, 45)
     );
+    assert_eq!(
+      parse_theme_item("#1d2021").unwrap(),
+      Color::Rgb(0x1d, 0x20, 0x21)
+    );
+    assert_eq!(parse_theme_item("208").unwrap(), Color::Indexed(208));
+    assert!(parse_theme_item("#12345").is_err());
+    assert!(parse_theme_item("Purple").is_err());
+  }
+
+  #[test]
+  fn load_theme_applies_items_on_top_of_preset() {
+    use super::{UserConfig, UserTheme};
+    use tui::style::{Color, Modifier, Style};
+
+    let theme: UserTheme = serde_yaml::from_str(
+      r##"
+name: monochrome
+text: 244
+header: { fg: "#ff0000", bold: true, underline: true }
+playbar_background: Blue
+"##,
+    )
+    .unwrap();
+    let mut config = UserConfig::new();
+    config.load_theme(theme).unwrap();
+
+    assert_eq!(config.theme.text, Style::default().fg(Color::Indexed(244)));
+    assert_eq!(
+      config.theme.header,
+      Style::default()
+        .fg(Color::Rgb(255, 0, 0))
+        .add_modifier(Modifier::BOLD | Modifier::UNDERLINED)
+    );
+    assert_eq!(
+      config.theme.playbar_background,
+      Style::default().bg(Color::Blue)
+    );
+    // Items that aren't set come from the preset
+    assert_eq!(
+      config.theme.selected,
+      Style::default().fg(Color::Black).bg(Color::White)
+    );
+
+    let unknown: UserTheme = serde_yaml::from_str("name: unknown").unwrap();
+    assert!(config.load_theme(unknown).is_err());
+  }
+
+  #[test]
+  fn theme_presets_parse() {
+    use super::{UserConfig, UserTheme, THEME_PRESETS};
+
+    for (name, _) in THEME_PRESETS {
+      let mut config = UserConfig::new();
+      let theme = UserTheme {
+        name: Some(name.to_string()),
+        ..Default::default()
+      };
+      assert!(config.load_theme(theme).is_ok(), "theme {}", name);
+    }
+  }
+
+  #[test]
+  fn test_parse_key_binding() {
+    use super::parse_key_binding;
+    use crate::event::Key;
+    let leader = Key::Char(',');
+    assert_eq!(
+      parse_key_binding(String::from(" "), &leader).unwrap(),
+      Key::Char(' ')
+    );
+    assert_eq!(
+      parse_key_binding(String::from("ctrl-d"), &leader).unwrap(),
+      Key::Ctrl('d')
+    );
+    let chord = parse_key_binding(String::from("g a"), &leader).unwrap();
+    assert_eq!(chord, Key::chord(&[Key::Char('g'), Key::Char('a')]));
+    assert_eq!(chord.to_string(), "g a");
+    assert_eq!(
+      parse_key_binding(String::from("<leader> ctrl-n"), &leader).unwrap(),
+      Key::chord(&[leader.clone(), Key::Ctrl('n')])
+    );
+    assert!(parse_key_binding(String::from("a b c d e"), &leader).is_err());
+  }
+
+  #[test]
+  fn test_key_binding_conflicts() {
+    use super::{KeyBindingsString, UserConfig};
+
+    let mut config = UserConfig::new();
+    assert!(config.keys.check_conflicts().is_ok());
+
+    // Actions of different blocks can share a key
+    let keybindings: KeyBindingsString = serde_yaml::from_str("clear_queue: r").unwrap();
+    assert!(config.load_keybindings(keybindings).is_ok());
+
+    let keybindings: KeyBindingsString = serde_yaml::from_str("clear_queue: s").unwrap();
+    assert!(config.load_keybindings(keybindings).is_err());
+
+    // A key that starts a chord can't be bound on its own
+    let keybindings: KeyBindingsString =
+      serde_yaml::from_str("jump_to_start: g g\njump_to_end: g").unwrap();
+    assert!(config.load_keybindings(keybindings).is_err());
   }
 
   #[test]
@@ -591,7 +1121,7 @@ mod tests {
     use crate::event::Key;
 
     assert!(
-      check_reserved_keys(Key::Enter).is_err(),
+      check_reserved_keys(&Key::Enter).is_err(),
       "Enter key should be reserved"
     );
   }